@@ -8,6 +8,9 @@ import os
 import sys
 import time
 import json
+import hmac
+import hashlib
+import uuid
 import logging
 import subprocess
 import shutil
@@ -216,24 +219,39 @@ class SubmissionHandler(FileSystemEventHandler):
     def report_grade(self, student_id, assignment_name, grades):
         """Report grades to the main application via webhook."""
         if not WEBHOOK_SECRET:
-            logger.warning("NBGRADER_WEBHOOK_SECRET not set, using empty secret")
+            logger.warning("NBGRADER_WEBHOOK_SECRET not set, sending an unsigned webhook")
         
         payload = {
             'assignmentName': assignment_name,
             'studentId': student_id,
             'score': grades['score'],
             'maxScore': grades['max_score'],
-            'webhookSecret': WEBHOOK_SECRET,
             'timestamp': time.strftime('%Y-%m-%dT%H:%M:%SZ', time.gmtime())
         }
         
         logger.info(f"Reporting grade to {WEBHOOK_URL}: {payload}")
         
+        # The backend verifies an HMAC-SHA256 over "<timestamp>.<delivery id>.<body>"
+        # and processes each delivery ID only once
+        body = json.dumps(payload).encode('utf-8')
+        timestamp = str(int(time.time()))
+        delivery_id = str(uuid.uuid4())
+        signature = hmac.new(
+            WEBHOOK_SECRET.encode('utf-8'),
+            timestamp.encode('utf-8') + b'.' + delivery_id.encode('utf-8') + b'.' + body,
+            hashlib.sha256
+        ).hexdigest()
+        
         try:
             response = requests.post(
                 WEBHOOK_URL,
-                json=payload,
-                headers={'Content-Type': 'application/json'},
+                data=body,
+                headers={
+                    'Content-Type': 'application/json',
+                    'X-Webhook-Timestamp': timestamp,
+                    'X-Webhook-Signature': f'sha256={signature}',
+                    'X-Webhook-Delivery': delivery_id,
+                },
                 timeout=30
             )
             
@@ -0,0 +1,34 @@
+-- Roles are data rather than a CHECK constraint so new staff roles only need rows here
+CREATE TABLE IF NOT EXISTS roles (
+    name VARCHAR(50) PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO roles (name, description) VALUES
+    ('user', 'Club member taking challenges'),
+    ('grader', 'Reviews and grades submissions'),
+    ('content_editor', 'Manages resources and certificates'),
+    ('admin', 'Full access, including assigning roles')
+ON CONFLICT (name) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role VARCHAR(50) NOT NULL REFERENCES roles(name) ON DELETE CASCADE ON UPDATE CASCADE,
+    permission VARCHAR(50) NOT NULL,
+    PRIMARY KEY (role, permission)
+);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('grader', 'grade_submissions'),
+    ('content_editor', 'manage_resources'),
+    ('content_editor', 'manage_certificates'),
+    ('admin', 'grade_submissions'),
+    ('admin', 'manage_challenges'),
+    ('admin', 'manage_resources'),
+    ('admin', 'manage_certificates'),
+    ('admin', 'manage_roles')
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_role_check;
+ALTER TABLE users
+ADD CONSTRAINT users_role_fkey FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
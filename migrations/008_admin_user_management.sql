@@ -0,0 +1,6 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS suspended_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS suspended_reason TEXT;
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'manage_users')
+ON CONFLICT DO NOTHING;
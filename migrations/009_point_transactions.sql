@@ -0,0 +1,39 @@
+-- Every change to a user's points; users.points is the cached sum of this ledger
+CREATE TABLE IF NOT EXISTS point_transactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    amount INTEGER NOT NULL CHECK (amount <> 0),
+    reason TEXT NOT NULL,
+    submission_id UUID REFERENCES challenge_submissions(id) ON DELETE SET NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_point_transactions_user_created
+ON point_transactions(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_point_transactions_submission_id
+ON point_transactions(submission_id);
+
+-- Backfill from credited submissions
+INSERT INTO point_transactions (user_id, amount, reason, submission_id, created_by, created_at)
+SELECT
+    cs.user_id,
+    cs.points_awarded,
+    'Graded: ' || c.title || ' (attempt ' || cs.attempt_number || ')',
+    cs.id,
+    cs.manual_graded_by,
+    COALESCE(cs.graded_at, cs.updated_at)
+FROM challenge_submissions cs
+JOIN challenges c ON c.id = cs.challenge_id
+WHERE cs.points_credited AND cs.points_awarded <> 0;
+
+-- Whatever the ledger cannot explain predates it
+INSERT INTO point_transactions (user_id, amount, reason, created_at)
+SELECT u.id, u.points - COALESCE(t.total, 0), 'Opening balance', u.created_at
+FROM users u
+LEFT JOIN (
+    SELECT user_id, SUM(amount) AS total
+    FROM point_transactions
+    GROUP BY user_id
+) t ON t.user_id = u.id
+WHERE u.points <> COALESCE(t.total, 0);
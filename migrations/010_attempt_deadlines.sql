@@ -0,0 +1,27 @@
+-- Deadline fixed when an attempt starts: started_at + the notebook's time limit,
+-- capped at the challenge end date. NULL means the attempt is not timed.
+ALTER TABLE challenge_submissions ADD COLUMN IF NOT EXISTS deadline_at TIMESTAMPTZ;
+
+UPDATE challenge_submissions cs
+SET deadline_at = LEAST(
+    cs.started_at + make_interval(mins => cn.time_limit_minutes),
+    COALESCE(c.end_date, 'infinity'::TIMESTAMPTZ)
+)
+FROM challenge_notebooks cn, challenges c
+WHERE cn.id = cs.notebook_id
+  AND c.id = cs.challenge_id
+  AND cs.status = 'in_progress'
+  AND cs.started_at IS NOT NULL
+  AND cn.time_limit_minutes > 0;
+
+ALTER TABLE challenge_submissions
+DROP CONSTRAINT IF EXISTS submission_status_check;
+
+ALTER TABLE challenge_submissions
+ADD CONSTRAINT submission_status_check CHECK (
+    status IN ('not_started', 'in_progress', 'grading_pending', 'graded', 'error', 'expired')
+);
+
+CREATE INDEX IF NOT EXISTS idx_challenge_submissions_in_progress_deadline
+ON challenge_submissions(deadline_at)
+WHERE status = 'in_progress';
@@ -0,0 +1,11 @@
+-- How nbgrader results are turned into points for each challenge:
+--   manual           scores are stored and an admin grades the submission (previous behaviour)
+--   auto             the nbgrader score is converted to points and credited immediately
+--   auto_with_review the converted points are proposed but only credited once an admin grades
+ALTER TABLE challenges
+ADD COLUMN IF NOT EXISTS grading_mode VARCHAR(20) NOT NULL DEFAULT 'manual';
+
+ALTER TABLE challenges
+ADD CONSTRAINT challenges_grading_mode_check CHECK (
+    grading_mode IN ('manual', 'auto', 'auto_with_review')
+);
@@ -0,0 +1,30 @@
+-- Outbox for calls to the grading service. Jobs are written in the same transaction
+-- as the change that needs them and delivered by a background worker with backoff.
+CREATE TABLE IF NOT EXISTS grading_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(40) NOT NULL,
+    payload JSONB NOT NULL,
+    submission_id UUID REFERENCES challenge_submissions(id) ON DELETE SET NULL,
+    notebook_id INTEGER REFERENCES challenge_notebooks(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 10,
+    last_error TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT grading_jobs_kind_check CHECK (
+        kind IN ('prepare_notebook', 'submit', 'setup_assignment')
+    ),
+    CONSTRAINT grading_jobs_status_check CHECK (
+        status IN ('pending', 'succeeded', 'failed')
+    )
+);
+
+CREATE INDEX IF NOT EXISTS idx_grading_jobs_due
+ON grading_jobs(next_attempt_at)
+WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_grading_jobs_status_created
+ON grading_jobs(status, created_at DESC);
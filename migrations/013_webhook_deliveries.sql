@@ -0,0 +1,23 @@
+-- Every incoming webhook delivery, accepted or not, for debugging and replay protection.
+-- A delivery ID can only be processed once; rejected and failed deliveries may be retried.
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source VARCHAR(40) NOT NULL,
+    delivery_id VARCHAR(255),
+    status VARCHAR(20) NOT NULL,
+    signature_valid BOOLEAN NOT NULL DEFAULT FALSE,
+    error TEXT,
+    body TEXT NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    CONSTRAINT webhook_deliveries_status_check CHECK (
+        status IN ('processing', 'processed', 'rejected', 'failed')
+    )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_webhook_deliveries_once
+ON webhook_deliveries(source, delivery_id)
+WHERE status IN ('processing', 'processed');
+
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_received
+ON webhook_deliveries(source, received_at DESC);
@@ -0,0 +1,29 @@
+-- In-app notifications. Announcements to everyone are written as one row per member,
+-- so each member has their own read state.
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    -- Identifiers of what the notification is about, e.g. challengeId, for linking to it
+    data JSONB NOT NULL DEFAULT '{}'::jsonb,
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user
+ON notifications(user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_unread
+ON notifications(user_id)
+WHERE read_at IS NULL;
+
+-- One column per notification kind; a member without a row receives everything
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    submission_graded BOOLEAN NOT NULL DEFAULT TRUE,
+    challenge_published BOOLEAN NOT NULL DEFAULT TRUE,
+    certificate_published BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- Who did what through the admin API. before/after hold only the fields an update changed,
+-- or the whole target when it was created or deleted.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(100) NOT NULL,
+    target_type VARCHAR(50) NOT NULL,
+    target_id TEXT,
+    before JSONB,
+    after JSONB,
+    request_id VARCHAR(128),
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created_at
+ON audit_log(created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_actor
+ON audit_log(actor_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_target
+ON audit_log(target_type, target_id, created_at DESC);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('admin', 'view_audit_log')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,16 @@
+-- Deleting content moves it to the trash; it is only removed for good when purged
+ALTER TABLE challenges ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE challenge_notebooks ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE resources ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE certificates ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+-- A trashed notebook keeps its challenge and assignment name, so only live notebooks
+-- need to be unique. The index names match the constraints they replace.
+ALTER TABLE challenge_notebooks DROP CONSTRAINT IF EXISTS unique_challenge_notebook;
+ALTER TABLE challenge_notebooks DROP CONSTRAINT IF EXISTS challenge_notebooks_assignment_name_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS unique_challenge_notebook
+ON challenge_notebooks(challenge_id) WHERE deleted_at IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS challenge_notebooks_assignment_name_key
+ON challenge_notebooks(assignment_name) WHERE deleted_at IS NULL;
@@ -0,0 +1,22 @@
+-- Criteria a challenge can be graded against. Each criterion is scored from 0 to max_points
+-- and counts towards the final score in proportion to its weight.
+CREATE TABLE IF NOT EXISTS challenge_rubric_criteria (
+    id SERIAL PRIMARY KEY,
+    challenge_id INTEGER NOT NULL REFERENCES challenges(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL DEFAULT 0,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    weight DOUBLE PRECISION NOT NULL DEFAULT 1 CHECK (weight > 0),
+    max_points DOUBLE PRECISION NOT NULL CHECK (max_points > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_challenge_rubric_criteria_challenge
+ON challenge_rubric_criteria(challenge_id, position);
+
+-- Written feedback for the student, and the rubric as it was scored when the submission was
+-- graded, so later edits to the rubric do not rewrite past grades
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS feedback TEXT,
+ADD COLUMN IF NOT EXISTS rubric_scores JSONB;
@@ -0,0 +1,26 @@
+-- What happens to work submitted after a challenge's end date:
+--   cutoff         nothing is accepted after the end date (previous behaviour)
+--   grace          late work is accepted at full credit for late_grace_minutes
+--   linear         late_penalty_percent is taken off per late_penalty_period, pro rata
+--   stepped        late_penalty_percent is taken off for every started late_penalty_period
+--   practice_only  late work is graded but earns no points
+-- The grace window applies before any penalty starts for linear, stepped and practice_only.
+ALTER TABLE challenges
+ADD COLUMN IF NOT EXISTS late_policy VARCHAR(20) NOT NULL DEFAULT 'cutoff',
+ADD COLUMN IF NOT EXISTS late_grace_minutes INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS late_penalty_percent DOUBLE PRECISION NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS late_penalty_period VARCHAR(10) NOT NULL DEFAULT 'day';
+
+ALTER TABLE challenges
+ADD CONSTRAINT challenges_late_policy_check CHECK (
+    late_policy IN ('cutoff', 'grace', 'linear', 'stepped', 'practice_only')
+    AND late_grace_minutes >= 0
+    AND late_penalty_percent BETWEEN 0 AND 100
+    AND late_penalty_period IN ('hour', 'day')
+);
+
+-- How late a submission was and the penalty applied when its points were computed
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS late_minutes INTEGER,
+ADD COLUMN IF NOT EXISTS late_penalty_percent DOUBLE PRECISION NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS late_practice_only BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,43 @@
+-- Challenges are either JupyterHub notebooks graded by nbgrader, or "prediction" challenges
+-- where students upload a predictions CSV that is scored against a hidden ground truth
+ALTER TABLE challenges
+ADD COLUMN IF NOT EXISTS challenge_type VARCHAR(20) NOT NULL DEFAULT 'notebook';
+
+ALTER TABLE challenges
+ADD CONSTRAINT challenges_challenge_type_check CHECK (
+    challenge_type IN ('notebook', 'prediction')
+);
+
+-- The hidden test set lives in the database rather than under uploads/, which is served
+-- publicly. baseline is the metric value that earns no points.
+CREATE TABLE IF NOT EXISTS challenge_prediction_tasks (
+    id SERIAL PRIMARY KEY,
+    challenge_id INTEGER NOT NULL UNIQUE REFERENCES challenges(id) ON DELETE CASCADE,
+    metric VARCHAR(20) NOT NULL CHECK (
+        metric IN ('accuracy', 'macro_f1', 'rmse', 'mae', 'log_loss', 'auc')
+    ),
+    id_column VARCHAR(255) NOT NULL,
+    target_column VARCHAR(255) NOT NULL,
+    baseline DOUBLE PRECISION,
+    max_points INTEGER NOT NULL DEFAULT 100 CHECK (max_points >= 0),
+    ground_truth_filename VARCHAR(255) NOT NULL,
+    ground_truth BYTEA NOT NULL,
+    row_count INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Prediction submissions have no notebook
+ALTER TABLE challenge_submissions
+ALTER COLUMN notebook_id DROP NOT NULL;
+
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS metric_value DOUBLE PRECISION;
+
+-- The uploaded predictions, kept so a submission can be inspected or rescored
+CREATE TABLE IF NOT EXISTS challenge_prediction_files (
+    submission_id UUID PRIMARY KEY REFERENCES challenge_submissions(id) ON DELETE CASCADE,
+    filename VARCHAR(255) NOT NULL,
+    content BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,23 @@
+-- Prediction challenges can hold back part of the test set: while the challenge runs,
+-- submissions are ranked on the public rows only, and the private ranking is revealed once
+-- the end date passes. Without public_percent the whole test set is scored as before.
+ALTER TABLE challenge_prediction_tasks
+ADD COLUMN IF NOT EXISTS public_percent INTEGER CHECK (public_percent BETWEEN 1 AND 99);
+
+-- How many attempts each student may choose to count towards the private leaderboard
+ALTER TABLE challenge_prediction_tasks
+ADD COLUMN IF NOT EXISTS final_submissions INTEGER NOT NULL DEFAULT 2 CHECK (final_submissions >= 1);
+
+-- Set once points for the private leaderboard have been credited
+ALTER TABLE challenge_prediction_tasks
+ADD COLUMN IF NOT EXISTS revealed_at TIMESTAMPTZ;
+
+-- On a split test set, score and metric_value hold the private result
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS public_metric_value DOUBLE PRECISION;
+
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS public_score DOUBLE PRECISION;
+
+ALTER TABLE challenge_submissions
+ADD COLUMN IF NOT EXISTS final_selected BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,217 @@
+use std::str::FromStr;
+
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::{
+    config::AttemptConfig,
+    error::AppError,
+    events::{Event, publish_event},
+    grading::{GradingRequest, enqueue_grading_job},
+    live_updates::notify_submission_changed,
+    models::{ChallengeNotebook, ChallengeSubmission},
+};
+
+/// What happens to an attempt that is still in progress after its deadline and grace period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutAction {
+    /// Collect the notebook as it is and send it for grading
+    Submit,
+    /// Close the attempt without grading it
+    Expire,
+}
+
+impl TimeoutAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeoutAction::Submit => "submit",
+            TimeoutAction::Expire => "expire",
+        }
+    }
+}
+
+impl FromStr for TimeoutAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "submit" => Ok(TimeoutAction::Submit),
+            "expire" => Ok(TimeoutAction::Expire),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Deadline for an attempt started at `started_at`; attempts never run past the challenge end.
+/// Notebooks with a non-positive time limit are untimed.
+pub fn attempt_deadline(
+    started_at: OffsetDateTime,
+    time_limit_minutes: i32,
+    challenge_end: Option<OffsetDateTime>,
+) -> Option<OffsetDateTime> {
+    if time_limit_minutes <= 0 {
+        return None;
+    }
+
+    let deadline = started_at + Duration::minutes(time_limit_minutes as i64);
+
+    Some(match challenge_end {
+        Some(end_date) => deadline.min(end_date),
+        None => deadline,
+    })
+}
+
+/// Whether an attempt with this deadline can no longer be submitted, grace period included
+pub fn is_overdue(deadline_at: Option<OffsetDateTime>, settings: &AttemptConfig) -> bool {
+    deadline_at
+        .is_some_and(|deadline| OffsetDateTime::now_utc() > deadline + settings.grace_period())
+}
+
+/// Close an in-progress attempt whose deadline and grace period have passed.
+/// Returns `None` when the attempt is not overdue or was already closed by someone else.
+pub async fn close_overdue_attempt(
+    pool: &PgPool,
+    submission_id: Uuid,
+    settings: &AttemptConfig,
+) -> Result<Option<TimeoutAction>, AppError> {
+    let action = settings.timeout_action;
+    let mut tx = pool.begin().await?;
+
+    let overdue: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM challenge_submissions
+        WHERE id = $1
+          AND status = 'in_progress'
+          AND deadline_at + make_interval(secs => $2) < NOW()
+        FOR UPDATE
+        "#,
+    )
+    .bind(submission_id)
+    .bind(settings.grace_period_seconds as f64)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if overdue.is_none() {
+        return Ok(None);
+    }
+
+    match action {
+        TimeoutAction::Submit => {
+            let submission: ChallengeSubmission = sqlx::query_as(
+                "UPDATE challenge_submissions SET status = 'grading_pending', submitted_at = deadline_at, updated_at = NOW() WHERE id = $1 RETURNING *",
+            )
+            .bind(submission_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            publish_event(&mut tx, &Event::submission_submitted(&submission)).await?;
+            notify_submission_changed(&mut tx, &submission).await?;
+
+            let notebook: ChallengeNotebook = sqlx::query_as(
+                r#"
+                SELECT cn.*
+                FROM challenge_notebooks cn
+                JOIN challenge_submissions cs ON cs.notebook_id = cn.id
+                WHERE cs.id = $1
+                "#,
+            )
+            .bind(submission_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            let jupyterhub_username: Option<String> = sqlx::query_scalar(
+                r#"
+                SELECT u.jupyterhub_username
+                FROM users u
+                JOIN challenge_submissions cs ON cs.user_id = u.id
+                WHERE cs.id = $1
+                "#,
+            )
+            .bind(submission_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            // The grading worker delivers the queued call on its next poll
+            match jupyterhub_username {
+                Some(username) => {
+                    enqueue_grading_job(
+                        &mut tx,
+                        &GradingRequest::submit(&username, &notebook),
+                        Some(submission_id),
+                        Some(notebook.id),
+                    )
+                    .await?;
+                }
+                None => tracing::warn!(
+                    "Auto-submitted attempt {} has no JupyterHub user; it needs manual grading",
+                    submission_id
+                ),
+            }
+        }
+        TimeoutAction::Expire => {
+            let submission: ChallengeSubmission = sqlx::query_as(
+                "UPDATE challenge_submissions SET status = 'expired', updated_at = NOW() WHERE id = $1 RETURNING *",
+            )
+            .bind(submission_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            notify_submission_changed(&mut tx, &submission).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(action))
+}
+
+/// Close every overdue in-progress attempt; returns how many were closed
+pub async fn sweep_overdue_attempts(
+    pool: &PgPool,
+    settings: &AttemptConfig,
+) -> Result<usize, AppError> {
+    let overdue: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id
+        FROM challenge_submissions
+        WHERE status = 'in_progress'
+          AND deadline_at + make_interval(secs => $1) < NOW()
+        "#,
+    )
+    .bind(settings.grace_period_seconds as f64)
+    .fetch_all(pool)
+    .await?;
+
+    let mut closed = 0;
+    for submission_id in overdue {
+        if close_overdue_attempt(pool, submission_id, settings)
+            .await?
+            .is_some()
+        {
+            closed += 1;
+        }
+    }
+
+    Ok(closed)
+}
+
+/// Periodically close attempts whose time has run out
+pub fn spawn_attempt_sweeper(pool: PgPool, settings: AttemptConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            settings.sweep_interval_seconds,
+        ));
+
+        loop {
+            interval.tick().await;
+
+            match sweep_overdue_attempts(&pool, &settings).await {
+                Ok(0) => {}
+                Ok(closed) => tracing::info!("Closed {} overdue challenge attempts", closed),
+                Err(e) => tracing::error!("Failed to sweep overdue challenge attempts: {:?}", e),
+            }
+        }
+    });
+}
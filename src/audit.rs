@@ -0,0 +1,159 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Request},
+    http::{HeaderValue, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Correlates a request across the proxy, our logs and the audit log
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Give every request an ID, keeping one the proxy already assigned, and echo it in the response
+pub async fn assign_request_id(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .filter(|value| {
+            value.len() <= MAX_REQUEST_ID_LENGTH
+                && value.to_str().is_ok_and(|id| !id.trim().is_empty())
+        })
+        .cloned()
+        .unwrap_or_else(|| {
+            HeaderValue::from_str(&Uuid::new_v4().to_string()).expect("UUIDs are valid headers")
+        });
+
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, request_id.clone());
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+
+    response
+}
+
+/// Where a request came from, recorded with each audited action
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    pub request_id: Option<String>,
+    /// The client as reported by the reverse proxy, or the peer address when there is none
+    pub ip_address: Option<String>,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for RequestContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+
+        // Clients can send their own X-Forwarded-For, which the proxy appends to, so only
+        // the address the proxy saw is trusted: X-Real-IP, or else the last hop it added
+        let real_ip = header("x-real-ip").map(str::to_string);
+        let forwarded_for = header("x-forwarded-for")
+            .and_then(|value| value.rsplit(',').next())
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .map(str::to_string);
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        Ok(Self {
+            request_id: header(REQUEST_ID_HEADER).map(str::to_string),
+            ip_address: real_ip.or(forwarded_for).or(peer),
+        })
+    }
+}
+
+/// One admin action for the audit log
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub actor_id: Uuid,
+    /// `<target type>.<verb>`, e.g. `challenge.delete`
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: Option<String>,
+    /// The target before the action; `None` when it was created
+    pub before: Option<Value>,
+    /// The target after the action; `None` when it was deleted
+    pub after: Option<Value>,
+}
+
+/// JSON snapshot of a target for [`AuditEntry::before`] and [`AuditEntry::after`]
+pub fn snapshot<T: Serialize>(value: &T) -> Option<Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// Keep only the top-level fields that differ, so an update records what it changed
+fn diff(before: Option<Value>, after: Option<Value>) -> (Option<Value>, Option<Value>) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut changed_before = Map::new();
+            let mut changed_after = Map::new();
+            for key in before.keys().chain(after.keys()) {
+                let old = before.get(key).unwrap_or(&Value::Null);
+                let new = after.get(key).unwrap_or(&Value::Null);
+                if old != new {
+                    changed_before.insert(key.clone(), old.clone());
+                    changed_after.insert(key.clone(), new.clone());
+                }
+            }
+            (
+                Some(Value::Object(changed_before)),
+                Some(Value::Object(changed_after)),
+            )
+        }
+        (before, after) => (before, after),
+    }
+}
+
+/// Record an admin action. Run it in the transaction of the change it describes.
+pub async fn record_audit(
+    conn: &mut PgConnection,
+    request: &RequestContext,
+    entry: AuditEntry,
+) -> Result<(), AppError> {
+    let (before, after) = diff(entry.before, entry.after);
+
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log
+            (actor_id, action, target_type, target_id, before, after, request_id, ip_address)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(entry.actor_id)
+    .bind(entry.action)
+    .bind(entry.target_type)
+    .bind(entry.target_id)
+    .bind(before)
+    .bind(after)
+    .bind(&request.request_id)
+    .bind(&request.ip_address)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
@@ -4,18 +4,28 @@ use axum::{
     http::{header::AUTHORIZATION, request::Parts},
 };
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
-use std::env;
+use std::marker::PhantomData;
 use uuid::Uuid;
 
 use crate::error::AppError;
 
-static KEYS: Lazy<Keys> = Lazy::new(|| {
-    let secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    Keys::new(secret.as_bytes())
-});
+static KEYS: OnceCell<Keys> = OnceCell::new();
+
+/// Sign tokens with the configured secret; call once at startup, before serving requests
+pub fn init_jwt_secret(secret: &str) {
+    if KEYS.set(Keys::new(secret.as_bytes())).is_err() {
+        tracing::warn!("JWT secret was already initialised; keeping the first one");
+    }
+}
+
+fn keys() -> &'static Keys {
+    KEYS.get()
+        .expect("init_jwt_secret must be called before tokens are issued or checked")
+}
 
 struct Keys {
     encoding: EncodingKey,
@@ -31,24 +41,268 @@ impl Keys {
     }
 }
 
+/// Lifetime of an access token; clients renew it through `/auth/refresh`
+const ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+/// Lifetime of a single refresh token before the user has to sign in again
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
+    /// Session family the token was issued for (see `user_sessions.family_id`)
+    pub sid: Uuid,
     pub exp: i64,
 }
 
 impl Claims {
-    pub fn new(user_id: Uuid) -> Self {
+    pub fn new(user_id: Uuid, session_id: Uuid) -> Self {
         Self {
             sub: user_id.to_string(),
-            exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp(),
+            sid: session_id,
+            exp: (chrono::Utc::now() + chrono::Duration::minutes(ACCESS_TOKEN_TTL_MINUTES))
+                .timestamp(),
         }
     }
 }
 
-pub fn create_token(user_id: Uuid) -> Result<String, AppError> {
-    encode(&Header::default(), &Claims::new(user_id), &KEYS.encoding)
-        .map_err(|e| AppError::InternalError(e.into()))
+pub fn create_token(user_id: Uuid, session_id: Uuid) -> Result<String, AppError> {
+    encode(
+        &Header::default(),
+        &Claims::new(user_id, session_id),
+        &keys().encoding,
+    )
+    .map_err(|e| AppError::InternalError(e.into()))
+}
+
+/// Access token plus the refresh token that can renew it
+pub struct SessionTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// Random single-use secret for refresh, password reset and verification tokens
+fn generate_opaque_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Only hashes of opaque tokens are stored, so a database leak does not expose usable tokens
+fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+async fn insert_refresh_token<'e, E>(
+    executor: E,
+    user_id: Uuid,
+    family_id: Uuid,
+) -> Result<String, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let refresh_token = generate_opaque_token();
+    let expires_at = time::OffsetDateTime::now_utc() + time::Duration::days(REFRESH_TOKEN_TTL_DAYS);
+
+    sqlx::query(
+        "INSERT INTO user_sessions (user_id, family_id, refresh_token_hash, expires_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(user_id)
+    .bind(family_id)
+    .bind(hash_opaque_token(&refresh_token))
+    .bind(expires_at)
+    .execute(executor)
+    .await?;
+
+    Ok(refresh_token)
+}
+
+/// Start a new session for a user who just authenticated.
+/// Suspended accounts are refused here so every sign-in path rejects them.
+pub async fn create_session(pool: &PgPool, user_id: Uuid) -> Result<SessionTokens, AppError> {
+    let suspended: bool =
+        sqlx::query_scalar("SELECT suspended_at IS NOT NULL FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::AuthError)?;
+
+    if suspended {
+        return Err(AppError::AccountSuspended);
+    }
+
+    let family_id = Uuid::new_v4();
+    let refresh_token = insert_refresh_token(pool, user_id, family_id).await?;
+
+    Ok(SessionTokens {
+        access_token: create_token(user_id, family_id)?,
+        refresh_token,
+    })
+}
+
+/// Exchange a refresh token for a new token pair.
+/// Presenting a refresh token that was already rotated revokes its whole session family,
+/// since that only happens when the token has been copied.
+pub async fn rotate_session(pool: &PgPool, refresh_token: &str) -> Result<SessionTokens, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct SessionRow {
+        id: Uuid,
+        user_id: Uuid,
+        family_id: Uuid,
+        expires_at: time::OffsetDateTime,
+        rotated_at: Option<time::OffsetDateTime>,
+        revoked_at: Option<time::OffsetDateTime>,
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let session: SessionRow = sqlx::query_as(
+        r#"
+        SELECT id, user_id, family_id, expires_at, rotated_at, revoked_at
+        FROM user_sessions
+        WHERE refresh_token_hash = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(hash_opaque_token(refresh_token))
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::AuthError)?;
+
+    if session.revoked_at.is_some() {
+        return Err(AppError::AuthError);
+    }
+
+    if session.rotated_at.is_some() {
+        tracing::warn!(
+            "Refresh token reuse detected for user {}; revoking session {}",
+            session.user_id,
+            session.family_id
+        );
+        drop(tx);
+        revoke_session(pool, session.user_id, session.family_id).await?;
+        return Err(AppError::AuthError);
+    }
+
+    if session.expires_at < time::OffsetDateTime::now_utc() {
+        return Err(AppError::AuthError);
+    }
+
+    sqlx::query("UPDATE user_sessions SET rotated_at = NOW() WHERE id = $1")
+        .bind(session.id)
+        .execute(&mut *tx)
+        .await?;
+
+    let refresh_token = insert_refresh_token(&mut *tx, session.user_id, session.family_id).await?;
+
+    tx.commit().await?;
+
+    Ok(SessionTokens {
+        access_token: create_token(session.user_id, session.family_id)?,
+        refresh_token,
+    })
+}
+
+/// Revoke every refresh token of one session family
+pub async fn revoke_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = $1 AND family_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke all sessions of a user, signing them out everywhere
+pub async fn revoke_all_sessions(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    sqlx::query(
+        "UPDATE user_sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountTokenPurpose {
+    PasswordReset,
+    EmailVerification,
+}
+
+impl AccountTokenPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountTokenPurpose::PasswordReset => "password_reset",
+            AccountTokenPurpose::EmailVerification => "email_verification",
+        }
+    }
+}
+
+/// Issue a single-use emailed token, invalidating any earlier unused token for the same purpose
+pub async fn issue_account_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    purpose: AccountTokenPurpose,
+    ttl: time::Duration,
+) -> Result<String, AppError> {
+    let token = generate_opaque_token();
+    let expires_at = time::OffsetDateTime::now_utc() + ttl;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "UPDATE account_tokens SET used_at = NOW() WHERE user_id = $1 AND purpose = $2 AND used_at IS NULL",
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO account_tokens (user_id, purpose, token_hash, expires_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(user_id)
+    .bind(purpose.as_str())
+    .bind(hash_opaque_token(&token))
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(token)
+}
+
+/// Mark an emailed token as used and return its user, or `None` if it is unknown,
+/// expired, already used or issued for a different purpose
+pub async fn consume_account_token<'e, E>(
+    executor: E,
+    token: &str,
+    purpose: AccountTokenPurpose,
+) -> Result<Option<Uuid>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let user_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        UPDATE account_tokens
+        SET used_at = NOW()
+        WHERE token_hash = $1 AND purpose = $2 AND used_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+    )
+    .bind(hash_opaque_token(token))
+    .bind(purpose.as_str())
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(user_id)
 }
 
 /// Claims for JupyterHub SSO token
@@ -76,14 +330,14 @@ pub fn create_jupyterhub_token(
         purpose: "jupyterhub_sso".to_string(),
     };
 
-    encode(&Header::default(), &claims, &KEYS.encoding)
+    encode(&Header::default(), &claims, &keys().encoding)
         .map_err(|e| AppError::InternalError(e.into()))
 }
 
 /// Verify and decode a JupyterHub SSO token
 /// Returns the claims if valid
 pub fn verify_jupyterhub_token(token: &str) -> Result<JupyterHubClaims, AppError> {
-    let token_data = decode::<JupyterHubClaims>(token, &KEYS.decoding, &Validation::default())
+    let token_data = decode::<JupyterHubClaims>(token, &keys().decoding, &Validation::default())
         .map_err(|_| AppError::AuthError)?;
 
     if token_data.claims.purpose != "jupyterhub_sso" {
@@ -95,74 +349,161 @@ pub fn verify_jupyterhub_token(token: &str) -> Result<JupyterHubClaims, AppError
 
 pub struct AuthUser {
     pub user_id: Uuid,
+    pub session_id: Uuid,
 }
 
-pub struct AdminUser {
+/// A capability granted to roles through the `role_permissions` table
+pub trait Permission: Send + Sync + 'static {
+    /// Name stored in `role_permissions.permission`
+    const NAME: &'static str;
+}
+
+macro_rules! permissions {
+    ($($(#[$doc:meta])* $ty:ident => $name:literal),* $(,)?) => {
+        $(
+            $(#[$doc])*
+            pub struct $ty;
+
+            impl Permission for $ty {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+permissions! {
+    /// Review, download and grade student submissions
+    GradeSubmissions => "grade_submissions",
+    /// Create and edit challenges and their notebooks
+    ManageChallenges => "manage_challenges",
+    /// Create and edit learning resources
+    ManageResources => "manage_resources",
+    /// Create and edit certificates
+    ManageCertificates => "manage_certificates",
+    /// Assign roles to other users
+    ManageRoles => "manage_roles",
+    /// Search users, suspend accounts and adjust points
+    ManageUsers => "manage_users",
+    /// Subscribe integrations to platform events and inspect their deliveries
+    ManageWebhooks => "manage_webhooks",
+    /// Read the record of admin actions
+    ViewAuditLog => "view_audit_log",
+}
+
+/// Authenticated user whose role grants the permission `P`
+pub struct RequirePermission<P: Permission> {
     pub user_id: Uuid,
+    _permission: PhantomData<P>,
+}
+
+/// Whether the user's current role grants `permission`
+pub async fn has_permission(
+    pool: &PgPool,
+    user_id: Uuid,
+    permission: &str,
+) -> Result<bool, AppError> {
+    let granted = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM users u
+            JOIN role_permissions rp ON rp.role = u.role
+            WHERE u.id = $1 AND rp.permission = $2
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(permission)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(granted)
+}
+
+/// Decode the bearer access token and make sure its session has not been revoked
+async fn authenticate(parts: &Parts, pool: &PgPool) -> Result<(Uuid, Uuid), AppError> {
+    let bearer = parts
+        .headers
+        .get(AUTHORIZATION)
+        .ok_or(AppError::AuthError)?
+        .to_str()
+        .map_err(|_| AppError::AuthError)?
+        .strip_prefix("Bearer ")
+        .ok_or(AppError::AuthError)?;
+
+    let token_data = decode::<Claims>(bearer, &keys().decoding, &Validation::default())
+        .map_err(|_| AppError::AuthError)?;
+
+    let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError::AuthError)?;
+    let session_id = token_data.claims.sid;
+
+    let session_active: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM user_sessions s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.user_id = $1
+              AND s.family_id = $2
+              AND s.revoked_at IS NULL
+              AND u.suspended_at IS NULL
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(session_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalError(e.into()))?;
+
+    if !session_active {
+        return Err(AppError::AuthError);
+    }
+
+    Ok((user_id, session_id))
 }
 
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
     S: Send + Sync,
+    PgPool: axum::extract::FromRef<S>,
 {
     type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let bearer = parts
-            .headers
-            .get(AUTHORIZATION)
-            .ok_or(AppError::AuthError)?
-            .to_str()
-            .map_err(|_| AppError::AuthError)?
-            .strip_prefix("Bearer ")
-            .ok_or(AppError::AuthError)?;
-
-        let token_data = decode::<Claims>(bearer, &KEYS.decoding, &Validation::default())
-            .map_err(|_| AppError::AuthError)?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let pool = PgPool::from_ref(state);
 
-        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError::AuthError)?;
+        let (user_id, session_id) = authenticate(parts, &pool).await?;
 
-        Ok(Self { user_id })
+        Ok(Self {
+            user_id,
+            session_id,
+        })
     }
 }
 
 #[async_trait]
-impl<S> FromRequestParts<S> for AdminUser
+impl<S, P> FromRequestParts<S> for RequirePermission<P>
 where
     S: Send + Sync,
+    P: Permission,
     PgPool: axum::extract::FromRef<S>,
 {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let bearer = parts
-            .headers
-            .get(AUTHORIZATION)
-            .ok_or(AppError::AuthError)?
-            .to_str()
-            .map_err(|_| AppError::AuthError)?
-            .strip_prefix("Bearer ")
-            .ok_or(AppError::AuthError)?;
-
-        let token_data = decode::<Claims>(bearer, &KEYS.decoding, &Validation::default())
-            .map_err(|_| AppError::AuthError)?;
-
-        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError::AuthError)?;
-
         let pool = PgPool::from_ref(state);
 
-        let user_role: (String,) = sqlx::query_as("SELECT role FROM users WHERE id = $1")
-            .bind(user_id)
-            .fetch_optional(&pool)
-            .await
-            .map_err(|e| AppError::InternalError(e.into()))?
-            .ok_or(AppError::AuthError)?;
+        let (user_id, _session_id) = authenticate(parts, &pool).await?;
 
-        if user_role.0 != "admin" {
-            return Err(AppError::AuthError);
+        if !has_permission(&pool, user_id, P::NAME).await? {
+            return Err(AppError::Forbidden);
         }
 
-        Ok(Self { user_id })
+        Ok(Self {
+            user_id,
+            _permission: PhantomData,
+        })
     }
 }
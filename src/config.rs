@@ -0,0 +1,479 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{OAuthConfig, attempts::TimeoutAction};
+
+/// Every setting the backend reads. Each one can be given as an environment variable or as
+/// the same name in lowercase in the TOML config file; the environment wins.
+const KEYS: &[&str] = &[
+    "APP_ENV",
+    "DATABASE_URL",
+    "POSTGRES_USER",
+    "POSTGRES_PASSWORD",
+    "POSTGRES_DB",
+    "POSTGRES_HOST",
+    "SERVER_ADDRESS",
+    "JWT_SECRET",
+    "FRONTEND_URL",
+    "JUPYTERHUB_URL",
+    "GRADING_SERVICE_URL",
+    "GRADING_JOB_POLL_INTERVAL_SECONDS",
+    "NBGRADER_WEBHOOK_SECRET",
+    "WEBHOOK_DELIVERY_POLL_INTERVAL_SECONDS",
+    "LEADERBOARD_REVEAL_INTERVAL_SECONDS",
+    "GOOGLE_CLIENT_ID",
+    "GOOGLE_CLIENT_SECRET",
+    "GOOGLE_REDIRECT_URI",
+    "GOOGLE_AUTH_URL",
+    "GOOGLE_TOKEN_URL",
+    "GOOGLE_USERINFO_URL",
+    "SMTP_URL",
+    "MAIL_FROM",
+    "MAIL_OUTBOX_DIR",
+    "ATTEMPT_GRACE_PERIOD_SECONDS",
+    "ATTEMPT_TIMEOUT_ACTION",
+    "ATTEMPT_SWEEP_INTERVAL_SECONDS",
+];
+
+const REDACTED: &str = "********";
+/// Shortest JWT secret accepted in production
+const MIN_PRODUCTION_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Environment {
+    Development,
+    Production,
+}
+
+impl Environment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Production => "production",
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "development" => Ok(Environment::Development),
+            "production" => Ok(Environment::Production),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MailConfig {
+    /// Without an SMTP relay, emails are only logged (and written to `outbox_dir` if set)
+    pub smtp_url: Option<String>,
+    pub from: String,
+    pub outbox_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttemptConfig {
+    /// Extra time after the deadline during which a submission is still accepted
+    pub grace_period_seconds: i64,
+    pub timeout_action: TimeoutAction,
+    pub sweep_interval_seconds: u64,
+}
+
+impl AttemptConfig {
+    pub fn grace_period(&self) -> time::Duration {
+        time::Duration::seconds(self.grace_period_seconds)
+    }
+}
+
+/// No `Debug`: it holds secrets. Use [`Config::redacted`] to show it.
+#[derive(Clone)]
+pub struct Config {
+    pub environment: Environment,
+    pub database_url: String,
+    pub server_address: SocketAddr,
+    pub jwt_secret: String,
+    pub frontend_url: String,
+    pub jupyterhub_url: String,
+    pub grading_service_url: String,
+    pub grading_job_poll_interval_seconds: u64,
+    pub nbgrader_webhook_secret: Option<String>,
+    pub webhook_delivery_poll_interval_seconds: u64,
+    pub leaderboard_reveal_interval_seconds: u64,
+    pub google: OAuthConfig,
+    pub mail: MailConfig,
+    pub attempts: AttemptConfig,
+}
+
+/// Everything wrong with the configuration, reported together
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<String>);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Read the TOML file at `path` (or `CONFIG_FILE`), if any, with the environment on top
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut vars = HashMap::new();
+
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os("CONFIG_FILE").map(PathBuf::from));
+        if let Some(path) = path {
+            vars.extend(read_toml(&path)?);
+        }
+
+        vars.extend(std::env::vars().filter(|(key, _)| KEYS.contains(&key.as_str())));
+
+        Self::from_vars(vars)
+    }
+
+    /// Build and validate the configuration from `NAME => value` pairs
+    pub fn from_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut source = Source {
+            vars: vars
+                .into_iter()
+                .filter(|(_, value)| !value.trim().is_empty())
+                .collect(),
+            problems: Vec::new(),
+        };
+
+        let environment = source.parse("APP_ENV", Environment::Development);
+
+        let database_url = match source.get("DATABASE_URL") {
+            Some(url) => url,
+            // Docker compose provides the Postgres credentials separately
+            None => match source.get("POSTGRES_PASSWORD") {
+                Some(password) => format!(
+                    "postgres://{}:{}@{}:5432/{}",
+                    source.get_or("POSTGRES_USER", "uj_ai_club"),
+                    password,
+                    source.get_or("POSTGRES_HOST", "postgres"),
+                    source.get_or("POSTGRES_DB", "uj_ai_club"),
+                ),
+                None => {
+                    source.problem("DATABASE_URL (or POSTGRES_PASSWORD) must be set");
+                    String::new()
+                }
+            },
+        };
+
+        let server_address = source.parse("SERVER_ADDRESS", SocketAddr::from(([0, 0, 0, 0], 8000)));
+
+        let jwt_secret = source.required("JWT_SECRET");
+        if environment == Environment::Production
+            && !jwt_secret.is_empty()
+            && jwt_secret.len() < MIN_PRODUCTION_SECRET_LEN
+        {
+            source.problem(format!(
+                "JWT_SECRET must be at least {MIN_PRODUCTION_SECRET_LEN} characters in production"
+            ));
+        }
+
+        let frontend_url = source.url("FRONTEND_URL", "https://aiclub-uj.com");
+        let jupyterhub_url = source.url("JUPYTERHUB_URL", "https://jupyter.aiclub-uj.com");
+        let grading_service_url = match source.get("GRADING_SERVICE_URL").as_deref() {
+            Some("memory") => "memory".to_string(),
+            _ => source.url("GRADING_SERVICE_URL", "http://uj-ai-club-grading:9100"),
+        };
+        let grading_job_poll_interval_seconds =
+            source.positive("GRADING_JOB_POLL_INTERVAL_SECONDS", 5);
+        let nbgrader_webhook_secret = source.get("NBGRADER_WEBHOOK_SECRET");
+        if environment == Environment::Production && nbgrader_webhook_secret.is_none() {
+            source.problem("NBGRADER_WEBHOOK_SECRET must be set in production");
+        }
+        let webhook_delivery_poll_interval_seconds =
+            source.positive("WEBHOOK_DELIVERY_POLL_INTERVAL_SECONDS", 5);
+        let leaderboard_reveal_interval_seconds =
+            source.positive("LEADERBOARD_REVEAL_INTERVAL_SECONDS", 60);
+
+        let google = OAuthConfig {
+            client_id: source.required("GOOGLE_CLIENT_ID"),
+            client_secret: source.required("GOOGLE_CLIENT_SECRET"),
+            redirect_uri: source.required_url("GOOGLE_REDIRECT_URI"),
+            auth_url: source.url(
+                "GOOGLE_AUTH_URL",
+                "https://accounts.google.com/o/oauth2/v2/auth",
+            ),
+            token_url: source.url("GOOGLE_TOKEN_URL", "https://oauth2.googleapis.com/token"),
+            userinfo_url: source.url(
+                "GOOGLE_USERINFO_URL",
+                "https://www.googleapis.com/oauth2/v3/userinfo",
+            ),
+        };
+
+        let smtp_url = source.get("SMTP_URL");
+        if let Some(url) = &smtp_url
+            && lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::from_url(url).is_err()
+        {
+            source.problem("SMTP_URL is not a valid SMTP connection URL");
+        }
+        let from = source.get_or("MAIL_FROM", "UJ AI Club <no-reply@aiclub-uj.com>");
+        if from.parse::<lettre::message::Mailbox>().is_err() {
+            source.problem(format!("MAIL_FROM is not a valid mailbox: '{from}'"));
+        }
+        let mail = MailConfig {
+            smtp_url,
+            from,
+            outbox_dir: source.get("MAIL_OUTBOX_DIR").map(PathBuf::from),
+        };
+
+        let grace_period_seconds = source.parse("ATTEMPT_GRACE_PERIOD_SECONDS", 120i64);
+        if grace_period_seconds < 0 {
+            source.problem("ATTEMPT_GRACE_PERIOD_SECONDS must not be negative");
+        }
+        let attempts = AttemptConfig {
+            grace_period_seconds,
+            timeout_action: source.parse("ATTEMPT_TIMEOUT_ACTION", TimeoutAction::Submit),
+            sweep_interval_seconds: source.positive("ATTEMPT_SWEEP_INTERVAL_SECONDS", 30),
+        };
+
+        if !source.problems.is_empty() {
+            return Err(ConfigError(source.problems));
+        }
+
+        Ok(Self {
+            environment,
+            database_url,
+            server_address,
+            jwt_secret,
+            frontend_url,
+            jupyterhub_url,
+            grading_service_url,
+            grading_job_poll_interval_seconds,
+            nbgrader_webhook_secret,
+            webhook_delivery_poll_interval_seconds,
+            leaderboard_reveal_interval_seconds,
+            google,
+            mail,
+            attempts,
+        })
+    }
+
+    /// The effective configuration, one `name = value` per line, with secrets redacted
+    pub fn redacted(&self) -> String {
+        let secret = |value: &str| {
+            if value.is_empty() {
+                "<unset>".to_string()
+            } else {
+                REDACTED.to_string()
+            }
+        };
+        let optional =
+            |value: &Option<String>| value.clone().unwrap_or_else(|| "<unset>".to_string());
+
+        let lines = [
+            ("app_env", self.environment.as_str().to_string()),
+            ("database_url", redact_url_password(&self.database_url)),
+            ("server_address", self.server_address.to_string()),
+            ("jwt_secret", secret(&self.jwt_secret)),
+            ("frontend_url", self.frontend_url.clone()),
+            ("jupyterhub_url", self.jupyterhub_url.clone()),
+            ("grading_service_url", self.grading_service_url.clone()),
+            (
+                "grading_job_poll_interval_seconds",
+                self.grading_job_poll_interval_seconds.to_string(),
+            ),
+            (
+                "nbgrader_webhook_secret",
+                secret(self.nbgrader_webhook_secret.as_deref().unwrap_or_default()),
+            ),
+            (
+                "webhook_delivery_poll_interval_seconds",
+                self.webhook_delivery_poll_interval_seconds.to_string(),
+            ),
+            (
+                "leaderboard_reveal_interval_seconds",
+                self.leaderboard_reveal_interval_seconds.to_string(),
+            ),
+            ("google_client_id", self.google.client_id.clone()),
+            ("google_client_secret", secret(&self.google.client_secret)),
+            ("google_redirect_uri", self.google.redirect_uri.clone()),
+            ("google_auth_url", self.google.auth_url.clone()),
+            ("google_token_url", self.google.token_url.clone()),
+            ("google_userinfo_url", self.google.userinfo_url.clone()),
+            (
+                "smtp_url",
+                self.mail
+                    .smtp_url
+                    .as_deref()
+                    .map(redact_url_password)
+                    .unwrap_or_else(|| "<unset>".to_string()),
+            ),
+            ("mail_from", self.mail.from.clone()),
+            (
+                "mail_outbox_dir",
+                optional(
+                    &self
+                        .mail
+                        .outbox_dir
+                        .as_ref()
+                        .map(|dir| dir.display().to_string()),
+                ),
+            ),
+            (
+                "attempt_grace_period_seconds",
+                self.attempts.grace_period_seconds.to_string(),
+            ),
+            (
+                "attempt_timeout_action",
+                self.attempts.timeout_action.as_str().to_string(),
+            ),
+            (
+                "attempt_sweep_interval_seconds",
+                self.attempts.sweep_interval_seconds.to_string(),
+            ),
+        ];
+
+        lines
+            .iter()
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Collects values by name and every problem found while reading them
+struct Source {
+    vars: HashMap<String, String>,
+    problems: Vec<String>,
+}
+
+impl Source {
+    fn get(&self, key: &str) -> Option<String> {
+        self.vars.get(key).map(|value| value.trim().to_string())
+    }
+
+    fn get_or(&self, key: &str, default: &str) -> String {
+        self.get(key).unwrap_or_else(|| default.to_string())
+    }
+
+    fn problem(&mut self, problem: impl Into<String>) {
+        self.problems.push(problem.into());
+    }
+
+    fn required(&mut self, key: &str) -> String {
+        self.get(key).unwrap_or_else(|| {
+            self.problem(format!("{key} must be set"));
+            String::new()
+        })
+    }
+
+    fn parse<T: FromStr>(&mut self, key: &str, default: T) -> T {
+        match self.get(key) {
+            None => default,
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                self.problem(format!("{key} has an invalid value: '{value}'"));
+                default
+            }),
+        }
+    }
+
+    fn positive(&mut self, key: &str, default: u64) -> u64 {
+        let value = self.parse(key, default);
+        if value == 0 {
+            self.problem(format!("{key} must be greater than 0"));
+        }
+        value
+    }
+
+    /// An http(s) URL, without a trailing slash
+    fn url(&mut self, key: &str, default: &str) -> String {
+        let value = self.get_or(key, default);
+        self.check_url(key, value)
+    }
+
+    fn required_url(&mut self, key: &str) -> String {
+        match self.get(key) {
+            Some(value) => self.check_url(key, value),
+            None => self.required(key),
+        }
+    }
+
+    fn check_url(&mut self, key: &str, value: String) -> String {
+        match url::Url::parse(&value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                value.trim_end_matches('/').to_string()
+            }
+            _ => {
+                self.problem(format!("{key} must be an http(s) URL, got '{value}'"));
+                value
+            }
+        }
+    }
+}
+
+/// Settings from a TOML file, keyed like the environment variables
+fn read_toml(path: &Path) -> Result<HashMap<String, String>, ConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ConfigError(vec![format!(
+            "Could not read config file {}: {e}",
+            path.display()
+        )])
+    })?;
+    let table: toml::Table = contents.parse().map_err(|e| {
+        ConfigError(vec![format!(
+            "Could not parse config file {}: {e}",
+            path.display()
+        )])
+    })?;
+
+    let mut vars = HashMap::new();
+    let mut problems = Vec::new();
+
+    for (key, value) in table {
+        let name = key.to_uppercase();
+        if !KEYS.contains(&name.as_str()) {
+            problems.push(format!("Unknown setting '{key}' in {}", path.display()));
+            continue;
+        }
+
+        let value = match value {
+            toml::Value::String(s) => s,
+            toml::Value::Integer(i) => i.to_string(),
+            toml::Value::Boolean(b) => b.to_string(),
+            _ => {
+                problems.push(format!(
+                    "Setting '{key}' must be a string, integer or boolean"
+                ));
+                continue;
+            }
+        };
+        vars.insert(name, value);
+    }
+
+    if !problems.is_empty() {
+        return Err(ConfigError(problems));
+    }
+
+    Ok(vars)
+}
+
+fn redact_url_password(value: &str) -> String {
+    match url::Url::parse(value) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some(REDACTED));
+            url.to_string()
+        }
+        _ => value.to_string(),
+    }
+}
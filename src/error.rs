@@ -10,6 +10,10 @@ use thiserror::Error;
 pub enum AppError {
     #[error("Authentication failed")]
     AuthError,
+    #[error("Permission denied")]
+    Forbidden,
+    #[error("Account suspended")]
+    AccountSuspended,
     #[error("Database error")]
     DatabaseError(#[from] sqlx::Error),
     #[error("Validation error: {0}")]
@@ -18,6 +22,8 @@ pub enum AppError {
     BadRequest(String),
     #[error("User already exists")]
     UserExists,
+    #[error("Conflict: {0}")]
+    Conflict(String),
     #[error("Resource not found")]
     NotFound,
     #[error("Internal server error")]
@@ -31,6 +37,14 @@ impl IntoResponse for AppError {
                 StatusCode::UNAUTHORIZED,
                 "Authentication failed".to_string(),
             ),
+            AppError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "You do not have permission to perform this action".to_string(),
+            ),
+            AppError::AccountSuspended => (
+                StatusCode::FORBIDDEN,
+                "This account has been suspended".to_string(),
+            ),
             AppError::NotFound => (StatusCode::NOT_FOUND, "Resource not found".to_string()),
             AppError::DatabaseError(err) => match err {
                 sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => {
@@ -51,6 +65,7 @@ impl IntoResponse for AppError {
             AppError::ValidationError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::UserExists => (StatusCode::CONFLICT, "User already exists".to_string()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::InternalError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Internal server error".to_string(),
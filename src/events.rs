@@ -0,0 +1,150 @@
+use serde_json::{Value, json};
+use sqlx::PgConnection;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{Certificate, Challenge, ChallengeSubmission, User},
+};
+
+/// Platform events that webhook subscriptions can listen for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    /// A challenge became visible to members
+    ChallengePublished,
+    /// An attempt was handed in, by the student or automatically at its deadline
+    SubmissionSubmitted,
+    /// Points for an attempt were credited, by an admin or by auto-grading
+    SubmissionGraded,
+    UserSignedUp,
+    /// A certificate became visible
+    CertificatePublished,
+}
+
+impl EventType {
+    pub const ALL: [EventType; 5] = [
+        EventType::ChallengePublished,
+        EventType::SubmissionSubmitted,
+        EventType::SubmissionGraded,
+        EventType::UserSignedUp,
+        EventType::CertificatePublished,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::ChallengePublished => "challenge.published",
+            EventType::SubmissionSubmitted => "submission.submitted",
+            EventType::SubmissionGraded => "submission.graded",
+            EventType::UserSignedUp => "user.signed_up",
+            EventType::CertificatePublished => "certificate.published",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == s)
+    }
+}
+
+/// Something that happened on the platform, with the data sent to subscribers
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub event_type: EventType,
+    pub data: Value,
+}
+
+impl Event {
+    pub fn challenge_published(challenge: &Challenge) -> Self {
+        Event {
+            event_type: EventType::ChallengePublished,
+            data: json!({
+                "challengeId": challenge.id,
+                "title": challenge.title,
+                "week": challenge.week,
+                "startDate": challenge.start_date.and_then(|d| d.format(&Rfc3339).ok()),
+                "endDate": challenge.end_date.and_then(|d| d.format(&Rfc3339).ok()),
+            }),
+        }
+    }
+
+    pub fn submission_submitted(submission: &ChallengeSubmission) -> Self {
+        Event {
+            event_type: EventType::SubmissionSubmitted,
+            data: json!({
+                "submissionId": submission.id,
+                "userId": submission.user_id,
+                "challengeId": submission.challenge_id,
+                "attemptNumber": submission.attempt_number,
+            }),
+        }
+    }
+
+    pub fn submission_graded(submission: &ChallengeSubmission) -> Self {
+        Event {
+            event_type: EventType::SubmissionGraded,
+            data: json!({
+                "submissionId": submission.id,
+                "userId": submission.user_id,
+                "challengeId": submission.challenge_id,
+                "attemptNumber": submission.attempt_number,
+                "score": submission.score,
+                "maxScore": submission.max_score,
+                "pointsAwarded": submission.points_awarded,
+            }),
+        }
+    }
+
+    /// Only the public profile: subscribers are third-party bots
+    pub fn user_signed_up(user: &User) -> Self {
+        Event {
+            event_type: EventType::UserSignedUp,
+            data: json!({
+                "userId": user.id,
+                "fullName": user.full_name,
+            }),
+        }
+    }
+
+    pub fn certificate_published(certificate: &Certificate) -> Self {
+        Event {
+            event_type: EventType::CertificatePublished,
+            data: json!({
+                "certificateId": certificate.id,
+                "title": certificate.title,
+                "courseTitle": certificate.course_title,
+                "level": certificate.level,
+                "recipient": format!("{} {}", certificate.first_name, certificate.second_name),
+            }),
+        }
+    }
+}
+
+/// Queue a delivery of `event` to every active subscription listening for it. Run it in the
+/// same transaction as the change it describes, so the event goes out exactly when it commits.
+pub async fn publish_event(conn: &mut PgConnection, event: &Event) -> Result<(), AppError> {
+    let payload = json!({
+        "id": Uuid::new_v4(),
+        "type": event.event_type.as_str(),
+        "createdAt": OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(anyhow::Error::from)?,
+        "data": event.data,
+    });
+
+    sqlx::query(
+        r#"
+        INSERT INTO outbound_webhook_deliveries (subscription_id, event_type, payload)
+        SELECT id, $1, $2
+        FROM webhook_subscriptions
+        WHERE is_active AND $1 = ANY(event_types)
+        "#,
+    )
+    .bind(event.event_type.as_str())
+    .bind(payload)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
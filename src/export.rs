@@ -0,0 +1,257 @@
+use std::io;
+
+use axum::{
+    body::Body,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures_util::{StreamExt, stream};
+use serde::Deserialize;
+use sqlx::{
+    FromRow, PgPool, Postgres,
+    postgres::{PgArguments, PgRow},
+    query::QueryAs,
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tokio::sync::mpsc;
+
+use crate::error::AppError;
+
+/// Rows fetched ahead of the client while a CSV export streams
+const EXPORT_BUFFER_ROWS: usize = 256;
+/// Leading characters that make a spreadsheet read a text cell as a formula
+const FORMULA_PREFIXES: [char; 6] = ['=', '+', '-', '@', '\t', '\r'];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+/// `?format=csv` (the default) or `?format=xlsx`
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// One exported value. Numbers stay numbers in XLSX; CSV writes everything as text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+impl Cell {
+    fn into_text(self) -> String {
+        match self {
+            Cell::Text(text) => neutralise_formula(text),
+            Cell::Number(number) => number.to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(number: i32) -> Self {
+        Cell::Number(number.into())
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(number: f64) -> Self {
+        Cell::Number(number)
+    }
+}
+
+impl From<OffsetDateTime> for Cell {
+    fn from(at: OffsetDateTime) -> Self {
+        at.format(&Rfc3339).map(Cell::Text).unwrap_or(Cell::Empty)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Cell::Empty)
+    }
+}
+
+/// Prefix `'` to text a spreadsheet would otherwise evaluate, such as a name of
+/// `=HYPERLINK(...)`, so user-entered values are shown rather than run
+fn neutralise_formula(text: String) -> String {
+    if text.starts_with(FORMULA_PREFIXES) {
+        format!("'{text}")
+    } else {
+        text
+    }
+}
+
+/// Undo [`neutralise_formula`] for a value read back from an exported file
+pub fn restore_formula(text: &str) -> &str {
+    match text.strip_prefix('\'') {
+        Some(rest) if rest.starts_with(FORMULA_PREFIXES) => rest,
+        _ => text,
+    }
+}
+
+/// A query row that can be written to a spreadsheet
+pub trait ExportRow {
+    /// Column titles, in the order `cells` returns them
+    const HEADERS: &'static [&'static str];
+
+    fn cells(self) -> Vec<Cell>;
+}
+
+/// Run `sql` and send its rows as a `{filename}.csv` or `{filename}.xlsx` attachment.
+/// CSV is streamed while the query runs; XLSX is built in memory.
+pub async fn export_rows<T, F>(
+    pool: PgPool,
+    sql: String,
+    bind: F,
+    format: ExportFormat,
+    filename: &str,
+) -> Result<Response, AppError>
+where
+    T: ExportRow + for<'r> FromRow<'r, PgRow> + Send + Unpin + 'static,
+    F: for<'q> FnOnce(
+            QueryAs<'q, Postgres, T, PgArguments>,
+        ) -> QueryAs<'q, Postgres, T, PgArguments>
+        + Send
+        + 'static,
+{
+    if format == ExportFormat::Xlsx && !cfg!(feature = "xlsx") {
+        return Err(AppError::BadRequest(
+            "XLSX export is not available in this build; use format=csv".to_string(),
+        ));
+    }
+
+    let (sender, mut receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+    tokio::spawn(async move {
+        let mut rows = bind(sqlx::query_as::<_, T>(&sql)).fetch(&pool);
+        while let Some(row) = rows.next().await {
+            let failed = row.is_err();
+            // Stop once the client has gone away or the query has failed
+            if sender.send(row).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    // A query that fails outright is an error response rather than an empty file
+    let first = receiver.recv().await.transpose()?;
+
+    match format {
+        ExportFormat::Csv => {
+            let header = csv_record(T::HEADERS.iter());
+            let first = first.map(|row| csv_record(row.cells().into_iter().map(Cell::into_text)));
+            let rest = stream::unfold(receiver, |mut receiver| async move {
+                let row = receiver.recv().await?;
+                let chunk = row
+                    .map_err(io::Error::other)
+                    .and_then(|row| csv_record(row.cells().into_iter().map(Cell::into_text)));
+                Some((chunk, receiver))
+            });
+            let body =
+                Body::from_stream(stream::iter(std::iter::once(header).chain(first)).chain(rest));
+
+            Ok(attachment(
+                body,
+                "text/csv; charset=utf-8",
+                &format!("{filename}.csv"),
+            ))
+        }
+        ExportFormat::Xlsx => {
+            let mut rows: Vec<T> = first.into_iter().collect();
+            while let Some(row) = receiver.recv().await {
+                rows.push(row?);
+            }
+            let workbook = xlsx_workbook(T::HEADERS, rows.into_iter().map(ExportRow::cells))?;
+
+            Ok(attachment(
+                Body::from(workbook),
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                &format!("{filename}.xlsx"),
+            ))
+        }
+    }
+}
+
+fn attachment(body: Body, content_type: &str, filename: &str) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+fn csv_record<I>(fields: I) -> io::Result<Bytes>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields)?;
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| e.into_error())
+}
+
+#[cfg(feature = "xlsx")]
+fn xlsx_workbook(
+    headers: &[&str],
+    rows: impl Iterator<Item = Vec<Cell>>,
+) -> Result<Vec<u8>, AppError> {
+    use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+    let build = || -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let bold = Format::new().set_bold();
+        let sheet = workbook.add_worksheet();
+
+        for (col, title) in headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *title, &bold)?;
+        }
+        for (index, cells) in rows.enumerate() {
+            let row = index as u32 + 1;
+            for (col, cell) in cells.into_iter().enumerate() {
+                match cell {
+                    Cell::Text(text) => {
+                        sheet.write_string(row, col as u16, neutralise_formula(text))?
+                    }
+                    Cell::Number(number) => sheet.write_number(row, col as u16, number)?,
+                    Cell::Empty => continue,
+                };
+            }
+        }
+        sheet.set_freeze_panes(1, 0)?;
+
+        workbook.save_to_buffer()
+    };
+
+    build().map_err(|e| AppError::InternalError(e.into()))
+}
+
+#[cfg(not(feature = "xlsx"))]
+fn xlsx_workbook(
+    _headers: &[&str],
+    _rows: impl Iterator<Item = Vec<Cell>>,
+) -> Result<Vec<u8>, AppError> {
+    unreachable!("XLSX exports are refused before the query runs")
+}
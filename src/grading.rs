@@ -0,0 +1,371 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    grading_service::GradingServiceClient,
+    models::{ChallengeNotebook, GradingJob},
+};
+
+const BASE_BACKOFF_SECONDS: i64 = 15;
+const MAX_BACKOFF_SECONDS: i64 = 3600;
+/// A claimed job is hidden from other workers for this long, in case delivery never reports back
+const CLAIM_TIMEOUT_SECONDS: i64 = 300;
+const BATCH_SIZE: i64 = 20;
+
+/// A call to the grading service, stored as the payload of a `grading_jobs` row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GradingRequest {
+    /// Copy the student version of a notebook (solutions removed) into the user's workspace
+    PrepareNotebook {
+        jupyterhub_username: String,
+        assignment_name: String,
+        notebook_path: String,
+        notebook_filename: String,
+    },
+    /// Collect the notebook from the user's workspace into the nbgrader exchange and grade it
+    Submit {
+        jupyterhub_username: String,
+        assignment_name: String,
+        notebook_path: String,
+        notebook_filename: String,
+    },
+    /// Set the notebook up as an nbgrader assignment source
+    SetupAssignment {
+        assignment_name: String,
+        notebook_path: String,
+        max_points: i32,
+    },
+}
+
+impl GradingRequest {
+    pub fn prepare_notebook(jupyterhub_username: &str, notebook: &ChallengeNotebook) -> Self {
+        GradingRequest::PrepareNotebook {
+            jupyterhub_username: jupyterhub_username.to_string(),
+            assignment_name: notebook.assignment_name.clone(),
+            notebook_path: notebook.notebook_path.clone(),
+            notebook_filename: notebook.notebook_filename.clone(),
+        }
+    }
+
+    pub fn submit(jupyterhub_username: &str, notebook: &ChallengeNotebook) -> Self {
+        GradingRequest::Submit {
+            jupyterhub_username: jupyterhub_username.to_string(),
+            assignment_name: notebook.assignment_name.clone(),
+            notebook_path: notebook.notebook_path.clone(),
+            notebook_filename: notebook.notebook_filename.clone(),
+        }
+    }
+
+    pub fn setup_assignment(notebook: &ChallengeNotebook) -> Self {
+        // The grading service mounts the uploads directory at /srv/notebooks
+        GradingRequest::SetupAssignment {
+            assignment_name: notebook.assignment_name.clone(),
+            notebook_path: format!(
+                "/srv/notebooks/{}",
+                notebook.notebook_path.replace("uploads/", "")
+            ),
+            max_points: notebook.max_points,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GradingRequest::PrepareNotebook { .. } => "prepare_notebook",
+            GradingRequest::Submit { .. } => "submit",
+            GradingRequest::SetupAssignment { .. } => "setup_assignment",
+        }
+    }
+
+    async fn send(&self, grading: &dyn GradingServiceClient) -> anyhow::Result<()> {
+        match self {
+            GradingRequest::PrepareNotebook {
+                jupyterhub_username,
+                assignment_name,
+                notebook_path,
+                notebook_filename,
+            } => {
+                grading
+                    .prepare_notebook(
+                        jupyterhub_username,
+                        assignment_name,
+                        notebook_path,
+                        notebook_filename,
+                    )
+                    .await
+            }
+            GradingRequest::Submit {
+                jupyterhub_username,
+                assignment_name,
+                notebook_path,
+                notebook_filename,
+            } => {
+                grading
+                    .submit(
+                        jupyterhub_username,
+                        assignment_name,
+                        notebook_path,
+                        notebook_filename,
+                    )
+                    .await
+            }
+            GradingRequest::SetupAssignment {
+                assignment_name,
+                notebook_path,
+                max_points,
+            } => {
+                grading
+                    .setup_assignment(assignment_name, notebook_path, *max_points)
+                    .await
+            }
+        }
+    }
+}
+
+/// Queue a grading service call. Run it in the same transaction as the change that
+/// needs it, so the call is recorded exactly when that change commits.
+pub async fn enqueue_grading_job(
+    conn: &mut PgConnection,
+    request: &GradingRequest,
+    submission_id: Option<Uuid>,
+    notebook_id: Option<i32>,
+) -> Result<Uuid, AppError> {
+    let payload = serde_json::to_value(request).map_err(anyhow::Error::from)?;
+
+    let job_id = sqlx::query_scalar(
+        r#"
+        INSERT INTO grading_jobs (kind, payload, submission_id, notebook_id)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(request.kind())
+    .bind(payload)
+    .bind(submission_id)
+    .bind(notebook_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(job_id)
+}
+
+/// Try to deliver a freshly queued job straight away instead of waiting for the next poll.
+/// Failures are left to the worker's retries.
+pub fn dispatch_grading_job(pool: PgPool, grading: Arc<dyn GradingServiceClient>, job_id: Uuid) {
+    tokio::spawn(async move {
+        if let Err(e) = deliver_grading_job(&pool, grading.as_ref(), job_id).await {
+            tracing::error!("Failed to deliver grading job {}: {:?}", job_id, e);
+        }
+    });
+}
+
+/// Delay before the next attempt after `attempts` failed ones
+fn backoff_seconds(attempts: i32) -> i64 {
+    let exponent = attempts.clamp(1, 20) as u32 - 1;
+    (BASE_BACKOFF_SECONDS << exponent).min(MAX_BACKOFF_SECONDS)
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedJob {
+    id: Uuid,
+    payload: serde_json::Value,
+    attempts: i32,
+    max_attempts: i32,
+}
+
+/// Deliver one job if it is pending and due; does nothing when another worker holds it
+pub async fn deliver_grading_job(
+    pool: &PgPool,
+    grading: &dyn GradingServiceClient,
+    job_id: Uuid,
+) -> Result<(), AppError> {
+    let claimed: Option<ClaimedJob> = sqlx::query_as(
+        r#"
+        UPDATE grading_jobs
+        SET attempts = attempts + 1,
+            next_attempt_at = NOW() + make_interval(secs => $2),
+            updated_at = NOW()
+        WHERE id = $1 AND status = 'pending' AND next_attempt_at <= NOW()
+        RETURNING id, payload, attempts, max_attempts
+        "#,
+    )
+    .bind(job_id)
+    .bind(CLAIM_TIMEOUT_SECONDS as f64)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(job) = claimed {
+        run_claimed_job(pool, grading, job).await?;
+    }
+
+    Ok(())
+}
+
+/// Deliver every due job, oldest first; returns how many were attempted
+pub async fn process_due_grading_jobs(
+    pool: &PgPool,
+    grading: &dyn GradingServiceClient,
+) -> Result<usize, AppError> {
+    let claimed: Vec<ClaimedJob> = sqlx::query_as(
+        r#"
+        UPDATE grading_jobs
+        SET attempts = attempts + 1,
+            next_attempt_at = NOW() + make_interval(secs => $2),
+            updated_at = NOW()
+        WHERE id IN (
+            SELECT id
+            FROM grading_jobs
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, payload, attempts, max_attempts
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(CLAIM_TIMEOUT_SECONDS as f64)
+    .fetch_all(pool)
+    .await?;
+
+    let count = claimed.len();
+    for job in claimed {
+        run_claimed_job(pool, grading, job).await?;
+    }
+
+    Ok(count)
+}
+
+async fn run_claimed_job(
+    pool: &PgPool,
+    grading: &dyn GradingServiceClient,
+    job: ClaimedJob,
+) -> Result<(), AppError> {
+    let result = match serde_json::from_value::<GradingRequest>(job.payload) {
+        Ok(request) => request.send(grading).await.map_err(|e| format!("{e:#}")),
+        Err(e) => Err(format!("Invalid job payload: {e}")),
+    };
+
+    match result {
+        Ok(()) => {
+            sqlx::query(
+                r#"
+                UPDATE grading_jobs
+                SET status = 'succeeded', last_error = NULL, completed_at = NOW(), updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(job.id)
+            .execute(pool)
+            .await?;
+        }
+        Err(error) => {
+            let gave_up = job.attempts >= job.max_attempts;
+            if gave_up {
+                tracing::error!(
+                    "Grading job {} failed after {} attempts: {}",
+                    job.id,
+                    job.attempts,
+                    error
+                );
+            } else {
+                tracing::warn!(
+                    "Grading job {} attempt {} failed: {}",
+                    job.id,
+                    job.attempts,
+                    error
+                );
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE grading_jobs
+                SET status = CASE WHEN $2 THEN 'failed' ELSE 'pending' END,
+                    last_error = $3,
+                    next_attempt_at = NOW() + make_interval(secs => $4),
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(job.id)
+            .bind(gave_up)
+            .bind(&error)
+            .bind(backoff_seconds(job.attempts) as f64)
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Make a job due immediately, including one that already gave up
+pub async fn retry_grading_job(
+    pool: &PgPool,
+    grading: &dyn GradingServiceClient,
+    job_id: Uuid,
+) -> Result<GradingJob, AppError> {
+    let job: GradingJob = sqlx::query_as("SELECT * FROM grading_jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if job.status == "succeeded" {
+        return Err(AppError::BadRequest(
+            "This job has already been delivered".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE grading_jobs
+        SET status = 'pending', next_attempt_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status <> 'succeeded'
+        "#,
+    )
+    .bind(job_id)
+    .execute(pool)
+    .await?;
+
+    deliver_grading_job(pool, grading, job_id).await?;
+
+    let job = sqlx::query_as("SELECT * FROM grading_jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(job)
+}
+
+/// Periodically deliver queued grading service calls
+pub fn spawn_grading_worker(
+    pool: PgPool,
+    grading: Arc<dyn GradingServiceClient>,
+    interval_seconds: u64,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_seconds));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = process_due_grading_jobs(&pool, grading.as_ref()).await {
+                tracing::error!("Failed to process grading jobs: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Convert an nbgrader score into challenge points, scaled to the notebook's `max_points`
+pub fn points_for_score(score: f64, max_score: f64, max_points: i32) -> i32 {
+    if max_score <= 0.0 || !score.is_finite() {
+        return 0;
+    }
+
+    ((score / max_score).clamp(0.0, 1.0) * max_points as f64).round() as i32
+}
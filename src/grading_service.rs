@@ -0,0 +1,356 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::async_trait;
+
+/// A notebook collected from a student's workspace
+#[derive(Debug, Clone)]
+pub struct SubmittedNotebook {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// The Python grading service that runs nbgrader next to JupyterHub
+#[async_trait]
+pub trait GradingServiceClient: Send + Sync {
+    /// Copy the student version of a notebook (solutions removed) into the user's workspace
+    async fn prepare_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        notebook_path: &str,
+        notebook_filename: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Collect the notebook from the user's workspace into the nbgrader exchange and grade it
+    async fn submit(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        notebook_path: &str,
+        notebook_filename: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Set a notebook up as an nbgrader assignment source
+    async fn setup_assignment(
+        &self,
+        assignment_name: &str,
+        notebook_path: &str,
+        max_points: i32,
+    ) -> anyhow::Result<()>;
+
+    /// The notebook a student submitted, or `None` when the service has no copy yet
+    async fn fetch_submitted_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        download: bool,
+    ) -> anyhow::Result<Option<SubmittedNotebook>>;
+
+    async fn health(&self) -> anyhow::Result<()>;
+}
+
+/// Talks to the grading service over HTTP
+pub struct HttpGradingServiceClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpGradingServiceClient {
+    pub fn new(base_url: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .build()
+            .expect("failed to build grading service HTTP client");
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+
+    async fn post(&self, path: &str, payload: serde_json::Value) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&payload)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("{} - {}", status, error_text);
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GradingServiceClient for HttpGradingServiceClient {
+    async fn prepare_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        notebook_path: &str,
+        notebook_filename: &str,
+    ) -> anyhow::Result<()> {
+        self.post(
+            &format!(
+                "/prepare-notebook/{}/{}",
+                urlencoding::encode(jupyterhub_username),
+                urlencoding::encode(assignment_name)
+            ),
+            serde_json::json!({
+                "notebookPath": notebook_path,
+                "notebookFilename": notebook_filename
+            }),
+        )
+        .await
+    }
+
+    async fn submit(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        notebook_path: &str,
+        notebook_filename: &str,
+    ) -> anyhow::Result<()> {
+        self.post(
+            &format!(
+                "/submit/{}/{}",
+                urlencoding::encode(jupyterhub_username),
+                urlencoding::encode(assignment_name)
+            ),
+            serde_json::json!({
+                "notebookFilename": notebook_filename,
+                "notebookPath": notebook_path
+            }),
+        )
+        .await
+    }
+
+    async fn setup_assignment(
+        &self,
+        assignment_name: &str,
+        notebook_path: &str,
+        max_points: i32,
+    ) -> anyhow::Result<()> {
+        self.post(
+            &format!("/setup-assignment/{}", urlencoding::encode(assignment_name)),
+            serde_json::json!({
+                "notebookPath": notebook_path,
+                "assignmentName": assignment_name,
+                "maxPoints": max_points
+            }),
+        )
+        .await
+    }
+
+    async fn fetch_submitted_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        download: bool,
+    ) -> anyhow::Result<Option<SubmittedNotebook>> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/submissions/{}/{}/notebook?download={}",
+                self.base_url,
+                urlencoding::encode(jupyterhub_username),
+                urlencoding::encode(assignment_name),
+                if download { 1 } else { 0 }
+            ))
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to fetch submitted notebook from grading service: {}",
+                response.status()
+            );
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("application/x-ipynb+json")
+            .to_string();
+        let bytes = response.bytes().await?.to_vec();
+
+        Ok(Some(SubmittedNotebook {
+            content_type,
+            bytes,
+        }))
+    }
+
+    async fn health(&self) -> anyhow::Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/health", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Grading service is unhealthy: {}", response.status());
+        }
+
+        Ok(())
+    }
+}
+
+/// A call received by [`InMemoryGradingService`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GradingServiceCall {
+    PrepareNotebook {
+        jupyterhub_username: String,
+        assignment_name: String,
+    },
+    Submit {
+        jupyterhub_username: String,
+        assignment_name: String,
+    },
+    SetupAssignment {
+        assignment_name: String,
+        max_points: i32,
+    },
+}
+
+/// Stand-in for the grading service: records every call and serves notebooks put into it.
+/// Used by tests and for running the backend without the Python service.
+#[derive(Default)]
+pub struct InMemoryGradingService {
+    calls: Mutex<Vec<GradingServiceCall>>,
+    notebooks: Mutex<HashMap<(String, String), SubmittedNotebook>>,
+    unavailable: AtomicBool,
+}
+
+impl InMemoryGradingService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn calls(&self) -> Vec<GradingServiceCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Make every call fail, as if the service were down
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+
+    /// Serve `bytes` as the notebook `jupyterhub_username` submitted for `assignment_name`
+    pub fn put_submitted_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        bytes: Vec<u8>,
+    ) {
+        self.notebooks.lock().unwrap().insert(
+            (jupyterhub_username.to_string(), assignment_name.to_string()),
+            SubmittedNotebook {
+                content_type: "application/x-ipynb+json".to_string(),
+                bytes,
+            },
+        );
+    }
+
+    fn record(&self, call: GradingServiceCall) -> anyhow::Result<()> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            anyhow::bail!("Grading service is unavailable");
+        }
+
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl GradingServiceClient for InMemoryGradingService {
+    async fn prepare_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        _notebook_path: &str,
+        _notebook_filename: &str,
+    ) -> anyhow::Result<()> {
+        self.record(GradingServiceCall::PrepareNotebook {
+            jupyterhub_username: jupyterhub_username.to_string(),
+            assignment_name: assignment_name.to_string(),
+        })
+    }
+
+    async fn submit(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        _notebook_path: &str,
+        _notebook_filename: &str,
+    ) -> anyhow::Result<()> {
+        self.record(GradingServiceCall::Submit {
+            jupyterhub_username: jupyterhub_username.to_string(),
+            assignment_name: assignment_name.to_string(),
+        })
+    }
+
+    async fn setup_assignment(
+        &self,
+        assignment_name: &str,
+        _notebook_path: &str,
+        max_points: i32,
+    ) -> anyhow::Result<()> {
+        self.record(GradingServiceCall::SetupAssignment {
+            assignment_name: assignment_name.to_string(),
+            max_points,
+        })
+    }
+
+    async fn fetch_submitted_notebook(
+        &self,
+        jupyterhub_username: &str,
+        assignment_name: &str,
+        _download: bool,
+    ) -> anyhow::Result<Option<SubmittedNotebook>> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            anyhow::bail!("Grading service is unavailable");
+        }
+
+        Ok(self
+            .notebooks
+            .lock()
+            .unwrap()
+            .get(&(jupyterhub_username.to_string(), assignment_name.to_string()))
+            .cloned())
+    }
+
+    async fn health(&self) -> anyhow::Result<()> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            anyhow::bail!("Grading service is unavailable");
+        }
+
+        Ok(())
+    }
+}
+
+/// HTTP client for the configured grading service URL, or the in-memory stand-in for `memory`
+pub fn grading_service_from_config(url: &str) -> Arc<dyn GradingServiceClient> {
+    if url == "memory" {
+        tracing::warn!("GRADING_SERVICE_URL=memory; grading service calls are only recorded");
+        return Arc::new(InMemoryGradingService::new());
+    }
+
+    Arc::new(HttpGradingServiceClient::new(url))
+}
@@ -0,0 +1,81 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::{RequirePermission, ViewAuditLog},
+    error::AppError,
+    models::*,
+};
+
+#[derive(Deserialize)]
+pub struct AdminAuditLogQuery {
+    #[serde(rename = "actorId")]
+    actor_id: Option<uuid::Uuid>,
+    action: Option<String>,
+    #[serde(rename = "targetType")]
+    target_type: Option<String>,
+    #[serde(rename = "targetId")]
+    target_id: Option<String>,
+    /// Inclusive lower bound, a date or an ISO 8601 timestamp
+    #[serde(default, deserialize_with = "crate::models::date_format::deserialize")]
+    from: Option<time::OffsetDateTime>,
+    /// Exclusive upper bound, a date or an ISO 8601 timestamp
+    #[serde(default, deserialize_with = "crate::models::date_format::deserialize")]
+    to: Option<time::OffsetDateTime>,
+    limit: Option<i64>,
+}
+
+/// Admin actions, newest first
+pub async fn admin_get_audit_log(
+    _auth: RequirePermission<ViewAuditLog>,
+    State(state): State<AppState>,
+    Query(query): Query<AdminAuditLogQuery>,
+) -> Result<Json<AdminItemsResponse<AuditLogEntry>>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    let items: Vec<AuditLogEntry> = sqlx::query_as(
+        r#"
+        SELECT
+            a.id,
+            a.actor_id,
+            u.full_name AS actor_name,
+            u.email AS actor_email,
+            a.action,
+            a.target_type,
+            a.target_id,
+            a.before,
+            a.after,
+            a.request_id,
+            a.ip_address,
+            a.created_at
+        FROM audit_log a
+        LEFT JOIN users u ON u.id = a.actor_id
+        WHERE ($1::UUID IS NULL OR a.actor_id = $1)
+          AND ($2::VARCHAR IS NULL OR a.action = $2)
+          AND ($3::VARCHAR IS NULL OR a.target_type = $3)
+          AND ($4::TEXT IS NULL OR a.target_id = $4)
+          AND ($5::TIMESTAMPTZ IS NULL OR a.created_at >= $5)
+          AND ($6::TIMESTAMPTZ IS NULL OR a.created_at < $6)
+        ORDER BY a.created_at DESC, a.id DESC
+        LIMIT $7
+        "#,
+    )
+    .bind(query.actor_id)
+    .bind(&query.action)
+    .bind(&query.target_type)
+    .bind(&query.target_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
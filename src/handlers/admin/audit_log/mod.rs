@@ -0,0 +1,3 @@
+pub mod admin_get_audit_log;
+
+pub use admin_get_audit_log::admin_get_audit_log;
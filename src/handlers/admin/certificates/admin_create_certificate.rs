@@ -1,17 +1,28 @@
 use axum::{Json, extract::State};
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    events::{Event, publish_event},
+    models::*,
+    notifications::{NewNotification, create_notification},
+};
 
 use super::normalize_youtube_url::normalize_youtube_url;
 
 pub async fn admin_create_certificate(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
+    request: RequestContext,
     Json(req): Json<AdminCreateCertificateRequest>,
 ) -> Result<Json<AdminItemResponse<AdminCertificateResponse>>, AppError> {
     let visible = req.visible.unwrap_or(true);
     let youtube_url = req.youtube_url.map(|url| normalize_youtube_url(&url));
 
+    let mut tx = state.pool.begin().await?;
+
     let certificate: Certificate = sqlx::query_as(
         r#"
         INSERT INTO certificates (level, title, course_title, cover_image, first_name, second_name, coursera_url, youtube_url, visible, created_at, updated_at)
@@ -28,9 +39,34 @@ pub async fn admin_create_certificate(
     .bind(&req.coursera_url)
     .bind(&youtube_url)
     .bind(visible)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if certificate.visible {
+        publish_event(&mut tx, &Event::certificate_published(&certificate)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::certificate_published(&certificate),
+        )
+        .await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.create",
+            target_type: "certificate",
+            target_id: Some(certificate.id.to_string()),
+            before: None,
+            after: snapshot(&certificate),
+        },
+    )
     .await?;
 
+    tx.commit().await?;
+
     let response = AdminCertificateResponse {
         id: certificate.id,
         level: certificate.level,
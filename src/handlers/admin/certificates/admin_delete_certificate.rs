@@ -5,24 +5,45 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageCertificates, RequirePermission},
     error::AppError,
     models::*,
 };
 
+/// Move a certificate to the trash
 pub async fn admin_delete_certificate(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminSuccessResponse>, AppError> {
-    let result = sqlx::query("DELETE FROM certificates WHERE id = $1")
-        .bind(id)
-        .execute(&state.pool)
-        .await?;
+    let mut tx = state.pool.begin().await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    let certificate: Certificate =
+        sqlx::query_as(
+            "UPDATE certificates SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+        )
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.delete",
+            target_type: "certificate",
+            target_id: Some(id.to_string()),
+            before: snapshot(&certificate),
+            after: None,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
     Ok(Json(AdminSuccessResponse { success: true }))
 }
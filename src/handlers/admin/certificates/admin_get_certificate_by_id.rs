@@ -3,18 +3,24 @@ use axum::{
     extract::{Path, State},
 };
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    models::*,
+};
 
 pub async fn admin_get_certificate_by_id(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminItemResponse<AdminCertificateResponse>>, AppError> {
-    let certificate: Certificate = sqlx::query_as("SELECT * FROM certificates WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let certificate: Certificate =
+        sqlx::query_as("SELECT * FROM certificates WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
     let response = AdminCertificateResponse {
         id: certificate.id,
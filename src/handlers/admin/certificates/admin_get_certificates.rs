@@ -4,28 +4,75 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    models::*,
+    pagination::{ListParams, SortOrder, search_pattern},
+};
 
 #[derive(Deserialize)]
 pub struct AdminCertificateQuery {
     #[serde(rename = "includeHidden")]
     include_hidden: Option<bool>,
+    /// Only visible (`true`) or hidden (`false`) certificates; takes precedence over `includeHidden`
+    visible: Option<bool>,
+    level: Option<String>,
+    /// Matches the title, course title or holder's name
+    search: Option<String>,
 }
 
 pub async fn admin_get_certificates(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
     Query(query): Query<AdminCertificateQuery>,
+    list: ListParams,
 ) -> Result<Json<AdminItemsResponse<AdminCertificateResponse>>, AppError> {
-    let include_hidden = query.include_hidden.unwrap_or(false);
+    let visible = query
+        .visible
+        .or((!query.include_hidden.unwrap_or(false)).then_some(true));
+    let search = search_pattern(query.search);
+    let order_by = list.order_by(
+        &[
+            ("id", "id"),
+            ("title", "title"),
+            ("level", "level"),
+            ("courseTitle", "course_title"),
+            ("createdAt", "created_at"),
+            ("updatedAt", "updated_at"),
+        ],
+        ("id", SortOrder::Asc),
+        "id",
+    )?;
+
+    const FILTERS: &str = r#"
+        WHERE deleted_at IS NULL
+          AND ($1::BOOLEAN IS NULL OR visible = $1)
+          AND ($2::TEXT IS NULL OR level = $2)
+          AND ($3::TEXT IS NULL
+               OR title ILIKE $3
+               OR course_title ILIKE $3
+               OR first_name || ' ' || second_name ILIKE $3)
+    "#;
 
-    let sql = if include_hidden {
-        "SELECT * FROM certificates ORDER BY id"
-    } else {
-        "SELECT * FROM certificates WHERE visible = true ORDER BY id"
-    };
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM certificates {FILTERS}"))
+        .bind(visible)
+        .bind(&query.level)
+        .bind(&search)
+        .fetch_one(&state.pool)
+        .await?;
 
-    let certificates: Vec<Certificate> = sqlx::query_as(sql).fetch_all(&state.pool).await?;
+    let certificates: Vec<Certificate> = sqlx::query_as(&format!(
+        "SELECT * FROM certificates {FILTERS} {order_by} LIMIT $4 OFFSET $5"
+    ))
+    .bind(visible)
+    .bind(&query.level)
+    .bind(&search)
+    .bind(list.limit())
+    .bind(list.offset())
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<AdminCertificateResponse> = certificates
         .into_iter()
@@ -45,5 +92,8 @@ pub async fn admin_get_certificates(
         })
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(AdminItemsResponse {
+        items: responses,
+        pagination: Some(list.pagination(total)),
+    }))
 }
@@ -0,0 +1,22 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, list_trash},
+};
+
+/// Deleted certificates that can still be restored or purged
+pub async fn admin_get_trashed_certificates(
+    _auth: RequirePermission<ManageCertificates>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminItemsResponse<TrashedItem>>, AppError> {
+    let items = list_trash(&state.pool, TrashKind::Certificate).await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
@@ -5,25 +5,63 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageCertificates, RequirePermission},
     error::AppError,
+    events::{Event, publish_event},
     models::*,
+    notifications::{NewNotification, create_notification},
 };
 
 pub async fn admin_patch_certificate_visibility(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     Json(req): Json<AdminVisibilityRequest>,
 ) -> Result<Json<AdminItemResponse<AdminCertificateResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let existing: Certificate = sqlx::query_as(
+        "SELECT * FROM certificates WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
     let certificate: Certificate = sqlx::query_as(
         "UPDATE certificates SET visible = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
     )
     .bind(req.visible)
     .bind(id)
-    .fetch_optional(&state.pool)
-    .await?
-    .ok_or(AppError::NotFound)?;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if certificate.visible && !existing.visible {
+        publish_event(&mut tx, &Event::certificate_published(&certificate)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::certificate_published(&certificate),
+        )
+        .await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.visibility",
+            target_type: "certificate",
+            target_id: Some(id.to_string()),
+            before: snapshot(&existing),
+            after: snapshot(&certificate),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
     let response = AdminCertificateResponse {
         id: certificate.id,
@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, restore_from_trash},
+};
+
+/// Take a deleted certificate out of the trash
+pub async fn admin_restore_certificate(
+    auth: RequirePermission<ManageCertificates>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<i32>,
+) -> Result<Json<AdminSuccessResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let certificate = restore_from_trash(&mut tx, TrashKind::Certificate, id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.restore",
+            target_type: "certificate",
+            target_id: Some(id.to_string()),
+            before: None,
+            after: Some(certificate),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminSuccessResponse { success: true }))
+}
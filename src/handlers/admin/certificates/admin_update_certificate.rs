@@ -3,21 +3,32 @@ use axum::{
     extract::{Path, State},
 };
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageCertificates, RequirePermission},
+    error::AppError,
+    events::{Event, publish_event},
+    models::*,
+    notifications::{NewNotification, create_notification},
+};
 
 use super::normalize_youtube_url::normalize_youtube_url;
 
 pub async fn admin_update_certificate(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     Json(req): Json<AdminUpdateCertificateRequest>,
 ) -> Result<Json<AdminItemResponse<AdminCertificateResponse>>, AppError> {
-    let existing: Certificate = sqlx::query_as("SELECT * FROM certificates WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let existing: Certificate =
+        sqlx::query_as("SELECT * FROM certificates WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
 
     let level = req.level.unwrap_or(existing.level);
     let title = req.title.unwrap_or(existing.title);
@@ -32,6 +43,8 @@ pub async fn admin_update_certificate(
         .or(existing.youtube_url);
     let visible = req.visible.unwrap_or(existing.visible);
 
+    let mut tx = state.pool.begin().await?;
+
     let certificate: Certificate = sqlx::query_as(
         r#"
         UPDATE certificates
@@ -50,9 +63,34 @@ pub async fn admin_update_certificate(
     .bind(&youtube_url)
     .bind(visible)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    if certificate.visible && !existing.visible {
+        publish_event(&mut tx, &Event::certificate_published(&certificate)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::certificate_published(&certificate),
+        )
+        .await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.update",
+            target_type: "certificate",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&certificate),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminCertificateResponse {
         id: certificate.id,
         level: certificate.level,
@@ -5,25 +5,31 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageCertificates, RequirePermission},
     error::AppError,
+    events::{Event, publish_event},
     models::*,
+    notifications::{NewNotification, create_notification},
 };
 
 use super::normalize_youtube_url::normalize_youtube_url;
 use super::save_uploaded_file::save_uploaded_file;
 
 pub async fn admin_update_certificate_multipart(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageCertificates>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Json<AdminItemResponse<AdminCertificateResponse>>, AppError> {
-    let existing: Certificate = sqlx::query_as("SELECT * FROM certificates WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let existing: Certificate =
+        sqlx::query_as("SELECT * FROM certificates WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
 
     let mut level: Option<String> = None;
     let mut title: Option<String> = None;
@@ -137,6 +143,8 @@ pub async fn admin_update_certificate_multipart(
     let youtube_url = youtube_url.unwrap_or(existing.youtube_url);
     let visible = visible.unwrap_or(existing.visible);
 
+    let mut tx = state.pool.begin().await?;
+
     let certificate: Certificate = sqlx::query_as(
         r#"
         UPDATE certificates
@@ -155,9 +163,34 @@ pub async fn admin_update_certificate_multipart(
     .bind(&youtube_url)
     .bind(visible)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    if certificate.visible && !existing.visible {
+        publish_event(&mut tx, &Event::certificate_published(&certificate)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::certificate_published(&certificate),
+        )
+        .await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "certificate.update",
+            target_type: "certificate",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&certificate),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminCertificateResponse {
         id: certificate.id,
         level: certificate.level,
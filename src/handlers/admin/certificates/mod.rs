@@ -6,7 +6,10 @@ pub mod admin_create_certificate_multipart;
 pub mod admin_delete_certificate;
 pub mod admin_get_certificate_by_id;
 pub mod admin_get_certificates;
+pub mod admin_get_trashed_certificates;
 pub mod admin_patch_certificate_visibility;
+pub mod admin_purge_certificate;
+pub mod admin_restore_certificate;
 pub mod admin_update_certificate;
 pub mod admin_update_certificate_multipart;
 
@@ -15,6 +18,9 @@ pub use admin_create_certificate_multipart::admin_create_certificate_multipart;
 pub use admin_delete_certificate::admin_delete_certificate;
 pub use admin_get_certificate_by_id::admin_get_certificate_by_id;
 pub use admin_get_certificates::admin_get_certificates;
+pub use admin_get_trashed_certificates::admin_get_trashed_certificates;
 pub use admin_patch_certificate_visibility::admin_patch_certificate_visibility;
+pub use admin_purge_certificate::admin_purge_certificate;
+pub use admin_restore_certificate::admin_restore_certificate;
 pub use admin_update_certificate::admin_update_certificate;
 pub use admin_update_certificate_multipart::admin_update_certificate_multipart;
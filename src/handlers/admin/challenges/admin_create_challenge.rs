@@ -1,16 +1,34 @@
 use axum::{Json, extract::State};
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    events::{Event, publish_event},
+    late_policy::validate_late_policy,
+    models::*,
+    notifications::{NewNotification, create_notification},
+};
 
 pub async fn admin_create_challenge(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Json(req): Json<AdminCreateChallengeRequest>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
     let visible = req.visible.unwrap_or(true);
     let week = req.week.unwrap_or(1);
     let challenge_url = req.challenge_url.unwrap_or_default();
     let allowed_submissions = req.allowed_submissions.unwrap_or(3);
+    let grading_mode = req.grading_mode.as_deref().unwrap_or("manual");
+    let challenge_type = req.challenge_type.as_deref().unwrap_or("notebook");
+    let late_policy = req.late_policy.unwrap_or_else(|| LatePolicySettings {
+        policy: "cutoff".to_string(),
+        grace_minutes: 0,
+        penalty_percent: 0.0,
+        penalty_period: "day".to_string(),
+    });
 
     if allowed_submissions < 1 {
         return Err(AppError::BadRequest(
@@ -18,10 +36,26 @@ pub async fn admin_create_challenge(
         ));
     }
 
+    if GradingMode::parse(grading_mode).is_none() {
+        return Err(AppError::ValidationError(
+            "gradingMode must be one of manual, auto, auto_with_review".to_string(),
+        ));
+    }
+
+    if ChallengeType::parse(challenge_type).is_none() {
+        return Err(AppError::ValidationError(
+            "challengeType must be one of notebook, prediction".to_string(),
+        ));
+    }
+
+    validate_late_policy(&late_policy)?;
+
+    let mut tx = state.pool.begin().await?;
+
     let challenge: Challenge = sqlx::query_as(
         r#"
-        INSERT INTO challenges (title, description, start_date, end_date, visible, week, challenge_url, allowed_submissions, is_current, created_at, updated_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false, NOW(), NOW())
+        INSERT INTO challenges (title, description, start_date, end_date, visible, week, challenge_url, allowed_submissions, grading_mode, challenge_type, late_policy, late_grace_minutes, late_penalty_percent, late_penalty_period, is_current, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, false, NOW(), NOW())
         RETURNING *
         "#,
     )
@@ -33,9 +67,37 @@ pub async fn admin_create_challenge(
     .bind(week)
     .bind(&challenge_url)
     .bind(allowed_submissions)
-    .fetch_one(&state.pool)
+    .bind(grading_mode)
+    .bind(challenge_type)
+    .bind(&late_policy.policy)
+    .bind(late_policy.grace_minutes)
+    .bind(late_policy.penalty_percent)
+    .bind(&late_policy.penalty_period)
+    .fetch_one(&mut *tx)
     .await?;
 
+    if challenge.visible {
+        publish_event(&mut tx, &Event::challenge_published(&challenge)).await?;
+        create_notification(&mut tx, &NewNotification::challenge_published(&challenge)).await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.create",
+            target_type: "challenge",
+            target_id: Some(challenge.id.to_string()),
+            before: None,
+            after: snapshot(&challenge),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let late_policy = challenge.late_policy_settings();
     let response = AdminChallengeResponse {
         id: challenge.id,
         title: challenge.title,
@@ -44,6 +106,9 @@ pub async fn admin_create_challenge(
         start_date: challenge.start_date,
         end_date: challenge.end_date,
         visible: challenge.visible,
+        grading_mode: challenge.grading_mode,
+        challenge_type: challenge.challenge_type,
+        late_policy,
         created_at: challenge.created_at,
         updated_at: challenge.updated_at,
     };
@@ -5,24 +5,44 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
 
+/// Move a challenge to the trash; its notebook and submissions are kept until it is purged
 pub async fn admin_delete_challenge(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminSuccessResponse>, AppError> {
-    let result = sqlx::query("DELETE FROM challenges WHERE id = $1")
-        .bind(id)
-        .execute(&state.pool)
-        .await?;
+    let mut tx = state.pool.begin().await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    let challenge: Challenge = sqlx::query_as(
+        "UPDATE challenges SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.delete",
+            target_type: "challenge",
+            target_id: Some(id.to_string()),
+            before: snapshot(&challenge),
+            after: None,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
     Ok(Json(AdminSuccessResponse { success: true }))
 }
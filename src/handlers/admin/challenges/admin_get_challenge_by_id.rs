@@ -5,22 +5,24 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
 
 pub async fn admin_get_challenge_by_id(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let challenge: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
+    let late_policy = challenge.late_policy_settings();
     let response = AdminChallengeResponse {
         id: challenge.id,
         title: challenge.title,
@@ -29,6 +31,9 @@ pub async fn admin_get_challenge_by_id(
         start_date: challenge.start_date,
         end_date: challenge.end_date,
         visible: challenge.visible,
+        grading_mode: challenge.grading_mode,
+        challenge_type: challenge.challenge_type,
+        late_policy,
         created_at: challenge.created_at,
         updated_at: challenge.updated_at,
     };
@@ -0,0 +1,32 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    models::*,
+    rubric::load_rubric,
+};
+
+/// The criteria a challenge is graded against; graders need them to score submissions
+pub async fn admin_get_challenge_rubric(
+    _auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+) -> Result<Json<AdminItemsResponse<RubricCriterion>>, AppError> {
+    sqlx::query_scalar::<_, i32>("SELECT id FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+        .bind(challenge_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let criteria = load_rubric(&state.pool, challenge_id).await?;
+
+    Ok(Json(AdminItemsResponse {
+        items: criteria,
+        pagination: None,
+    }))
+}
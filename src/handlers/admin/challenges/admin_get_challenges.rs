@@ -4,43 +4,99 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    pagination::{ListParams, SortOrder, search_pattern},
+};
 
 #[derive(Deserialize)]
 pub struct AdminChallengeQuery {
     #[serde(rename = "includeHidden")]
     include_hidden: Option<bool>,
+    /// Only visible (`true`) or hidden (`false`) challenges; takes precedence over `includeHidden`
+    visible: Option<bool>,
+    #[serde(rename = "gradingMode")]
+    grading_mode: Option<String>,
+    /// Matches the title
+    search: Option<String>,
 }
 
 pub async fn admin_get_challenges(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
     Query(query): Query<AdminChallengeQuery>,
+    list: ListParams,
 ) -> Result<Json<AdminItemsResponse<AdminChallengeResponse>>, AppError> {
-    let include_hidden = query.include_hidden.unwrap_or(false);
+    let visible = query
+        .visible
+        .or((!query.include_hidden.unwrap_or(false)).then_some(true));
+    let search = search_pattern(query.search);
+    let order_by = list.order_by(
+        &[
+            ("id", "id"),
+            ("title", "title"),
+            ("week", "week"),
+            ("startDate", "start_date"),
+            ("endDate", "end_date"),
+            ("createdAt", "created_at"),
+            ("updatedAt", "updated_at"),
+        ],
+        ("id", SortOrder::Asc),
+        "id",
+    )?;
+
+    const FILTERS: &str = r#"
+        WHERE deleted_at IS NULL
+          AND ($1::BOOLEAN IS NULL OR visible = $1)
+          AND ($2::TEXT IS NULL OR grading_mode = $2)
+          AND ($3::TEXT IS NULL OR title ILIKE $3)
+    "#;
 
-    let sql = if include_hidden {
-        "SELECT * FROM challenges ORDER BY id"
-    } else {
-        "SELECT * FROM challenges WHERE visible = true ORDER BY id"
-    };
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM challenges {FILTERS}"))
+        .bind(visible)
+        .bind(&query.grading_mode)
+        .bind(&search)
+        .fetch_one(&state.pool)
+        .await?;
 
-    let challenges: Vec<Challenge> = sqlx::query_as(sql).fetch_all(&state.pool).await?;
+    let challenges: Vec<Challenge> = sqlx::query_as(&format!(
+        "SELECT * FROM challenges {FILTERS} {order_by} LIMIT $4 OFFSET $5"
+    ))
+    .bind(visible)
+    .bind(&query.grading_mode)
+    .bind(&search)
+    .bind(list.limit())
+    .bind(list.offset())
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<AdminChallengeResponse> = challenges
         .into_iter()
-        .map(|c| AdminChallengeResponse {
-            id: c.id,
-            title: c.title,
-            description: c.description,
-            allowed_submissions: c.allowed_submissions,
-            start_date: c.start_date,
-            end_date: c.end_date,
-            visible: c.visible,
-            created_at: c.created_at,
-            updated_at: c.updated_at,
+        .map(|c| {
+            let late_policy = c.late_policy_settings();
+
+            AdminChallengeResponse {
+                id: c.id,
+                title: c.title,
+                description: c.description,
+                allowed_submissions: c.allowed_submissions,
+                start_date: c.start_date,
+                end_date: c.end_date,
+                visible: c.visible,
+                grading_mode: c.grading_mode,
+                challenge_type: c.challenge_type,
+                late_policy,
+                created_at: c.created_at,
+                updated_at: c.updated_at,
+            }
         })
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(AdminItemsResponse {
+        items: responses,
+        pagination: Some(list.pagination(total)),
+    }))
 }
@@ -0,0 +1,31 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    prediction::load_prediction_task,
+};
+
+/// How a prediction challenge is scored; the ground truth itself is never sent back
+pub async fn admin_get_prediction_task(
+    _auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+) -> Result<Json<AdminItemResponse<PredictionTask>>, AppError> {
+    sqlx::query_scalar::<_, i32>("SELECT id FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+        .bind(challenge_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let task = load_prediction_task(&state.pool, challenge_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    Ok(Json(AdminItemResponse { item: task }))
+}
@@ -0,0 +1,22 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, list_trash},
+};
+
+/// Deleted challenges that can still be restored or purged
+pub async fn admin_get_trashed_challenges(
+    _auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminItemsResponse<TrashedItem>>, AppError> {
+    let items = list_trash(&state.pool, TrashKind::Challenge).await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
@@ -5,26 +5,60 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
+    events::{Event, publish_event},
     models::*,
+    notifications::{NewNotification, create_notification},
 };
 
 pub async fn admin_patch_challenge_visibility(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     Json(req): Json<AdminVisibilityRequest>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let existing: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
     let challenge: Challenge = sqlx::query_as(
         "UPDATE challenges SET visible = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
     )
     .bind(req.visible)
     .bind(id)
-    .fetch_optional(&state.pool)
-    .await?
-    .ok_or(AppError::NotFound)?;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if challenge.visible && !existing.visible {
+        publish_event(&mut tx, &Event::challenge_published(&challenge)).await?;
+        create_notification(&mut tx, &NewNotification::challenge_published(&challenge)).await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.visibility",
+            target_type: "challenge",
+            target_id: Some(id.to_string()),
+            before: snapshot(&existing),
+            after: snapshot(&challenge),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
+    let late_policy = challenge.late_policy_settings();
     let response = AdminChallengeResponse {
         id: challenge.id,
         title: challenge.title,
@@ -33,6 +67,9 @@ pub async fn admin_patch_challenge_visibility(
         start_date: challenge.start_date,
         end_date: challenge.end_date,
         visible: challenge.visible,
+        grading_mode: challenge.grading_mode,
+        challenge_type: challenge.challenge_type,
+        late_policy,
         created_at: challenge.created_at,
         updated_at: challenge.updated_at,
     };
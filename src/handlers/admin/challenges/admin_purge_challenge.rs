@@ -0,0 +1,55 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, purge_from_trash},
+};
+
+/// Permanently delete a challenge from the trash
+pub async fn admin_purge_challenge(
+    auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<i32>,
+    Query(query): Query<AdminPurgeQuery>,
+) -> Result<Json<AdminPurgeResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let purged = purge_from_trash(
+        &mut tx,
+        TrashKind::Challenge,
+        id,
+        query.force.unwrap_or(false),
+    )
+    .await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.purge",
+            target_type: "challenge",
+            target_id: Some(id.to_string()),
+            before: Some(purged.item.clone()),
+            after: None,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    purged.remove_files().await;
+
+    Ok(Json(AdminPurgeResponse {
+        success: true,
+        submissions_deleted: purged.submissions_deleted,
+    }))
+}
@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, restore_from_trash},
+};
+
+/// Take a deleted challenge out of the trash
+pub async fn admin_restore_challenge(
+    auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<i32>,
+) -> Result<Json<AdminSuccessResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let challenge = restore_from_trash(&mut tx, TrashKind::Challenge, id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.restore",
+            target_type: "challenge",
+            target_id: Some(id.to_string()),
+            before: None,
+            after: Some(challenge),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminSuccessResponse { success: true }))
+}
@@ -5,22 +5,33 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
+    events::{Event, publish_event},
+    late_policy::validate_late_policy,
     models::*,
+    notifications::{NewNotification, create_notification},
 };
 
 pub async fn admin_update_challenge(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
-    Json(req): Json<AdminUpdateChallengeRequest>,
+    Json(mut req): Json<AdminUpdateChallengeRequest>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeResponse>>, AppError> {
-    let existing: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let existing: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
+    let late_policy = req
+        .late_policy
+        .take()
+        .unwrap_or_else(|| existing.late_policy_settings());
 
     let title = req.title.unwrap_or(existing.title);
     let description = req.description.unwrap_or(existing.description);
@@ -32,6 +43,8 @@ pub async fn admin_update_challenge(
     let start_date = req.start_date.or(existing.start_date);
     let end_date = req.end_date.or(existing.end_date);
     let visible = req.visible.unwrap_or(existing.visible);
+    let grading_mode = req.grading_mode.unwrap_or(existing.grading_mode);
+    let challenge_type = req.challenge_type.unwrap_or(existing.challenge_type);
 
     if allowed_submissions < 1 {
         return Err(AppError::BadRequest(
@@ -39,11 +52,29 @@ pub async fn admin_update_challenge(
         ));
     }
 
+    if GradingMode::parse(&grading_mode).is_none() {
+        return Err(AppError::ValidationError(
+            "gradingMode must be one of manual, auto, auto_with_review".to_string(),
+        ));
+    }
+
+    if ChallengeType::parse(&challenge_type).is_none() {
+        return Err(AppError::ValidationError(
+            "challengeType must be one of notebook, prediction".to_string(),
+        ));
+    }
+
+    validate_late_policy(&late_policy)?;
+
+    let mut tx = state.pool.begin().await?;
+
     let challenge: Challenge = sqlx::query_as(
         r#"
         UPDATE challenges 
-        SET title = $1, description = $2, week = $3, challenge_url = $4, allowed_submissions = $5, start_date = $6, end_date = $7, visible = $8, updated_at = NOW()
-        WHERE id = $9
+        SET title = $1, description = $2, week = $3, challenge_url = $4, allowed_submissions = $5, start_date = $6, end_date = $7, visible = $8, grading_mode = $9, challenge_type = $10,
+            late_policy = $11, late_grace_minutes = $12, late_penalty_percent = $13, late_penalty_period = $14,
+            updated_at = NOW()
+        WHERE id = $15
         RETURNING *
         "#,
     )
@@ -55,10 +86,38 @@ pub async fn admin_update_challenge(
     .bind(start_date)
     .bind(end_date)
     .bind(visible)
+    .bind(&grading_mode)
+    .bind(&challenge_type)
+    .bind(&late_policy.policy)
+    .bind(late_policy.grace_minutes)
+    .bind(late_policy.penalty_percent)
+    .bind(&late_policy.penalty_period)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    if challenge.visible && !existing.visible {
+        publish_event(&mut tx, &Event::challenge_published(&challenge)).await?;
+        create_notification(&mut tx, &NewNotification::challenge_published(&challenge)).await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.update",
+            target_type: "challenge",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&challenge),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    let late_policy = challenge.late_policy_settings();
     let response = AdminChallengeResponse {
         id: challenge.id,
         title: challenge.title,
@@ -67,6 +126,9 @@ pub async fn admin_update_challenge(
         start_date: challenge.start_date,
         end_date: challenge.end_date,
         visible: challenge.visible,
+        grading_mode: challenge.grading_mode,
+        challenge_type: challenge.challenge_type,
+        late_policy,
         created_at: challenge.created_at,
         updated_at: challenge.updated_at,
     };
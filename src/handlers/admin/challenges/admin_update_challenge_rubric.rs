@@ -0,0 +1,108 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    rubric::load_rubric,
+};
+
+const MAX_RUBRIC_CRITERIA: usize = 50;
+
+/// Replace a challenge's rubric. Submissions already graded keep the rubric they were scored with.
+pub async fn admin_update_challenge_rubric(
+    auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(challenge_id): Path<i32>,
+    Json(req): Json<AdminUpdateRubricRequest>,
+) -> Result<Json<AdminItemsResponse<RubricCriterion>>, AppError> {
+    if req.criteria.len() > MAX_RUBRIC_CRITERIA {
+        return Err(AppError::BadRequest(format!(
+            "A rubric can have at most {MAX_RUBRIC_CRITERIA} criteria"
+        )));
+    }
+    for criterion in &req.criteria {
+        if criterion.title.trim().is_empty() {
+            return Err(AppError::BadRequest(
+                "Every criterion needs a title".to_string(),
+            ));
+        }
+        if criterion.max_points <= 0.0 {
+            return Err(AppError::BadRequest(format!(
+                "maxPoints for \"{}\" must be greater than 0",
+                criterion.title
+            )));
+        }
+        if criterion.weight.is_some_and(|weight| weight <= 0.0) {
+            return Err(AppError::BadRequest(format!(
+                "weight for \"{}\" must be greater than 0",
+                criterion.title
+            )));
+        }
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    // Lock the challenge so concurrent edits replace the rubric one after another
+    sqlx::query_scalar::<_, i32>(
+        "SELECT id FROM challenges WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let before = load_rubric(&mut *tx, challenge_id).await?;
+
+    sqlx::query("DELETE FROM challenge_rubric_criteria WHERE challenge_id = $1")
+        .bind(challenge_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for (position, criterion) in req.criteria.into_iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO challenge_rubric_criteria
+                (challenge_id, position, title, description, weight, max_points)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(challenge_id)
+        .bind(position as i32)
+        .bind(criterion.title.trim())
+        .bind(criterion.description.filter(|d| !d.trim().is_empty()))
+        .bind(criterion.weight.unwrap_or(1.0))
+        .bind(criterion.max_points)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let criteria = load_rubric(&mut *tx, challenge_id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.rubric_update",
+            target_type: "challenge",
+            target_id: Some(challenge_id.to_string()),
+            before: snapshot(&before),
+            after: snapshot(&criteria),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemsResponse {
+        items: criteria,
+        pagination: None,
+    }))
+}
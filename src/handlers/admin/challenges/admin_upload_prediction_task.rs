@@ -0,0 +1,244 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+    prediction::{
+        PredictionMetric, PredictionRow, load_prediction_task, public_ids, read_prediction_csv,
+    },
+};
+
+/// Upload the hidden ground truth `file` of a prediction challenge with the `metric` it is
+/// scored by. An earlier test set can be replaced until students submit predictions, after
+/// which their scores and the split depend on it. With `publicPercent`, only that share of the rows is ranked while the challenge runs and
+/// the rest make up the private leaderboard revealed at the end date.
+pub async fn admin_upload_prediction_task(
+    auth: RequirePermission<ManageChallenges>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(challenge_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Json<AdminItemResponse<PredictionTask>>, AppError> {
+    let mut metric: Option<String> = None;
+    let mut id_column = "id".to_string();
+    let mut target_column = "target".to_string();
+    let mut baseline: Option<f64> = None;
+    let mut max_points: i32 = 100;
+    let mut public_percent: Option<i32> = None;
+    let mut final_submissions: i32 = 2;
+    let mut filename: Option<String> = None;
+    let mut ground_truth: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+    {
+        let field_name = field.name().unwrap_or("").to_string();
+        if field_name == "file" {
+            filename = Some(field.file_name().unwrap_or("ground_truth.csv").to_string());
+            ground_truth = Some(
+                field
+                    .bytes()
+                    .await
+                    .map_err(|e| AppError::InternalError(e.into()))?
+                    .to_vec(),
+            );
+            continue;
+        }
+
+        let text = field
+            .text()
+            .await
+            .map_err(|e| AppError::InternalError(e.into()))?;
+        let text = text.trim();
+
+        match field_name.as_str() {
+            "metric" => metric = Some(text.to_string()),
+            "idColumn" if !text.is_empty() => id_column = text.to_string(),
+            "targetColumn" if !text.is_empty() => target_column = text.to_string(),
+            "baseline" if !text.is_empty() => {
+                baseline = Some(
+                    text.parse()
+                        .map_err(|_| AppError::BadRequest("Invalid baseline".to_string()))?,
+                );
+            }
+            "maxPoints" => {
+                max_points = text
+                    .parse()
+                    .map_err(|_| AppError::BadRequest("Invalid maxPoints".to_string()))?;
+            }
+            "publicPercent" if !text.is_empty() => {
+                public_percent = Some(
+                    text.parse()
+                        .map_err(|_| AppError::BadRequest("Invalid publicPercent".to_string()))?,
+                );
+            }
+            "finalSubmissions" => {
+                final_submissions = text
+                    .parse()
+                    .map_err(|_| AppError::BadRequest("Invalid finalSubmissions".to_string()))?;
+            }
+            _ => {}
+        }
+    }
+
+    let metric = metric
+        .as_deref()
+        .and_then(PredictionMetric::parse)
+        .ok_or_else(|| {
+            AppError::ValidationError(
+                "metric must be one of accuracy, macro_f1, rmse, mae, log_loss, auc".to_string(),
+            )
+        })?;
+    metric.validate_baseline(baseline)?;
+    if max_points < 0 {
+        return Err(AppError::BadRequest(
+            "maxPoints cannot be negative".to_string(),
+        ));
+    }
+    if let Some(percent) = public_percent
+        && !(1..=99).contains(&percent)
+    {
+        return Err(AppError::ValidationError(
+            "publicPercent must be between 1 and 99".to_string(),
+        ));
+    }
+    if final_submissions < 1 {
+        return Err(AppError::ValidationError(
+            "finalSubmissions must be at least 1".to_string(),
+        ));
+    }
+    if id_column.eq_ignore_ascii_case(&target_column) {
+        return Err(AppError::BadRequest(
+            "idColumn and targetColumn must be different columns".to_string(),
+        ));
+    }
+
+    let ground_truth = ground_truth
+        .ok_or_else(|| AppError::BadRequest("A ground truth CSV file is required".to_string()))?;
+    let rows = read_prediction_csv(&ground_truth, &id_column, &target_column)?;
+    metric.validate_ground_truth(&rows)?;
+
+    // Each part of a split is scored on its own; the values were checked above, so only
+    // a part missing one of the classes log-loss and AUC need can fail here
+    if let Some(percent) = public_percent {
+        if rows.len() < 2 {
+            return Err(AppError::BadRequest(
+                "A public/private split needs at least two rows".to_string(),
+            ));
+        }
+        let public = public_ids(challenge_id, &rows, percent);
+        let (public_rows, private_rows): (Vec<PredictionRow>, Vec<PredictionRow>) = rows
+            .iter()
+            .cloned()
+            .partition(|row| public.contains(row.id.as_str()));
+        for (part, part_rows) in [("public", public_rows), ("private", private_rows)] {
+            metric.validate_ground_truth(&part_rows).map_err(|_| {
+                AppError::BadRequest(format!(
+                    "The {part} rows of the split must contain both 0 and 1 labels"
+                ))
+            })?;
+        }
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let (challenge_type, end_date): (String, Option<time::OffsetDateTime>) = sqlx::query_as(
+        "SELECT challenge_type, end_date FROM challenges WHERE id = $1 AND deleted_at IS NULL FOR UPDATE",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if ChallengeType::parse(&challenge_type) != Some(ChallengeType::Prediction) {
+        return Err(AppError::BadRequest(
+            "Only prediction challenges take a ground truth file".to_string(),
+        ));
+    }
+    if public_percent.is_some() && end_date.is_none() {
+        return Err(AppError::BadRequest(
+            "A private leaderboard is revealed at the end date; set one for the challenge first"
+                .to_string(),
+        ));
+    }
+
+    // Stored scores, points and the split of every attempt were computed from the current
+    // test set; predictions hold the challenge row while they are stored, so none slip in
+    let submitted: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM challenge_submissions WHERE challenge_id = $1)",
+    )
+    .bind(challenge_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if submitted {
+        return Err(AppError::Conflict(
+            "Students have already submitted predictions; the ground truth can no longer be replaced"
+                .to_string(),
+        ));
+    }
+
+    let before = load_prediction_task(&mut *tx, challenge_id).await?;
+
+    let task: PredictionTask = sqlx::query_as(
+        r#"
+        INSERT INTO challenge_prediction_tasks
+            (challenge_id, metric, id_column, target_column, baseline, max_points,
+             ground_truth_filename, ground_truth, row_count, public_percent, final_submissions)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (challenge_id) DO UPDATE
+        SET metric = EXCLUDED.metric,
+            id_column = EXCLUDED.id_column,
+            target_column = EXCLUDED.target_column,
+            baseline = EXCLUDED.baseline,
+            max_points = EXCLUDED.max_points,
+            ground_truth_filename = EXCLUDED.ground_truth_filename,
+            ground_truth = EXCLUDED.ground_truth,
+            row_count = EXCLUDED.row_count,
+            public_percent = EXCLUDED.public_percent,
+            final_submissions = EXCLUDED.final_submissions,
+            updated_at = NOW()
+        RETURNING id, challenge_id, metric, id_column, target_column, baseline, max_points,
+                  ground_truth_filename, row_count, public_percent, final_submissions,
+                  revealed_at, created_at, updated_at
+        "#,
+    )
+    .bind(challenge_id)
+    .bind(metric.as_str())
+    .bind(&id_column)
+    .bind(&target_column)
+    .bind(baseline)
+    .bind(max_points)
+    .bind(filename.unwrap_or_default())
+    .bind(&ground_truth)
+    .bind(rows.len() as i32)
+    .bind(public_percent)
+    .bind(final_submissions)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.prediction_task_update",
+            target_type: "challenge",
+            target_id: Some(challenge_id.to_string()),
+            before: before.as_ref().and_then(snapshot),
+            after: snapshot(&task),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse { item: task }))
+}
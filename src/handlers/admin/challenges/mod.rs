@@ -1,13 +1,27 @@
-pub mod admin_get_challenges;
-pub mod admin_get_challenge_by_id;
 pub mod admin_create_challenge;
-pub mod admin_update_challenge;
 pub mod admin_delete_challenge;
+pub mod admin_get_challenge_by_id;
+pub mod admin_get_challenge_rubric;
+pub mod admin_get_challenges;
+pub mod admin_get_prediction_task;
+pub mod admin_get_trashed_challenges;
 pub mod admin_patch_challenge_visibility;
+pub mod admin_purge_challenge;
+pub mod admin_restore_challenge;
+pub mod admin_update_challenge;
+pub mod admin_update_challenge_rubric;
+pub mod admin_upload_prediction_task;
 
-pub use admin_get_challenges::admin_get_challenges;
-pub use admin_get_challenge_by_id::admin_get_challenge_by_id;
 pub use admin_create_challenge::admin_create_challenge;
-pub use admin_update_challenge::admin_update_challenge;
 pub use admin_delete_challenge::admin_delete_challenge;
+pub use admin_get_challenge_by_id::admin_get_challenge_by_id;
+pub use admin_get_challenge_rubric::admin_get_challenge_rubric;
+pub use admin_get_challenges::admin_get_challenges;
+pub use admin_get_prediction_task::admin_get_prediction_task;
+pub use admin_get_trashed_challenges::admin_get_trashed_challenges;
 pub use admin_patch_challenge_visibility::admin_patch_challenge_visibility;
+pub use admin_purge_challenge::admin_purge_challenge;
+pub use admin_restore_challenge::admin_restore_challenge;
+pub use admin_update_challenge::admin_update_challenge;
+pub use admin_update_challenge_rubric::admin_update_challenge_rubric;
+pub use admin_upload_prediction_task::admin_upload_prediction_task;
@@ -0,0 +1,65 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+#[derive(Deserialize)]
+pub struct AdminGradingJobQuery {
+    /// `pending`, `succeeded` or `failed`
+    status: Option<String>,
+    #[serde(rename = "submissionId")]
+    submission_id: Option<uuid::Uuid>,
+    limit: Option<i64>,
+}
+
+/// Queue state of grading service calls, newest first
+pub async fn admin_get_grading_jobs(
+    _auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    Query(query): Query<AdminGradingJobQuery>,
+) -> Result<Json<AdminGradingQueueResponse>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    let (pending, failed, succeeded): (i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE status = 'pending'),
+            COUNT(*) FILTER (WHERE status = 'failed'),
+            COUNT(*) FILTER (WHERE status = 'succeeded')
+        FROM grading_jobs
+        "#,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    let items: Vec<GradingJob> = sqlx::query_as(
+        r#"
+        SELECT *
+        FROM grading_jobs
+        WHERE ($1::VARCHAR IS NULL OR status = $1)
+          AND ($2::UUID IS NULL OR submission_id = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(&query.status)
+    .bind(query.submission_id)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AdminGradingQueueResponse {
+        pending,
+        failed,
+        succeeded,
+        items,
+    }))
+}
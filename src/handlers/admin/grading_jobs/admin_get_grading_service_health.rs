@@ -0,0 +1,27 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+/// Whether the grading service is reachable, so admins can tell a down service from a stuck queue
+pub async fn admin_get_grading_service_health(
+    _auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminGradingServiceHealthResponse>, AppError> {
+    let response = match state.grading.health().await {
+        Ok(()) => AdminGradingServiceHealthResponse {
+            healthy: true,
+            message: "Grading service is reachable".to_string(),
+        },
+        Err(e) => AdminGradingServiceHealthResponse {
+            healthy: false,
+            message: format!("{e:#}"),
+        },
+    };
+
+    Ok(Json(response))
+}
@@ -0,0 +1,39 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    grading::retry_grading_job,
+    models::*,
+};
+
+/// Deliver a pending or failed grading job now and return its updated state
+pub async fn admin_retry_grading_job(
+    auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(job_id): Path<uuid::Uuid>,
+) -> Result<Json<AdminItemResponse<GradingJob>>, AppError> {
+    let job = retry_grading_job(&state.pool, state.grading.as_ref(), job_id).await?;
+
+    record_audit(
+        &mut *state.pool.acquire().await?,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "grading_job.retry",
+            target_type: "grading_job",
+            target_id: Some(job_id.to_string()),
+            before: None,
+            after: snapshot(&job),
+        },
+    )
+    .await?;
+
+    Ok(Json(AdminItemResponse { item: job }))
+}
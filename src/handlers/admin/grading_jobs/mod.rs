@@ -0,0 +1,7 @@
+pub mod admin_get_grading_jobs;
+pub mod admin_get_grading_service_health;
+pub mod admin_retry_grading_job;
+
+pub use admin_get_grading_jobs::admin_get_grading_jobs;
+pub use admin_get_grading_service_health::admin_get_grading_service_health;
+pub use admin_retry_grading_job::admin_retry_grading_job;
@@ -1,34 +1,68 @@
+#[path = "audit_log/mod.rs"]
+pub mod audit_log;
 #[path = "certificates/mod.rs"]
 pub mod certificates;
 #[path = "challenges/mod.rs"]
 pub mod challenges;
+#[path = "grading_jobs/mod.rs"]
+pub mod grading_jobs;
 #[path = "notebooks/mod.rs"]
 pub mod notebooks;
+#[path = "points/mod.rs"]
+pub mod points;
 #[path = "resources/mod.rs"]
 pub mod resources;
+#[path = "roles/mod.rs"]
+pub mod roles;
 #[path = "submissions/mod.rs"]
 pub mod submissions;
+#[path = "users/mod.rs"]
+pub mod users;
+#[path = "webhooks/mod.rs"]
+pub mod webhooks;
 
+pub use audit_log::admin_get_audit_log;
 pub use certificates::{
     admin_create_certificate, admin_create_certificate_multipart, admin_delete_certificate,
-    admin_get_certificate_by_id, admin_get_certificates, admin_patch_certificate_visibility,
+    admin_get_certificate_by_id, admin_get_certificates, admin_get_trashed_certificates,
+    admin_patch_certificate_visibility, admin_purge_certificate, admin_restore_certificate,
     admin_update_certificate, admin_update_certificate_multipart,
 };
 pub use challenges::{
     admin_create_challenge, admin_delete_challenge, admin_get_challenge_by_id,
-    admin_get_challenges, admin_patch_challenge_visibility, admin_update_challenge,
+    admin_get_challenge_rubric, admin_get_challenges, admin_get_prediction_task,
+    admin_get_trashed_challenges, admin_patch_challenge_visibility, admin_purge_challenge,
+    admin_restore_challenge, admin_update_challenge, admin_update_challenge_rubric,
+    admin_upload_prediction_task,
+};
+pub use grading_jobs::{
+    admin_get_grading_jobs, admin_get_grading_service_health, admin_retry_grading_job,
 };
 pub use notebooks::{
     admin_create_notebook_multipart, admin_delete_notebook, admin_get_notebook_by_challenge,
-    admin_get_notebook_edit_url, admin_get_notebooks, admin_sync_notebook_to_nbgrader,
+    admin_get_notebook_edit_url, admin_get_notebooks, admin_get_trashed_notebooks,
+    admin_purge_notebook, admin_restore_notebook, admin_sync_notebook_to_nbgrader,
     admin_update_notebook,
 };
+pub use points::{admin_recompute_challenge_points, admin_recompute_points};
 pub use resources::{
     admin_create_resource, admin_create_resource_multipart, admin_delete_resource,
-    admin_get_resource_by_id, admin_get_resources, admin_patch_resource_visibility,
+    admin_get_resource_by_id, admin_get_resources, admin_get_trashed_resources,
+    admin_patch_resource_visibility, admin_purge_resource, admin_restore_resource,
     admin_update_resource, admin_update_resource_multipart,
 };
+pub use roles::admin_get_roles;
 pub use submissions::{
-    admin_get_submission_access, admin_get_submission_file, admin_get_submissions,
-    admin_grade_submission,
+    admin_bulk_grade_submissions, admin_bulk_grade_submissions_multipart,
+    admin_export_challenge_grades, admin_export_submissions, admin_get_submission_access,
+    admin_get_submission_file, admin_get_submissions, admin_grade_submission,
+};
+pub use users::{
+    admin_adjust_user_points, admin_get_user_by_id, admin_get_users,
+    admin_reset_user_jupyterhub_username, admin_suspend_user, admin_unsuspend_user,
+    admin_update_user_role,
+};
+pub use webhooks::{
+    admin_create_webhook, admin_delete_webhook, admin_get_webhook_deliveries, admin_get_webhooks,
+    admin_retry_webhook_delivery, admin_update_webhook,
 };
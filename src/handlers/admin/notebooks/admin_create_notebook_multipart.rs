@@ -1,12 +1,19 @@
 use axum::{Json, extract::State};
 use uuid::Uuid;
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::*};
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
+    error::AppError,
+    models::*,
+};
 
 /// Create/upload a notebook for a challenge (admin)
 pub async fn admin_create_notebook_multipart(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Json<AdminItemResponse<AdminChallengeNotebookResponse>>, AppError> {
     use tokio::io::AsyncWriteExt;
@@ -107,18 +114,20 @@ pub async fn admin_create_notebook_multipart(
         notebook_data.ok_or_else(|| AppError::BadRequest("Missing notebook file".to_string()))?;
 
     // Verify challenge exists
-    let _challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(challenge_id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or_else(|| AppError::BadRequest("Challenge not found".to_string()))?;
-
-    // Check if notebook already exists for this challenge
-    let existing: Option<ChallengeNotebook> =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE challenge_id = $1")
+    let _challenge: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
             .bind(challenge_id)
             .fetch_optional(&state.pool)
-            .await?;
+            .await?
+            .ok_or_else(|| AppError::BadRequest("Challenge not found".to_string()))?;
+
+    // Check if notebook already exists for this challenge
+    let existing: Option<ChallengeNotebook> = sqlx::query_as(
+        "SELECT * FROM challenge_notebooks WHERE challenge_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?;
 
     if existing.is_some() {
         return Err(AppError::BadRequest(
@@ -127,7 +136,7 @@ pub async fn admin_create_notebook_multipart(
     }
 
     let existing_assignment: Option<(i32,)> =
-        sqlx::query_as("SELECT id FROM challenge_notebooks WHERE assignment_name = $1 LIMIT 1")
+        sqlx::query_as("SELECT id FROM challenge_notebooks WHERE assignment_name = $1 AND deleted_at IS NULL LIMIT 1")
             .bind(&assignment_name)
             .fetch_optional(&state.pool)
             .await?;
@@ -158,6 +167,7 @@ pub async fn admin_create_notebook_multipart(
     })?;
 
     // Insert into database
+    let mut tx = state.pool.begin().await?;
     let notebook_result = sqlx::query_as(
         r#"
         INSERT INTO challenge_notebooks 
@@ -175,7 +185,7 @@ pub async fn admin_create_notebook_multipart(
     .bind(&memory_limit)
     .bind(time_limit_minutes)
     .bind(network_disabled)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await;
 
     let notebook: ChallengeNotebook = match notebook_result {
@@ -196,6 +206,22 @@ pub async fn admin_create_notebook_multipart(
         Err(e) => return Err(AppError::DatabaseError(e)),
     };
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "notebook.create",
+            target_type: "notebook",
+            target_id: Some(notebook.id.to_string()),
+            before: None,
+            after: snapshot(&notebook),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminChallengeNotebookResponse {
         id: notebook.id,
         challenge_id: notebook.challenge_id,
@@ -5,37 +5,43 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
 
-/// Delete a notebook (admin)
+/// Move a notebook to the trash; its file is kept until it is purged (admin)
 pub async fn admin_delete_notebook(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(notebook_id): Path<i32>,
 ) -> Result<Json<AdminSuccessResponse>, AppError> {
-    // Get notebook to delete the file
-    let notebook: Option<ChallengeNotebook> =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1")
-            .bind(notebook_id)
-            .fetch_optional(&state.pool)
-            .await?;
+    let mut tx = state.pool.begin().await?;
 
-    if let Some(nb) = notebook {
-        // Delete the file
-        let _ = tokio::fs::remove_file(&nb.notebook_path).await;
-    }
+    let notebook: ChallengeNotebook =
+        sqlx::query_as("UPDATE challenge_notebooks SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *")
+            .bind(notebook_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
-    let result = sqlx::query("DELETE FROM challenge_notebooks WHERE id = $1")
-        .bind(notebook_id)
-        .execute(&state.pool)
-        .await?;
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "notebook.delete",
+            target_type: "notebook",
+            target_id: Some(notebook_id.to_string()),
+            before: snapshot(&notebook),
+            after: None,
+        },
+    )
+    .await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    tx.commit().await?;
 
     Ok(Json(AdminSuccessResponse { success: true }))
 }
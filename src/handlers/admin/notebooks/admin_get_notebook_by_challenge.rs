@@ -5,23 +5,24 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
 
 /// Get notebook for a specific challenge (admin)
 pub async fn admin_get_notebook_by_challenge(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
     Path(challenge_id): Path<i32>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeNotebookResponse>>, AppError> {
-    let notebook: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE challenge_id = $1")
-            .bind(challenge_id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or(AppError::NotFound)?;
+    let notebook: ChallengeNotebook = sqlx::query_as(
+        "SELECT * FROM challenge_notebooks WHERE challenge_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
 
     let response = AdminChallengeNotebookResponse {
         id: notebook.id,
@@ -5,7 +5,7 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
@@ -13,13 +13,13 @@ use crate::{
 /// Admin access to JupyterHub for editing notebooks
 /// Returns a JupyterHub URL where admin can edit the source notebook with grading cells
 pub async fn admin_get_notebook_edit_url(
-    auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
     Path(notebook_id): Path<i32>,
 ) -> Result<Json<AdminJupyterHubAccessResponse>, AppError> {
     // Get the notebook
     let notebook: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1")
+        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1 AND deleted_at IS NULL")
             .bind(notebook_id)
             .fetch_optional(&state.pool)
             .await?
@@ -52,8 +52,7 @@ pub async fn admin_get_notebook_edit_url(
     let notebook_filename = &notebook.notebook_filename;
 
     // Generate JupyterHub URL
-    let jupyterhub_base_url =
-        std::env::var("JUPYTERHUB_URL").unwrap_or_else(|_| "http://localhost:8888".to_string());
+    let jupyterhub_base_url = &state.config.jupyterhub_url;
 
     // For admin editing, we want to open the notebook directly in their workspace
     // The notebook will be copied to their workspace when they spawn
@@ -1,21 +1,69 @@
-use axum::{Json, extract::State};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
+    pagination::{ListParams, SortOrder, search_pattern},
 };
 
+#[derive(Deserialize)]
+pub struct AdminNotebookQuery {
+    #[serde(rename = "challengeId")]
+    challenge_id: Option<i32>,
+    /// Matches the assignment name or notebook filename
+    search: Option<String>,
+}
+
 /// Get all challenge notebooks (admin)
 pub async fn admin_get_notebooks(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    Query(query): Query<AdminNotebookQuery>,
+    list: ListParams,
 ) -> Result<Json<AdminItemsResponse<AdminChallengeNotebookResponse>>, AppError> {
-    let notebooks: Vec<ChallengeNotebook> =
-        sqlx::query_as("SELECT * FROM challenge_notebooks ORDER BY id")
-            .fetch_all(&state.pool)
-            .await?;
+    let search = search_pattern(query.search);
+    let order_by = list.order_by(
+        &[
+            ("id", "id"),
+            ("challengeId", "challenge_id"),
+            ("assignmentName", "assignment_name"),
+            ("maxPoints", "max_points"),
+            ("createdAt", "created_at"),
+            ("updatedAt", "updated_at"),
+        ],
+        ("id", SortOrder::Asc),
+        "id",
+    )?;
+
+    const FILTERS: &str = r#"
+        WHERE deleted_at IS NULL
+          AND ($1::INT IS NULL OR challenge_id = $1)
+          AND ($2::TEXT IS NULL OR assignment_name ILIKE $2 OR notebook_filename ILIKE $2)
+    "#;
+
+    let total: i64 = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM challenge_notebooks {FILTERS}"
+    ))
+    .bind(query.challenge_id)
+    .bind(&search)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let notebooks: Vec<ChallengeNotebook> = sqlx::query_as(&format!(
+        "SELECT * FROM challenge_notebooks {FILTERS} {order_by} LIMIT $3 OFFSET $4"
+    ))
+    .bind(query.challenge_id)
+    .bind(&search)
+    .bind(list.limit())
+    .bind(list.offset())
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<AdminChallengeNotebookResponse> = notebooks
         .into_iter()
@@ -35,5 +83,8 @@ pub async fn admin_get_notebooks(
         })
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(AdminItemsResponse {
+        items: responses,
+        pagination: Some(list.pagination(total)),
+    }))
 }
@@ -5,24 +5,27 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageChallenges, RequirePermission},
     error::AppError,
     models::*,
 };
 
 /// Update notebook settings (admin)
 pub async fn admin_update_notebook(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageChallenges>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(notebook_id): Path<i32>,
     Json(req): Json<AdminUpdateNotebookRequest>,
 ) -> Result<Json<AdminItemResponse<AdminChallengeNotebookResponse>>, AppError> {
     let existing: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1")
+        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1 AND deleted_at IS NULL")
             .bind(notebook_id)
             .fetch_optional(&state.pool)
             .await?
             .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
 
     let assignment_name = req.assignment_name.unwrap_or(existing.assignment_name);
     let max_points = req.max_points.unwrap_or(existing.max_points);
@@ -33,6 +36,8 @@ pub async fn admin_update_notebook(
         .unwrap_or(existing.time_limit_minutes);
     let network_disabled = req.network_disabled.unwrap_or(existing.network_disabled);
 
+    let mut tx = state.pool.begin().await?;
+
     let notebook: ChallengeNotebook = sqlx::query_as(
         r#"
         UPDATE challenge_notebooks 
@@ -49,9 +54,25 @@ pub async fn admin_update_notebook(
     .bind(time_limit_minutes)
     .bind(network_disabled)
     .bind(notebook_id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "notebook.update",
+            target_type: "notebook",
+            target_id: Some(notebook_id.to_string()),
+            before,
+            after: snapshot(&notebook),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminChallengeNotebookResponse {
         id: notebook.id,
         challenge_id: notebook.challenge_id,
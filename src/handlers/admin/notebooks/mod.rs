@@ -1,15 +1,21 @@
-pub mod admin_get_notebooks;
-pub mod admin_get_notebook_by_challenge;
 pub mod admin_create_notebook_multipart;
-pub mod admin_update_notebook;
 pub mod admin_delete_notebook;
+pub mod admin_get_notebook_by_challenge;
 pub mod admin_get_notebook_edit_url;
+pub mod admin_get_notebooks;
+pub mod admin_get_trashed_notebooks;
+pub mod admin_purge_notebook;
+pub mod admin_restore_notebook;
 pub mod admin_sync_notebook_to_nbgrader;
+pub mod admin_update_notebook;
 
-pub use admin_get_notebooks::admin_get_notebooks;
-pub use admin_get_notebook_by_challenge::admin_get_notebook_by_challenge;
 pub use admin_create_notebook_multipart::admin_create_notebook_multipart;
-pub use admin_update_notebook::admin_update_notebook;
 pub use admin_delete_notebook::admin_delete_notebook;
+pub use admin_get_notebook_by_challenge::admin_get_notebook_by_challenge;
 pub use admin_get_notebook_edit_url::admin_get_notebook_edit_url;
+pub use admin_get_notebooks::admin_get_notebooks;
+pub use admin_get_trashed_notebooks::admin_get_trashed_notebooks;
+pub use admin_purge_notebook::admin_purge_notebook;
+pub use admin_restore_notebook::admin_restore_notebook;
 pub use admin_sync_notebook_to_nbgrader::admin_sync_notebook_to_nbgrader;
+pub use admin_update_notebook::admin_update_notebook;
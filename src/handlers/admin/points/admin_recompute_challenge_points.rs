@@ -0,0 +1,173 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    grading::points_for_score,
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    late_policy::{ChallengeDeadline, apply_late_penalty},
+    live_updates::{notify_leaderboard_changed, notify_submission_changed},
+    models::*,
+    points::record_point_transaction,
+};
+
+/// Re-derive the points of a challenge's credited submissions from their stored scores,
+/// e.g. after its notebook's `max_points` or late policy changed. Differences go through
+/// the ledger.
+pub async fn admin_recompute_challenge_points(
+    auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(challenge_id): Path<i32>,
+) -> Result<Json<AdminRecomputeChallengePointsResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let challenge_title: String =
+        sqlx::query_scalar("SELECT title FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(challenge_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    #[derive(sqlx::FromRow)]
+    struct CreditedSubmission {
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        attempt_number: i32,
+        score: f64,
+        max_score: f64,
+        points_awarded: i32,
+        max_points: i32,
+        submitted_at: Option<time::OffsetDateTime>,
+        late_minutes: Option<i32>,
+        applied_penalty_percent: f64,
+        late_practice_only: bool,
+        #[sqlx(flatten)]
+        deadline: ChallengeDeadline,
+    }
+
+    // Locked like a manual grade, so a concurrent grade waits and then sees the new points
+    let submissions: Vec<CreditedSubmission> = sqlx::query_as(
+        r#"
+        SELECT
+            cs.id, cs.user_id, cs.attempt_number, cs.score, cs.max_score, cs.points_awarded,
+            COALESCE(cn.max_points, pt.max_points, 0) AS max_points, cs.submitted_at, cs.late_minutes,
+            cs.late_penalty_percent AS applied_penalty_percent, cs.late_practice_only,
+            c.end_date, c.late_policy, c.late_grace_minutes, c.late_penalty_percent,
+            c.late_penalty_period
+        FROM challenge_submissions cs
+        LEFT JOIN challenge_notebooks cn ON cn.id = cs.notebook_id
+        LEFT JOIN challenge_prediction_tasks pt ON pt.challenge_id = cs.challenge_id
+        JOIN challenges c ON c.id = cs.challenge_id
+        WHERE cs.challenge_id = $1
+          AND cs.points_credited
+          AND cs.score IS NOT NULL
+          AND cs.max_score IS NOT NULL
+        ORDER BY cs.created_at
+        FOR UPDATE OF cs
+        "#,
+    )
+    .bind(challenge_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut submissions_updated = 0;
+    let mut points_delta = 0;
+
+    for submission in submissions {
+        let late = submission
+            .submitted_at
+            .and_then(|submitted_at| submission.deadline.assess(submitted_at));
+        let points = apply_late_penalty(
+            points_for_score(
+                submission.score,
+                submission.max_score,
+                submission.max_points,
+            ),
+            late.as_ref(),
+        );
+        let delta = points - submission.points_awarded;
+        let recorded_late = LatePenalty::recorded(
+            submission.late_minutes,
+            submission.applied_penalty_percent,
+            submission.late_practice_only,
+        );
+        if delta == 0 && recorded_late == late {
+            continue;
+        }
+
+        let updated: ChallengeSubmission = sqlx::query_as(
+            r#"
+            UPDATE challenge_submissions
+            SET points_awarded = $1,
+                late_minutes = $3,
+                late_penalty_percent = $4,
+                late_practice_only = $5,
+                updated_at = NOW()
+            WHERE id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(points)
+        .bind(submission.id)
+        .bind(late.as_ref().map(|l| l.minutes_late))
+        .bind(late.as_ref().map_or(0.0, |l| l.penalty_percent))
+        .bind(late.as_ref().is_some_and(|l| l.practice_only))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        record_point_transaction(
+            &mut tx,
+            submission.user_id,
+            delta,
+            &format!(
+                "Recomputed: {challenge_title} (attempt {})",
+                submission.attempt_number
+            ),
+            Some(submission.id),
+            Some(auth.user_id),
+        )
+        .await?;
+        notify_submission_changed(&mut tx, &updated).await?;
+
+        submissions_updated += 1;
+        points_delta += delta as i64;
+    }
+
+    if submissions_updated > 0 {
+        notify_leaderboard_changed(&mut tx, challenge_id).await?;
+    }
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "challenge.points_recompute",
+            target_type: "challenge",
+            target_id: Some(challenge_id.to_string()),
+            before: None,
+            after: Some(json!({
+                "submissionsUpdated": submissions_updated,
+                "pointsDelta": points_delta,
+            })),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    update_user_ranks(&state.pool).await?;
+
+    Ok(Json(AdminRecomputeChallengePointsResponse {
+        success: true,
+        submissions_updated,
+        points_delta,
+    }))
+}
@@ -0,0 +1,46 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    models::*,
+    points::recompute_all_points,
+};
+
+/// Rebuild every user's points from the ledger and refresh ranks
+pub async fn admin_recompute_points(
+    auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    request: RequestContext,
+) -> Result<Json<AdminRecomputePointsResponse>, AppError> {
+    let users_updated = recompute_all_points(&state.pool).await?;
+
+    if users_updated > 0 {
+        tracing::warn!("Recomputed points for {users_updated} users that drifted from the ledger");
+    }
+
+    update_user_ranks(&state.pool).await?;
+
+    record_audit(
+        &mut *state.pool.acquire().await?,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "points.recompute",
+            target_type: "points",
+            target_id: None,
+            before: None,
+            after: Some(json!({ "usersUpdated": users_updated })),
+        },
+    )
+    .await?;
+
+    Ok(Json(AdminRecomputePointsResponse {
+        success: true,
+        users_updated,
+    }))
+}
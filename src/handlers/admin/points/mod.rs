@@ -0,0 +1,5 @@
+pub mod admin_recompute_challenge_points;
+pub mod admin_recompute_points;
+
+pub use admin_recompute_challenge_points::admin_recompute_challenge_points;
+pub use admin_recompute_points::admin_recompute_points;
@@ -2,14 +2,16 @@ use axum::{Json, extract::State};
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
 
 pub async fn admin_create_resource(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     Json(req): Json<AdminCreateResourceRequest>,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
     let visible = req.visible.unwrap_or(true);
@@ -20,6 +22,8 @@ pub async fn admin_create_resource(
         .unwrap_or_default();
     let instructor_image = req.instructor.as_ref().and_then(|i| i.image.clone());
 
+    let mut tx = state.pool.begin().await?;
+
     let resource: Resource = sqlx::query_as(
         r#"
         INSERT INTO resources (title, provider, cover_image, notion_url, instructor_name, instructor_image, visible, created_at, updated_at)
@@ -34,9 +38,25 @@ pub async fn admin_create_resource(
     .bind(&instructor_name)
     .bind(&instructor_image)
     .bind(visible)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.create",
+            target_type: "resource",
+            target_id: Some(resource.id.to_string()),
+            before: None,
+            after: snapshot(&resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminResourceResponse {
         id: resource.id,
         title: resource.title,
@@ -5,7 +5,8 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
@@ -15,8 +16,9 @@ use super::save_uploaded_file::save_uploaded_file;
 // Admin resource endpoints with multipart form data
 
 pub async fn admin_create_resource_multipart(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
     tracing::info!("Starting multipart resource creation");
@@ -121,6 +123,8 @@ pub async fn admin_create_resource_multipart(
     let instructor_name = instructor_name.unwrap_or_default();
     let visible = visible.unwrap_or(true);
 
+    let mut tx = state.pool.begin().await?;
+
     let resource: Resource = sqlx::query_as(
         r#"
         INSERT INTO resources (title, provider, cover_image, notion_url, instructor_name, instructor_image, visible, created_at, updated_at)
@@ -135,9 +139,25 @@ pub async fn admin_create_resource_multipart(
     .bind(&instructor_name)
     .bind(&instructor_image)
     .bind(visible)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.create",
+            target_type: "resource",
+            target_id: Some(resource.id.to_string()),
+            before: None,
+            after: snapshot(&resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminResourceResponse {
         id: resource.id,
         title: resource.title,
@@ -5,24 +5,44 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
 
+/// Move a resource to the trash
 pub async fn admin_delete_resource(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminSuccessResponse>, AppError> {
-    let result = sqlx::query("DELETE FROM resources WHERE id = $1")
-        .bind(id)
-        .execute(&state.pool)
-        .await?;
+    let mut tx = state.pool.begin().await?;
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound);
-    }
+    let resource: Resource = sqlx::query_as(
+        "UPDATE resources SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.delete",
+            target_type: "resource",
+            target_id: Some(id.to_string()),
+            before: snapshot(&resource),
+            after: None,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
     Ok(Json(AdminSuccessResponse { success: true }))
 }
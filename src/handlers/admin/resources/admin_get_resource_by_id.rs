@@ -5,21 +5,22 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
 
 pub async fn admin_get_resource_by_id(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let resource: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let resource: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
     let response = AdminResourceResponse {
         id: resource.id,
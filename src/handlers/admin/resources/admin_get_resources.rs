@@ -6,31 +6,65 @@ use serde::Deserialize;
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
+    pagination::{ListParams, SortOrder, search_pattern},
 };
 
 #[derive(Deserialize)]
 pub struct AdminResourceQuery {
     #[serde(rename = "includeHidden")]
     include_hidden: Option<bool>,
+    /// Only visible (`true`) or hidden (`false`) resources; takes precedence over `includeHidden`
+    visible: Option<bool>,
+    /// Matches the title, provider or instructor
+    search: Option<String>,
 }
 
 pub async fn admin_get_resources(
-    _auth: AdminUser,
+    _auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
     Query(query): Query<AdminResourceQuery>,
+    list: ListParams,
 ) -> Result<Json<AdminItemsResponse<AdminResourceResponse>>, AppError> {
-    let include_hidden = query.include_hidden.unwrap_or(false);
+    let visible = query
+        .visible
+        .or((!query.include_hidden.unwrap_or(false)).then_some(true));
+    let search = search_pattern(query.search);
+    let order_by = list.order_by(
+        &[
+            ("id", "id"),
+            ("title", "title"),
+            ("provider", "provider"),
+            ("createdAt", "created_at"),
+            ("updatedAt", "updated_at"),
+        ],
+        ("id", SortOrder::Asc),
+        "id",
+    )?;
 
-    let sql = if include_hidden {
-        "SELECT * FROM resources ORDER BY id"
-    } else {
-        "SELECT * FROM resources WHERE visible = true ORDER BY id"
-    };
+    const FILTERS: &str = r#"
+        WHERE deleted_at IS NULL
+          AND ($1::BOOLEAN IS NULL OR visible = $1)
+          AND ($2::TEXT IS NULL OR title ILIKE $2 OR provider ILIKE $2 OR instructor_name ILIKE $2)
+    "#;
 
-    let resources: Vec<Resource> = sqlx::query_as(sql).fetch_all(&state.pool).await?;
+    let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM resources {FILTERS}"))
+        .bind(visible)
+        .bind(&search)
+        .fetch_one(&state.pool)
+        .await?;
+
+    let resources: Vec<Resource> = sqlx::query_as(&format!(
+        "SELECT * FROM resources {FILTERS} {order_by} LIMIT $3 OFFSET $4"
+    ))
+    .bind(visible)
+    .bind(&search)
+    .bind(list.limit())
+    .bind(list.offset())
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<AdminResourceResponse> = resources
         .into_iter()
@@ -51,5 +85,8 @@ pub async fn admin_get_resources(
         })
         .collect();
 
-    Ok(Json(AdminItemsResponse { items: responses }))
+    Ok(Json(AdminItemsResponse {
+        items: responses,
+        pagination: Some(list.pagination(total)),
+    }))
 }
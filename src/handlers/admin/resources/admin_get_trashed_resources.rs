@@ -0,0 +1,22 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{ManageResources, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, list_trash},
+};
+
+/// Deleted resources that can still be restored or purged
+pub async fn admin_get_trashed_resources(
+    _auth: RequirePermission<ManageResources>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminItemsResponse<TrashedItem>>, AppError> {
+    let items = list_trash(&state.pool, TrashKind::Resource).await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
@@ -5,25 +5,51 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
 
 pub async fn admin_patch_resource_visibility(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     Json(req): Json<AdminVisibilityRequest>,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let existing: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
     let resource: Resource = sqlx::query_as(
         "UPDATE resources SET visible = $1, updated_at = NOW() WHERE id = $2 RETURNING *",
     )
     .bind(req.visible)
     .bind(id)
-    .fetch_optional(&state.pool)
-    .await?
-    .ok_or(AppError::NotFound)?;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.visibility",
+            target_type: "resource",
+            target_id: Some(id.to_string()),
+            before: snapshot(&existing),
+            after: snapshot(&resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
 
     let response = AdminResourceResponse {
         id: resource.id,
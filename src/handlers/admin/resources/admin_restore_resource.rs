@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit},
+    auth::{ManageResources, RequirePermission},
+    error::AppError,
+    models::*,
+    trash::{TrashKind, restore_from_trash},
+};
+
+/// Take a deleted resource out of the trash
+pub async fn admin_restore_resource(
+    auth: RequirePermission<ManageResources>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<i32>,
+) -> Result<Json<AdminSuccessResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let resource = restore_from_trash(&mut tx, TrashKind::Resource, id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.restore",
+            target_type: "resource",
+            target_id: Some(id.to_string()),
+            before: None,
+            after: Some(resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminSuccessResponse { success: true }))
+}
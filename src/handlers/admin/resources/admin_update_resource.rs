@@ -5,22 +5,26 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
 
 pub async fn admin_update_resource(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     Json(req): Json<AdminUpdateResourceRequest>,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let existing: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let existing: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
 
     let title = req.title.unwrap_or(existing.title);
     let provider = req.provider.unwrap_or(existing.provider);
@@ -38,6 +42,8 @@ pub async fn admin_update_resource(
         .or(existing.instructor_image);
     let visible = req.visible.unwrap_or(existing.visible);
 
+    let mut tx = state.pool.begin().await?;
+
     let resource: Resource = sqlx::query_as(
         r#"
         UPDATE resources 
@@ -54,9 +60,25 @@ pub async fn admin_update_resource(
     .bind(&instructor_image)
     .bind(visible)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.update",
+            target_type: "resource",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminResourceResponse {
         id: resource.id,
         title: resource.title,
@@ -5,7 +5,8 @@ use axum::{
 
 use crate::{
     AppState,
-    auth::AdminUser,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageResources, RequirePermission},
     error::AppError,
     models::*,
 };
@@ -13,16 +14,19 @@ use crate::{
 use super::save_uploaded_file::save_uploaded_file;
 
 pub async fn admin_update_resource_multipart(
-    _auth: AdminUser,
+    auth: RequirePermission<ManageResources>,
     State(state): State<AppState>,
+    request: RequestContext,
     Path(id): Path<i32>,
     mut multipart: axum::extract::Multipart,
 ) -> Result<Json<AdminItemResponse<AdminResourceResponse>>, AppError> {
-    let existing: Resource = sqlx::query_as("SELECT * FROM resources WHERE id = $1")
-        .bind(id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let existing: Resource =
+        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND deleted_at IS NULL")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
 
     let mut title: Option<String> = None;
     let mut provider: Option<String> = None;
@@ -123,6 +127,8 @@ pub async fn admin_update_resource_multipart(
     let instructor_image = instructor_image.unwrap_or(existing.instructor_image);
     let visible = visible.unwrap_or(existing.visible);
 
+    let mut tx = state.pool.begin().await?;
+
     let resource: Resource = sqlx::query_as(
         r#"
         UPDATE resources 
@@ -139,9 +145,25 @@ pub async fn admin_update_resource_multipart(
     .bind(&instructor_image)
     .bind(visible)
     .bind(id)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "resource.update",
+            target_type: "resource",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&resource),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     let response = AdminResourceResponse {
         id: resource.id,
         title: resource.title,
@@ -0,0 +1,50 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{ManageRoles, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+pub async fn admin_get_roles(
+    _auth: RequirePermission<ManageRoles>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminItemsResponse<AdminRoleResponse>>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct RoleRow {
+        name: String,
+        description: String,
+        permissions: Vec<String>,
+    }
+
+    let rows: Vec<RoleRow> = sqlx::query_as(
+        r#"
+        SELECT
+            r.name,
+            r.description,
+            COALESCE(
+                ARRAY_AGG(rp.permission ORDER BY rp.permission) FILTER (WHERE rp.permission IS NOT NULL),
+                ARRAY[]::VARCHAR[]
+            ) AS permissions
+        FROM roles r
+        LEFT JOIN role_permissions rp ON rp.role = r.name
+        GROUP BY r.name, r.description, r.created_at
+        ORDER BY r.created_at, r.name
+        "#,
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AdminItemsResponse {
+        items: rows
+            .into_iter()
+            .map(|row| AdminRoleResponse {
+                name: row.name,
+                description: row.description,
+                permissions: row.permissions,
+            })
+            .collect(),
+        pagination: None,
+    }))
+}
@@ -0,0 +1,3 @@
+pub mod admin_get_roles;
+
+pub use admin_get_roles::admin_get_roles;
@@ -0,0 +1,46 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    audit::RequestContext,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+use super::bulk_grade::{BulkGradeRow, apply_bulk_grades};
+
+/// Grade many submissions of a challenge at once; all or nothing
+pub async fn admin_bulk_grade_submissions(
+    auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(challenge_id): Path<i32>,
+    Json(req): Json<AdminBulkGradeRequest>,
+) -> Result<Response, AppError> {
+    let rows = req
+        .grades
+        .into_iter()
+        .enumerate()
+        .map(|(index, grade)| BulkGradeRow {
+            row: index + 1,
+            submission_id: grade.submission_id,
+            score: grade.score,
+            feedback: grade.feedback,
+        })
+        .collect();
+
+    apply_bulk_grades(
+        &state,
+        &request,
+        auth.user_id,
+        challenge_id,
+        rows,
+        Vec::new(),
+    )
+    .await
+}
@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    audit::RequestContext,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+};
+
+use super::bulk_grade::{apply_bulk_grades, parse_grades_csv};
+
+/// Grade many submissions of a challenge from an uploaded CSV `file`; all or nothing
+pub async fn admin_bulk_grade_submissions_multipart(
+    auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(challenge_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Response, AppError> {
+    let mut csv_data: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+    {
+        if field.name() == Some("file") {
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::InternalError(e.into()))?;
+            csv_data = Some(bytes.to_vec());
+        }
+    }
+
+    let csv_data =
+        csv_data.ok_or_else(|| AppError::BadRequest("A CSV file is required".to_string()))?;
+    let (rows, errors) = parse_grades_csv(&csv_data)?;
+
+    apply_bulk_grades(&state, &request, auth.user_id, challenge_id, rows, errors).await
+}
@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    export::ExportQuery,
+    pagination::ListParams,
+};
+
+use super::{submission_export::export_submissions, submission_filters::SubmissionFilters};
+
+/// Download one challenge's submissions and grades as CSV or XLSX
+pub async fn admin_export_challenge_grades(
+    _auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+    Query(mut filters): Query<SubmissionFilters>,
+    Query(export): Query<ExportQuery>,
+    list: ListParams,
+) -> Result<Response, AppError> {
+    sqlx::query_scalar::<_, i32>("SELECT id FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+        .bind(challenge_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    filters.challenge_id = Some(challenge_id);
+
+    export_submissions(
+        state.pool,
+        filters,
+        &list,
+        export.format,
+        &format!("challenge-{challenge_id}-grades"),
+    )
+    .await
+}
@@ -0,0 +1,25 @@
+use axum::{
+    extract::{Query, State},
+    response::Response,
+};
+
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    export::ExportQuery,
+    pagination::ListParams,
+};
+
+use super::{submission_export::export_submissions, submission_filters::SubmissionFilters};
+
+/// Download the submissions list as CSV or XLSX, with the same filters and sorting
+pub async fn admin_export_submissions(
+    _auth: RequirePermission<GradeSubmissions>,
+    State(state): State<AppState>,
+    Query(filters): Query<SubmissionFilters>,
+    Query(export): Query<ExportQuery>,
+    list: ListParams,
+) -> Result<Response, AppError> {
+    export_submissions(state.pool, filters, &list, export.format, "submissions").await
+}
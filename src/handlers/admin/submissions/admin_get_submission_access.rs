@@ -3,10 +3,15 @@ use axum::{
     extract::{Path, State},
 };
 
-use crate::{AppState, auth::AdminUser, error::AppError, models::AdminSubmissionAccessResponse};
+use crate::{
+    AppState,
+    auth::{GradeSubmissions, RequirePermission},
+    error::AppError,
+    models::AdminSubmissionAccessResponse,
+};
 
 pub async fn admin_get_submission_access(
-    auth: AdminUser,
+    auth: RequirePermission<GradeSubmissions>,
     State(state): State<AppState>,
     Path(submission_id): Path<uuid::Uuid>,
 ) -> Result<Json<AdminSubmissionAccessResponse>, AppError> {
@@ -49,8 +54,7 @@ pub async fn admin_get_submission_access(
     let jupyterhub_token =
         crate::auth::create_jupyterhub_token(auth.user_id, &admin_jupyterhub_username)?;
 
-    let jupyterhub_base_url =
-        std::env::var("JUPYTERHUB_URL").unwrap_or_else(|_| "http://localhost:8888".to_string());
+    let jupyterhub_base_url = &state.config.jupyterhub_url;
 
     let view_next_path = format!(
         "/user/{}/notebooks/{}",
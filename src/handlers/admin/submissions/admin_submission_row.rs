@@ -0,0 +1,78 @@
+use crate::models::*;
+
+/// Columns of [`AdminSubmissionRow`] over submissions (`cs`) joined with their student (`u`)
+/// and challenge (`c`). Callers add `attempts_used`, since what it counts over differs.
+pub(crate) const ADMIN_SUBMISSION_COLUMNS: &str = r#"
+    cs.id, cs.user_id, u.full_name AS user_name, u.email AS user_email,
+    cs.challenge_id, c.title AS challenge_title, c.allowed_submissions,
+    cs.attempt_number,
+    cs.status, cs.score, cs.max_score, cs.metric_value, cs.public_metric_value,
+    cs.points_awarded, cs.points_credited,
+    cs.started_at, cs.submitted_at, cs.graded_at, cs.feedback, cs.rubric_scores,
+    cs.late_minutes, cs.late_penalty_percent, cs.late_practice_only
+"#;
+
+/// A submission as the admin views list it, with its student and challenge
+#[derive(sqlx::FromRow)]
+pub(crate) struct AdminSubmissionRow {
+    id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    user_name: String,
+    user_email: String,
+    challenge_id: i32,
+    challenge_title: String,
+    allowed_submissions: i32,
+    attempt_number: i32,
+    attempts_used: i64,
+    status: String,
+    score: Option<f64>,
+    max_score: Option<f64>,
+    metric_value: Option<f64>,
+    public_metric_value: Option<f64>,
+    points_awarded: i32,
+    points_credited: bool,
+    started_at: Option<time::OffsetDateTime>,
+    submitted_at: Option<time::OffsetDateTime>,
+    graded_at: Option<time::OffsetDateTime>,
+    feedback: Option<String>,
+    rubric_scores: Option<sqlx::types::Json<Vec<RubricScore>>>,
+    late_minutes: Option<i32>,
+    late_penalty_percent: f64,
+    late_practice_only: bool,
+}
+
+impl From<AdminSubmissionRow> for AdminSubmissionResponse {
+    fn from(s: AdminSubmissionRow) -> Self {
+        let allowed_submissions = s.allowed_submissions.max(1);
+
+        AdminSubmissionResponse {
+            id: s.id,
+            user_id: s.user_id,
+            user_name: s.user_name,
+            user_email: s.user_email,
+            challenge_id: s.challenge_id,
+            challenge_title: s.challenge_title,
+            allowed_submissions,
+            attempt_number: s.attempt_number,
+            attempts_used: s.attempts_used,
+            attempts_remaining: (allowed_submissions as i64 - s.attempts_used).max(0),
+            status: s.status,
+            score: s.score,
+            max_score: s.max_score,
+            metric_value: s.metric_value,
+            public_metric_value: s.public_metric_value,
+            points_awarded: s.points_awarded,
+            points_credited: s.points_credited,
+            started_at: s.started_at,
+            submitted_at: s.submitted_at,
+            graded_at: s.graded_at,
+            feedback: s.feedback,
+            rubric: s.rubric_scores.map(|r| r.0),
+            late: LatePenalty::recorded(
+                s.late_minutes,
+                s.late_penalty_percent,
+                s.late_practice_only,
+            ),
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use serde_json::json;
+use sqlx::PgConnection;
+
+use crate::{
+    audit::{AuditEntry, RequestContext, record_audit},
+    error::AppError,
+    events::{Event, publish_event},
+    late_policy::{ChallengeDeadline, apply_late_penalty},
+    live_updates::{notify_leaderboard_changed, notify_submission_changed},
+    models::*,
+    notifications::{NewNotification, create_notification},
+    points::record_point_transaction,
+    prediction_leaderboard::private_revealed,
+    rubric::{load_rubric, score_rubric},
+};
+
+/// A manual grade for one submission
+pub struct Grade {
+    /// Out of 100; leave out when grading with `rubric`
+    pub score: Option<f64>,
+    pub rubric: Option<Vec<AdminRubricScoreRequest>>,
+    /// `None` keeps the earlier feedback; blank feedback clears it
+    pub feedback: Option<String>,
+}
+
+/// Grade a submission inside the caller's transaction: credit the points difference to the
+/// ledger, notify the student and subscribers, and record it in the audit log.
+/// The submission row stays locked until commit, so concurrent grades of the same
+/// submission apply one after another and each sees the points already credited.
+pub async fn apply_grade(
+    conn: &mut PgConnection,
+    request: &RequestContext,
+    grader_id: uuid::Uuid,
+    submission_id: uuid::Uuid,
+    grade: Grade,
+) -> Result<ChallengeSubmission, AppError> {
+    match (grade.score, &grade.rubric) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::BadRequest(
+                "Send either a score or rubric scores, not both".to_string(),
+            ));
+        }
+        (None, None) => {
+            return Err(AppError::BadRequest(
+                "A score or rubric scores are required".to_string(),
+            ));
+        }
+        (Some(score), None) if !(0.0..=100.0).contains(&score) => {
+            return Err(AppError::BadRequest(
+                "score must be between 0 and 100".to_string(),
+            ));
+        }
+        _ => {}
+    }
+    let feedback = grade
+        .feedback
+        .map(|f| Some(f.trim().to_string()).filter(|f| !f.is_empty()));
+
+    #[derive(sqlx::FromRow)]
+    struct GradeTarget {
+        user_id: uuid::Uuid,
+        challenge_id: i32,
+        challenge_title: String,
+        attempt_number: i32,
+        score: Option<f64>,
+        points_awarded: i32,
+        points_credited: bool,
+        max_points: i32,
+        status: String,
+        feedback: Option<String>,
+        submitted_at: Option<time::OffsetDateTime>,
+        public_metric_value: Option<f64>,
+        #[sqlx(flatten)]
+        deadline: ChallengeDeadline,
+    }
+
+    let target: GradeTarget = sqlx::query_as(
+        r#"
+        SELECT
+            cs.user_id,
+            cs.challenge_id,
+            c.title AS challenge_title,
+            cs.attempt_number,
+            cs.score,
+            cs.points_awarded,
+            cs.points_credited,
+            COALESCE(cn.max_points, pt.max_points, 0) AS max_points,
+            cs.status,
+            cs.feedback,
+            cs.submitted_at,
+            cs.public_metric_value,
+            c.end_date,
+            c.late_policy,
+            c.late_grace_minutes,
+            c.late_penalty_percent,
+            c.late_penalty_period
+        FROM challenge_submissions cs
+        LEFT JOIN challenge_notebooks cn ON cn.id = cs.notebook_id
+        LEFT JOIN challenge_prediction_tasks pt ON pt.challenge_id = cs.challenge_id
+        JOIN challenges c ON c.id = cs.challenge_id
+        WHERE cs.id = $1 AND c.deleted_at IS NULL
+        FOR UPDATE OF cs
+        "#,
+    )
+    .bind(submission_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if target.status != "grading_pending"
+        && target.status != "graded"
+        && target.status != "submitted"
+        && target.status != "grading"
+    {
+        return Err(AppError::BadRequest(
+            "Only submitted, grading, grading_pending, or graded submissions can be manually graded"
+                .to_string(),
+        ));
+    }
+
+    // Points and results on a split test set follow the private leaderboard; grading an
+    // attempt before it is revealed would credit and announce the hidden result
+    if target.public_metric_value.is_some()
+        && !private_revealed(target.deadline.end_date, time::OffsetDateTime::now_utc())
+    {
+        return Err(AppError::BadRequest(
+            "Attempts on a split test set can be graded once the private leaderboard is revealed at the end date"
+                .to_string(),
+        ));
+    }
+
+    let (score, rubric_scores) = match grade.rubric {
+        Some(scores) => {
+            let criteria = load_rubric(&mut *conn, target.challenge_id).await?;
+            let (score, breakdown) = score_rubric(&criteria, scores)?;
+            (score, Some(breakdown))
+        }
+        None => (grade.score.unwrap_or_default(), None),
+    };
+
+    let late = target
+        .submitted_at
+        .and_then(|submitted_at| target.deadline.assess(submitted_at));
+    let points_awarded = apply_late_penalty(
+        ((score / 100.0) * target.max_points as f64).round() as i32,
+        late.as_ref(),
+    );
+    let delta_points = if target.points_credited {
+        points_awarded - target.points_awarded
+    } else {
+        points_awarded
+    };
+
+    let updated_submission: ChallengeSubmission = sqlx::query_as(
+        r#"
+        UPDATE challenge_submissions
+        SET status = 'graded',
+            score = $1,
+            max_score = 100.0,
+            points_awarded = $2,
+            points_credited = true,
+            graded_at = NOW(),
+            manual_graded_by = $3,
+            manual_graded_at = NOW(),
+            feedback = CASE WHEN $5 THEN $6 ELSE feedback END,
+            rubric_scores = $7,
+            late_minutes = $8,
+            late_penalty_percent = $9,
+            late_practice_only = $10,
+            updated_at = NOW()
+        WHERE id = $4
+        RETURNING *
+        "#,
+    )
+    .bind(score)
+    .bind(points_awarded)
+    .bind(grader_id)
+    .bind(submission_id)
+    .bind(feedback.is_some())
+    .bind(feedback.flatten())
+    .bind(rubric_scores.map(sqlx::types::Json))
+    .bind(late.as_ref().map(|l| l.minutes_late))
+    .bind(late.as_ref().map_or(0.0, |l| l.penalty_percent))
+    .bind(late.as_ref().is_some_and(|l| l.practice_only))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let action = if target.points_credited {
+        "Regraded"
+    } else {
+        "Graded"
+    };
+    let reason = format!(
+        "{action}: {} (attempt {})",
+        target.challenge_title, target.attempt_number
+    );
+
+    record_point_transaction(
+        &mut *conn,
+        target.user_id,
+        delta_points,
+        &reason,
+        Some(submission_id),
+        Some(grader_id),
+    )
+    .await?;
+
+    publish_event(&mut *conn, &Event::submission_graded(&updated_submission)).await?;
+    create_notification(
+        &mut *conn,
+        &NewNotification::submission_graded(&updated_submission, &target.challenge_title),
+    )
+    .await?;
+    notify_submission_changed(&mut *conn, &updated_submission).await?;
+    notify_leaderboard_changed(&mut *conn, updated_submission.challenge_id).await?;
+
+    record_audit(
+        &mut *conn,
+        request,
+        AuditEntry {
+            actor_id: grader_id,
+            action: "submission.grade",
+            target_type: "submission",
+            target_id: Some(submission_id.to_string()),
+            before: Some(json!({
+                "status": target.status,
+                "score": target.score,
+                "pointsAwarded": target.points_awarded,
+                "pointsCredited": target.points_credited,
+                "feedback": target.feedback,
+            })),
+            after: Some(json!({
+                "status": updated_submission.status,
+                "score": updated_submission.score,
+                "pointsAwarded": updated_submission.points_awarded,
+                "pointsCredited": updated_submission.points_credited,
+                "feedback": updated_submission.feedback,
+                "rubricScores": updated_submission.rubric_scores,
+                "late": updated_submission.late_penalty(),
+            })),
+        },
+    )
+    .await?;
+
+    Ok(updated_submission)
+}
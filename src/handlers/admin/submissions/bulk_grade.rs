@@ -0,0 +1,209 @@
+use std::collections::HashSet;
+
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    AppState, audit::RequestContext, error::AppError, export::restore_formula,
+    handlers::webhooks::update_user_ranks::update_user_ranks, models::*,
+};
+
+use super::apply_grade::{Grade, apply_grade};
+
+const MAX_BULK_GRADES: usize = 2000;
+
+/// A grade to apply, with the row it came from for error reporting
+pub struct BulkGradeRow {
+    pub row: usize,
+    pub submission_id: uuid::Uuid,
+    pub score: f64,
+    pub feedback: Option<String>,
+}
+
+/// Grade many submissions of a challenge in one transaction. Every row is checked and
+/// any that fail are reported; if one fails, none of the grades are applied.
+/// `errors` holds rows that were already rejected while reading the input.
+pub async fn apply_bulk_grades(
+    state: &AppState,
+    request: &RequestContext,
+    grader_id: uuid::Uuid,
+    challenge_id: i32,
+    mut rows: Vec<BulkGradeRow>,
+    mut errors: Vec<BulkGradeError>,
+) -> Result<Response, AppError> {
+    sqlx::query_scalar::<_, i32>("SELECT id FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+        .bind(challenge_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if rows.is_empty() && errors.is_empty() {
+        return Err(AppError::BadRequest("No grades to apply".to_string()));
+    }
+    if rows.len() + errors.len() > MAX_BULK_GRADES {
+        return Err(AppError::BadRequest(format!(
+            "At most {MAX_BULK_GRADES} grades can be applied at once"
+        )));
+    }
+
+    let ids: Vec<uuid::Uuid> = rows.iter().map(|r| r.submission_id).collect();
+    let in_challenge: HashSet<uuid::Uuid> = sqlx::query_scalar(
+        "SELECT id FROM challenge_submissions WHERE challenge_id = $1 AND id = ANY($2)",
+    )
+    .bind(challenge_id)
+    .bind(&ids)
+    .fetch_all(&state.pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    // Lock submissions in a consistent order so overlapping batches cannot deadlock
+    rows.sort_by_key(|r| (r.submission_id, r.row));
+
+    let mut tx = state.pool.begin().await?;
+    let mut seen = HashSet::new();
+    let mut graded = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let error = |message: &str| BulkGradeError {
+            row: row.row,
+            submission_id: Some(row.submission_id),
+            message: message.to_string(),
+        };
+
+        if !seen.insert(row.submission_id) {
+            errors.push(error("This submission is graded more than once"));
+            continue;
+        }
+        if !in_challenge.contains(&row.submission_id) {
+            errors.push(error("Not a submission of this challenge"));
+            continue;
+        }
+
+        // Grades are rejected before anything is written, so the transaction stays
+        // usable and the remaining rows can still be checked
+        let grade = Grade {
+            score: Some(row.score),
+            rubric: None,
+            feedback: row.feedback,
+        };
+        match apply_grade(&mut tx, request, grader_id, row.submission_id, grade).await {
+            Ok(submission) => graded.push(BulkGradeResult {
+                row: row.row,
+                submission_id: submission.id,
+                score: submission.score,
+                points_awarded: submission.points_awarded,
+            }),
+            Err(
+                AppError::BadRequest(message)
+                | AppError::ValidationError(message)
+                | AppError::Conflict(message),
+            ) => errors.push(error(&message)),
+            Err(AppError::NotFound) => errors.push(error("Submission not found")),
+            Err(e) => return Err(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        tx.rollback().await?;
+        errors.sort_by_key(|e| e.row);
+
+        let response = AdminBulkGradeResponse {
+            success: false,
+            message: format!(
+                "No grades were applied; {} row(s) have errors",
+                errors.len()
+            ),
+            graded: Vec::new(),
+            errors,
+        };
+        return Ok((StatusCode::BAD_REQUEST, Json(response)).into_response());
+    }
+
+    tx.commit().await?;
+    graded.sort_by_key(|g| g.row);
+
+    update_user_ranks(&state.pool).await?;
+
+    Ok(Json(AdminBulkGradeResponse {
+        success: true,
+        message: format!("Graded {} submission(s)", graded.len()),
+        graded,
+        errors: Vec::new(),
+    })
+    .into_response())
+}
+
+/// Read grades from a CSV with `Submission ID` and `Score` columns and an optional
+/// `Feedback` column, as written by the grades export. Rows without a score are skipped.
+pub fn parse_grades_csv(data: &[u8]) -> Result<(Vec<BulkGradeRow>, Vec<BulkGradeError>), AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    // Match headers loosely: "Submission ID", "submissionId" and "submission_id" are the same
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::BadRequest(format!("Could not read the CSV header: {e}")))?
+        .iter()
+        .map(|h| {
+            h.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase()
+        })
+        .collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let (Some(id_column), Some(score_column)) = (column("submissionid"), column("score")) else {
+        return Err(AppError::BadRequest(
+            "The CSV needs \"Submission ID\" and \"Score\" columns".to_string(),
+        ));
+    };
+    let feedback_column = column("feedback");
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for record in reader.records() {
+        let record =
+            record.map_err(|e| AppError::BadRequest(format!("Could not read the CSV: {e}")))?;
+        let row = record.position().map_or(0, |p| p.line() as usize);
+        let field = |index: usize| record.get(index).unwrap_or("");
+
+        let submission_id = match uuid::Uuid::parse_str(field(id_column)) {
+            Ok(id) => id,
+            Err(_) => {
+                errors.push(BulkGradeError {
+                    row,
+                    submission_id: None,
+                    message: "Invalid submission ID".to_string(),
+                });
+                continue;
+            }
+        };
+        if field(score_column).is_empty() {
+            continue;
+        }
+        let Ok(score) = field(score_column).parse::<f64>() else {
+            errors.push(BulkGradeError {
+                row,
+                submission_id: Some(submission_id),
+                message: "Score is not a number".to_string(),
+            });
+            continue;
+        };
+
+        rows.push(BulkGradeRow {
+            row,
+            submission_id,
+            score,
+            feedback: feedback_column.map(|index| restore_formula(field(index)).to_string()),
+        });
+    }
+
+    Ok((rows, errors))
+}
@@ -1,8 +1,22 @@
+pub(crate) mod admin_submission_row;
+mod apply_grade;
+mod bulk_grade;
+mod submission_export;
+mod submission_filters;
+
+pub mod admin_bulk_grade_submissions;
+pub mod admin_bulk_grade_submissions_multipart;
+pub mod admin_export_challenge_grades;
+pub mod admin_export_submissions;
 pub mod admin_get_submission_access;
 pub mod admin_get_submission_file;
 pub mod admin_get_submissions;
 pub mod admin_grade_submission;
 
+pub use admin_bulk_grade_submissions::admin_bulk_grade_submissions;
+pub use admin_bulk_grade_submissions_multipart::admin_bulk_grade_submissions_multipart;
+pub use admin_export_challenge_grades::admin_export_challenge_grades;
+pub use admin_export_submissions::admin_export_submissions;
 pub use admin_get_submission_access::admin_get_submission_access;
 pub use admin_get_submission_file::admin_get_submission_file;
 pub use admin_get_submissions::admin_get_submissions;
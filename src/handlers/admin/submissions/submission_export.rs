@@ -0,0 +1,109 @@
+use axum::response::Response;
+
+use crate::{
+    error::AppError,
+    export::{Cell, ExportFormat, ExportRow, export_rows},
+    pagination::{ListParams, SortOrder},
+};
+
+use super::submission_filters::{FILTERED_SUBMISSIONS, SUBMISSION_SORT_COLUMNS, SubmissionFilters};
+
+#[derive(sqlx::FromRow)]
+struct SubmissionExportRow {
+    id: uuid::Uuid,
+    user_name: String,
+    user_email: String,
+    university: Option<String>,
+    major: Option<String>,
+    challenge_title: String,
+    attempt_number: i32,
+    status: String,
+    score: Option<f64>,
+    max_score: Option<f64>,
+    points_awarded: i32,
+    late_minutes: Option<i32>,
+    late_penalty_percent: f64,
+    started_at: Option<time::OffsetDateTime>,
+    submitted_at: Option<time::OffsetDateTime>,
+    graded_at: Option<time::OffsetDateTime>,
+    feedback: Option<String>,
+}
+
+impl ExportRow for SubmissionExportRow {
+    const HEADERS: &'static [&'static str] = &[
+        "Submission ID",
+        "Name",
+        "Email",
+        "University",
+        "Major",
+        "Challenge",
+        "Attempt",
+        "Status",
+        "Score",
+        "Max score",
+        "Points",
+        "Minutes late",
+        "Late penalty %",
+        "Started at",
+        "Submitted at",
+        "Graded at",
+        "Feedback",
+    ];
+
+    fn cells(self) -> Vec<Cell> {
+        vec![
+            self.id.to_string().into(),
+            self.user_name.into(),
+            self.user_email.into(),
+            self.university.into(),
+            self.major.into(),
+            self.challenge_title.into(),
+            self.attempt_number.into(),
+            self.status.into(),
+            self.score.into(),
+            self.max_score.into(),
+            self.points_awarded.into(),
+            self.late_minutes.into(),
+            self.late_minutes.map(|_| self.late_penalty_percent).into(),
+            self.started_at.into(),
+            self.submitted_at.into(),
+            self.graded_at.into(),
+            self.feedback.into(),
+        ]
+    }
+}
+
+/// Every submission matching `filters`, in the list's sort order (paging is ignored)
+pub async fn export_submissions(
+    pool: sqlx::PgPool,
+    filters: SubmissionFilters,
+    list: &ListParams,
+    format: ExportFormat,
+    filename: &str,
+) -> Result<Response, AppError> {
+    let order_by = list.order_by(
+        SUBMISSION_SORT_COLUMNS,
+        ("createdAt", SortOrder::Desc),
+        "cs.id",
+    )?;
+
+    let sql = format!(
+        r#"
+        SELECT
+            cs.id, u.full_name AS user_name, u.email AS user_email, u.university, u.major,
+            c.title AS challenge_title, cs.attempt_number, cs.status, cs.score, cs.max_score,
+            cs.points_awarded, cs.late_minutes, cs.late_penalty_percent, cs.started_at, cs.submitted_at, cs.graded_at, cs.feedback
+        {FILTERED_SUBMISSIONS}
+        {order_by}
+        "#
+    );
+
+    export_rows::<SubmissionExportRow, _>(
+        pool,
+        sql,
+        move |query| filters.bind(query),
+        format,
+        filename,
+    )
+    .await
+}
@@ -0,0 +1,66 @@
+use serde::Deserialize;
+use sqlx::{Postgres, postgres::PgArguments, query::QueryAs};
+
+use crate::pagination::search_pattern;
+
+/// Filters shared by the submission list and its exports
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionFilters {
+    pub status: Option<String>,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: Option<i32>,
+    #[serde(rename = "userId")]
+    pub user_id: Option<uuid::Uuid>,
+    /// Submitted at or after, a date or an ISO 8601 timestamp
+    #[serde(default, deserialize_with = "crate::models::date_format::deserialize")]
+    pub from: Option<time::OffsetDateTime>,
+    /// Submitted before, a date or an ISO 8601 timestamp
+    #[serde(default, deserialize_with = "crate::models::date_format::deserialize")]
+    pub to: Option<time::OffsetDateTime>,
+    /// Matches the student's name or email, or the challenge title
+    pub search: Option<String>,
+}
+
+/// Submissions (`cs`) with their student (`u`) and challenge (`c`), narrowed by
+/// `SubmissionFilters::bind` as `$1` to `$6`
+pub const FILTERED_SUBMISSIONS: &str = r#"
+    FROM challenge_submissions cs
+    JOIN users u ON cs.user_id = u.id
+    JOIN challenges c ON cs.challenge_id = c.id
+    WHERE c.deleted_at IS NULL
+      AND ($1::TEXT IS NULL OR cs.status = $1)
+      AND ($2::INT IS NULL OR cs.challenge_id = $2)
+      AND ($3::UUID IS NULL OR cs.user_id = $3)
+      AND ($4::TIMESTAMPTZ IS NULL OR cs.submitted_at >= $4)
+      AND ($5::TIMESTAMPTZ IS NULL OR cs.submitted_at < $5)
+      AND ($6::TEXT IS NULL OR u.full_name ILIKE $6 OR u.email ILIKE $6 OR c.title ILIKE $6)
+"#;
+
+/// Sort keys for `ListParams::order_by` over `FILTERED_SUBMISSIONS`
+pub const SUBMISSION_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("createdAt", "cs.created_at"),
+    ("startedAt", "cs.started_at"),
+    ("submittedAt", "cs.submitted_at"),
+    ("gradedAt", "cs.graded_at"),
+    ("status", "cs.status"),
+    ("score", "cs.score"),
+    ("pointsAwarded", "cs.points_awarded"),
+    ("attemptNumber", "cs.attempt_number"),
+    ("userName", "u.full_name"),
+    ("challengeTitle", "c.title"),
+];
+
+impl SubmissionFilters {
+    pub fn bind<'q, O>(
+        &self,
+        query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        query
+            .bind(self.status.clone())
+            .bind(self.challenge_id)
+            .bind(self.user_id)
+            .bind(self.from)
+            .bind(self.to)
+            .bind(search_pattern(self.search.clone()))
+    }
+}
@@ -0,0 +1,75 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    models::*,
+    points::record_point_transaction,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+/// Add or remove points by hand; the reason is stored in the points ledger
+pub async fn admin_adjust_user_points(
+    auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(user_id): Path<uuid::Uuid>,
+    Json(req): Json<AdminAdjustPointsRequest>,
+) -> Result<Json<AdminItemResponse<AdminUserResponse>>, AppError> {
+    if req.delta == 0 {
+        return Err(AppError::ValidationError(
+            "delta must not be zero".to_string(),
+        ));
+    }
+
+    let reason = req.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::ValidationError(
+            "A reason is required to adjust points".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await?;
+
+    let before = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+
+    record_point_transaction(
+        &mut tx,
+        user_id,
+        req.delta,
+        reason,
+        None,
+        Some(auth.user_id),
+    )
+    .await?;
+
+    let after = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "user.points_adjust",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            before,
+            after,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    update_user_ranks(&state.pool).await?;
+
+    Ok(Json(AdminItemResponse {
+        item: fetch_admin_user(&state.pool, user_id).await?,
+    }))
+}
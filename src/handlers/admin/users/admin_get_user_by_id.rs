@@ -0,0 +1,69 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    handlers::admin::submissions::admin_submission_row::{
+        ADMIN_SUBMISSION_COLUMNS, AdminSubmissionRow,
+    },
+    models::*,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+/// A single user with their stats and every submission they made
+pub async fn admin_get_user_by_id(
+    _auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    Path(user_id): Path<uuid::Uuid>,
+) -> Result<Json<AdminItemResponse<AdminUserDetailResponse>>, AppError> {
+    let user = fetch_admin_user(&state.pool, user_id).await?;
+
+    let stats: Option<UserStats> = sqlx::query_as("SELECT * FROM user_stats WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+    let submissions: Vec<AdminSubmissionRow> = sqlx::query_as(&format!(
+        r#"
+        SELECT
+            {ADMIN_SUBMISSION_COLUMNS},
+            COUNT(*) OVER (PARTITION BY cs.user_id, cs.challenge_id) AS attempts_used
+        FROM challenge_submissions cs
+        JOIN users u ON cs.user_id = u.id
+        JOIN challenges c ON cs.challenge_id = c.id
+        WHERE cs.user_id = $1 AND c.deleted_at IS NULL
+        ORDER BY cs.created_at DESC
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let stats = match stats {
+        Some(stats) => UserStatsResponse {
+            best_subject: stats.best_subject,
+            improveable: stats.improveable,
+            quickest_hunter: stats.quickest_hunter,
+            challenges_taken: stats.challenges_taken,
+        },
+        None => UserStatsResponse {
+            best_subject: None,
+            improveable: None,
+            quickest_hunter: 0,
+            challenges_taken: 0,
+        },
+    };
+
+    Ok(Json(AdminItemResponse {
+        item: AdminUserDetailResponse {
+            user,
+            stats,
+            submissions: submissions.into_iter().map(Into::into).collect(),
+        },
+    }))
+}
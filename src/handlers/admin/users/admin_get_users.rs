@@ -0,0 +1,72 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+use super::fetch_admin_user::ADMIN_USER_COLUMNS;
+
+#[derive(Deserialize)]
+pub struct AdminUserQuery {
+    /// Matches name, email or JupyterHub username
+    search: Option<String>,
+    university: Option<String>,
+    major: Option<String>,
+    role: Option<String>,
+    #[serde(rename = "minPoints")]
+    min_points: Option<i32>,
+    #[serde(rename = "maxPoints")]
+    max_points: Option<i32>,
+    suspended: Option<bool>,
+}
+
+pub async fn admin_get_users(
+    _auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    Query(query): Query<AdminUserQuery>,
+) -> Result<Json<AdminItemsResponse<AdminUserResponse>>, AppError> {
+    let search = query
+        .search
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{s}%"));
+
+    let users: Vec<AdminUserResponse> = sqlx::query_as(&format!(
+        r#"
+        SELECT {ADMIN_USER_COLUMNS}
+        FROM users
+        WHERE ($1::TEXT IS NULL
+               OR full_name ILIKE $1
+               OR email ILIKE $1
+               OR jupyterhub_username ILIKE $1)
+          AND ($2::TEXT IS NULL OR university ILIKE $2)
+          AND ($3::TEXT IS NULL OR major ILIKE $3)
+          AND ($4::TEXT IS NULL OR role = $4)
+          AND ($5::INTEGER IS NULL OR points >= $5)
+          AND ($6::INTEGER IS NULL OR points <= $6)
+          AND ($7::BOOLEAN IS NULL OR (suspended_at IS NOT NULL) = $7)
+        ORDER BY points DESC, created_at DESC
+        "#
+    ))
+    .bind(search)
+    .bind(query.university)
+    .bind(query.major)
+    .bind(query.role)
+    .bind(query.min_points)
+    .bind(query.max_points)
+    .bind(query.suspended)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AdminItemsResponse {
+        items: users,
+        pagination: None,
+    }))
+}
@@ -0,0 +1,86 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+/// Replace a user's JupyterHub username.
+/// Without a new name the username is cleared and regenerated the next time they start a challenge.
+pub async fn admin_reset_user_jupyterhub_username(
+    auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(user_id): Path<uuid::Uuid>,
+    Json(req): Json<AdminResetJupyterHubUsernameRequest>,
+) -> Result<Json<AdminItemResponse<AdminUserResponse>>, AppError> {
+    let new_username = req
+        .jupyterhub_username
+        .map(|u| u.trim().to_lowercase())
+        .filter(|u| !u.is_empty());
+
+    if let Some(username) = &new_username {
+        // JupyterHub usernames end up in container and volume names
+        if username.len() > 64
+            || !username
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        {
+            return Err(AppError::ValidationError(
+                "JupyterHub username may only contain lowercase letters, digits, '_' and '-' (max 64 characters)"
+                    .to_string(),
+            ));
+        }
+
+        let taken: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM users WHERE jupyterhub_username = $1 AND id <> $2)",
+        )
+        .bind(username)
+        .bind(user_id)
+        .fetch_one(&state.pool)
+        .await?;
+
+        if taken {
+            return Err(AppError::BadRequest(
+                "JupyterHub username is already in use".to_string(),
+            ));
+        }
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let before = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(&new_username)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let user = fetch_admin_user(&mut *tx, user_id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "user.jupyterhub_username_reset",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            before,
+            after: snapshot(&user),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse { item: user }))
+}
@@ -0,0 +1,67 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageUsers, RequirePermission, revoke_all_sessions},
+    error::AppError,
+    models::*,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+/// Block a user from signing in and end all of their sessions
+pub async fn admin_suspend_user(
+    auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(user_id): Path<uuid::Uuid>,
+    Json(req): Json<AdminSuspendUserRequest>,
+) -> Result<Json<AdminItemResponse<AdminUserResponse>>, AppError> {
+    if user_id == auth.user_id {
+        return Err(AppError::BadRequest(
+            "You cannot suspend your own account".to_string(),
+        ));
+    }
+
+    let reason = req
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+
+    let mut tx = state.pool.begin().await?;
+    let before = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+
+    sqlx::query(
+        "UPDATE users SET suspended_at = COALESCE(suspended_at, NOW()), suspended_reason = $1 WHERE id = $2",
+    )
+    .bind(&reason)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let user = fetch_admin_user(&mut *tx, user_id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "user.suspend",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            before,
+            after: snapshot(&user),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    revoke_all_sessions(&state.pool, user_id).await?;
+
+    Ok(Json(AdminItemResponse { item: user }))
+}
@@ -0,0 +1,49 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageUsers, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+pub async fn admin_unsuspend_user(
+    auth: RequirePermission<ManageUsers>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(user_id): Path<uuid::Uuid>,
+) -> Result<Json<AdminItemResponse<AdminUserResponse>>, AppError> {
+    let mut tx = state.pool.begin().await?;
+    let before = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+
+    sqlx::query("UPDATE users SET suspended_at = NULL, suspended_reason = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let user = fetch_admin_user(&mut *tx, user_id).await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "user.unsuspend",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            before,
+            after: snapshot(&user),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse { item: user }))
+}
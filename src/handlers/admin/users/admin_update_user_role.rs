@@ -0,0 +1,85 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageRoles, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+use super::fetch_admin_user::fetch_admin_user;
+
+pub async fn admin_update_user_role(
+    auth: RequirePermission<ManageRoles>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(user_id): Path<uuid::Uuid>,
+    Json(req): Json<AdminUpdateUserRoleRequest>,
+) -> Result<Json<AdminItemResponse<AdminUserRoleResponse>>, AppError> {
+    // Demoting yourself could leave nobody able to assign roles
+    if user_id == auth.user_id {
+        return Err(AppError::BadRequest(
+            "You cannot change your own role".to_string(),
+        ));
+    }
+
+    let role_exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1)")
+            .bind(&req.role)
+            .fetch_one(&state.pool)
+            .await?;
+
+    if !role_exists {
+        return Err(AppError::ValidationError(format!(
+            "Unknown role: {}",
+            req.role
+        )));
+    }
+
+    let mut tx = state.pool.begin().await?;
+    let before = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+
+    let user: User = sqlx::query_as("UPDATE users SET role = $1 WHERE id = $2 RETURNING *")
+        .bind(&req.role)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let permissions: Vec<String> = sqlx::query_scalar(
+        "SELECT permission FROM role_permissions WHERE role = $1 ORDER BY permission",
+    )
+    .bind(&user.role)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let after = snapshot(&fetch_admin_user(&mut *tx, user_id).await?);
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "user.role",
+            target_type: "user",
+            target_id: Some(user_id.to_string()),
+            before,
+            after,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse {
+        item: AdminUserRoleResponse {
+            id: user.id,
+            full_name: user.full_name,
+            email: user.email,
+            role: user.role,
+            permissions,
+        },
+    }))
+}
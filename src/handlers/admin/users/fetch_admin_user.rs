@@ -0,0 +1,24 @@
+use crate::{error::AppError, models::AdminUserResponse};
+
+/// Columns selected from `users` for `AdminUserResponse`
+pub const ADMIN_USER_COLUMNS: &str = r#"
+    id, email, full_name, phone_num, image, university, major, role, points, rank,
+    jupyterhub_username, email_verified_at IS NOT NULL AS email_verified,
+    suspended_at, suspended_reason, created_at
+"#;
+
+pub async fn fetch_admin_user<'e, E>(
+    executor: E,
+    user_id: uuid::Uuid,
+) -> Result<AdminUserResponse, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as(&format!(
+        "SELECT {ADMIN_USER_COLUMNS} FROM users WHERE id = $1"
+    ))
+    .bind(user_id)
+    .fetch_optional(executor)
+    .await?
+    .ok_or(AppError::NotFound)
+}
@@ -0,0 +1,17 @@
+mod fetch_admin_user;
+
+pub mod admin_adjust_user_points;
+pub mod admin_get_user_by_id;
+pub mod admin_get_users;
+pub mod admin_reset_user_jupyterhub_username;
+pub mod admin_suspend_user;
+pub mod admin_unsuspend_user;
+pub mod admin_update_user_role;
+
+pub use admin_adjust_user_points::admin_adjust_user_points;
+pub use admin_get_user_by_id::admin_get_user_by_id;
+pub use admin_get_users::admin_get_users;
+pub use admin_reset_user_jupyterhub_username::admin_reset_user_jupyterhub_username;
+pub use admin_suspend_user::admin_suspend_user;
+pub use admin_unsuspend_user::admin_unsuspend_user;
+pub use admin_update_user_role::admin_update_user_role;
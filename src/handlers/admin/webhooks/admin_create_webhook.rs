@@ -0,0 +1,56 @@
+use axum::{Json, extract::State};
+
+use super::validate_webhook_subscription::validate_webhook_subscription;
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+pub async fn admin_create_webhook(
+    auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Json(req): Json<AdminCreateWebhookRequest>,
+) -> Result<Json<AdminItemResponse<WebhookSubscription>>, AppError> {
+    let (name, url) =
+        validate_webhook_subscription(&req.name, &req.url, &req.secret, &req.event_types)?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let subscription: WebhookSubscription = sqlx::query_as(
+        r#"
+        INSERT INTO webhook_subscriptions (name, url, secret, event_types, is_active, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&name)
+    .bind(&url)
+    .bind(&req.secret)
+    .bind(&req.event_types)
+    .bind(req.is_active.unwrap_or(true))
+    .bind(auth.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "webhook.create",
+            target_type: "webhook",
+            target_id: Some(subscription.id.to_string()),
+            before: None,
+            after: snapshot(&subscription),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse { item: subscription }))
+}
@@ -0,0 +1,47 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+/// Delete a subscription along with its delivery log
+pub async fn admin_delete_webhook(
+    auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<AdminSuccessResponse>, AppError> {
+    let mut tx = state.pool.begin().await?;
+
+    let subscription: WebhookSubscription =
+        sqlx::query_as("DELETE FROM webhook_subscriptions WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "webhook.delete",
+            target_type: "webhook",
+            target_id: Some(id.to_string()),
+            before: snapshot(&subscription),
+            after: None,
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminSuccessResponse { success: true }))
+}
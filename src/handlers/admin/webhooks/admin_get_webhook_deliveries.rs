@@ -0,0 +1,59 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+
+use crate::{
+    AppState,
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+#[derive(Deserialize)]
+pub struct AdminWebhookDeliveryQuery {
+    /// `pending`, `succeeded` or `failed`
+    status: Option<String>,
+    limit: Option<i64>,
+}
+
+/// Delivery log of one subscription, newest first
+pub async fn admin_get_webhook_deliveries(
+    _auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+    Query(query): Query<AdminWebhookDeliveryQuery>,
+) -> Result<Json<AdminItemsResponse<OutboundWebhookDelivery>>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM webhook_subscriptions WHERE id = $1)")
+            .bind(id)
+            .fetch_one(&state.pool)
+            .await?;
+    if !exists {
+        return Err(AppError::NotFound);
+    }
+
+    let items: Vec<OutboundWebhookDelivery> = sqlx::query_as(
+        r#"
+        SELECT *
+        FROM outbound_webhook_deliveries
+        WHERE subscription_id = $1
+          AND ($2::VARCHAR IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(id)
+    .bind(&query.status)
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
@@ -0,0 +1,23 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+pub async fn admin_get_webhooks(
+    _auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+) -> Result<Json<AdminItemsResponse<WebhookSubscription>>, AppError> {
+    let items: Vec<WebhookSubscription> =
+        sqlx::query_as("SELECT * FROM webhook_subscriptions ORDER BY created_at DESC")
+            .fetch_all(&state.pool)
+            .await?;
+
+    Ok(Json(AdminItemsResponse {
+        items,
+        pagination: None,
+    }))
+}
@@ -0,0 +1,39 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+    outbound_webhooks::retry_webhook_delivery,
+};
+
+/// Queue a pending or failed delivery to be sent on the worker's next poll
+pub async fn admin_retry_webhook_delivery(
+    auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(delivery_id): Path<uuid::Uuid>,
+) -> Result<Json<AdminItemResponse<OutboundWebhookDelivery>>, AppError> {
+    let delivery = retry_webhook_delivery(&state.pool, delivery_id).await?;
+
+    record_audit(
+        &mut *state.pool.acquire().await?,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "webhook_delivery.retry",
+            target_type: "webhook_delivery",
+            target_id: Some(delivery_id.to_string()),
+            before: None,
+            after: snapshot(&delivery),
+        },
+    )
+    .await?;
+
+    Ok(Json(AdminItemResponse { item: delivery }))
+}
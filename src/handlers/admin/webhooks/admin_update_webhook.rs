@@ -0,0 +1,76 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use super::validate_webhook_subscription::validate_webhook_subscription;
+use crate::{
+    AppState,
+    audit::{AuditEntry, RequestContext, record_audit, snapshot},
+    auth::{ManageWebhooks, RequirePermission},
+    error::AppError,
+    models::*,
+};
+
+pub async fn admin_update_webhook(
+    auth: RequirePermission<ManageWebhooks>,
+    State(state): State<AppState>,
+    request: RequestContext,
+    Path(id): Path<uuid::Uuid>,
+    Json(req): Json<AdminUpdateWebhookRequest>,
+) -> Result<Json<AdminItemResponse<WebhookSubscription>>, AppError> {
+    let existing: WebhookSubscription =
+        sqlx::query_as("SELECT * FROM webhook_subscriptions WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+    let before = snapshot(&existing);
+
+    let secret = req.secret.unwrap_or(existing.secret);
+    let event_types = req.event_types.unwrap_or(existing.event_types);
+    let is_active = req.is_active.unwrap_or(existing.is_active);
+    let (name, url) = validate_webhook_subscription(
+        req.name.as_deref().unwrap_or(&existing.name),
+        req.url.as_deref().unwrap_or(&existing.url),
+        &secret,
+        &event_types,
+    )?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let subscription: WebhookSubscription = sqlx::query_as(
+        r#"
+        UPDATE webhook_subscriptions
+        SET name = $1, url = $2, secret = $3, event_types = $4, is_active = $5, updated_at = NOW()
+        WHERE id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(&name)
+    .bind(&url)
+    .bind(&secret)
+    .bind(&event_types)
+    .bind(is_active)
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_audit(
+        &mut tx,
+        &request,
+        AuditEntry {
+            actor_id: auth.user_id,
+            action: "webhook.update",
+            target_type: "webhook",
+            target_id: Some(id.to_string()),
+            before,
+            after: snapshot(&subscription),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(Json(AdminItemResponse { item: subscription }))
+}
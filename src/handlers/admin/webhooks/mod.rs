@@ -0,0 +1,15 @@
+mod validate_webhook_subscription;
+
+pub mod admin_create_webhook;
+pub mod admin_delete_webhook;
+pub mod admin_get_webhook_deliveries;
+pub mod admin_get_webhooks;
+pub mod admin_retry_webhook_delivery;
+pub mod admin_update_webhook;
+
+pub use admin_create_webhook::admin_create_webhook;
+pub use admin_delete_webhook::admin_delete_webhook;
+pub use admin_get_webhook_deliveries::admin_get_webhook_deliveries;
+pub use admin_get_webhooks::admin_get_webhooks;
+pub use admin_retry_webhook_delivery::admin_retry_webhook_delivery;
+pub use admin_update_webhook::admin_update_webhook;
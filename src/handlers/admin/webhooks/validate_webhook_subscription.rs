@@ -0,0 +1,51 @@
+use url::Url;
+
+use crate::{error::AppError, events::EventType};
+
+/// Receivers must be able to tell our deliveries apart from forgeries
+const MIN_SECRET_LEN: usize = 16;
+
+/// Check a subscription's settings, returning the trimmed name and URL
+pub fn validate_webhook_subscription(
+    name: &str,
+    url: &str,
+    secret: &str,
+    event_types: &[String],
+) -> Result<(String, String), AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::ValidationError("name is required".to_string()));
+    }
+
+    let url = url.trim();
+    if !Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+        return Err(AppError::ValidationError(
+            "url must be an http(s) URL".to_string(),
+        ));
+    }
+
+    if secret.len() < MIN_SECRET_LEN {
+        return Err(AppError::ValidationError(format!(
+            "secret must be at least {MIN_SECRET_LEN} characters"
+        )));
+    }
+
+    if event_types.is_empty() {
+        return Err(AppError::ValidationError(
+            "eventTypes must list at least one event".to_string(),
+        ));
+    }
+
+    if let Some(unknown) = event_types
+        .iter()
+        .find(|event_type| EventType::parse(event_type).is_none())
+    {
+        let known: Vec<_> = EventType::ALL.iter().map(EventType::as_str).collect();
+        return Err(AppError::ValidationError(format!(
+            "Unknown event type '{unknown}'; expected one of {}",
+            known.join(", ")
+        )));
+    }
+
+    Ok((name.to_string(), url.to_string()))
+}
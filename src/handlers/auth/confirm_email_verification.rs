@@ -0,0 +1,35 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{AccountTokenPurpose, consume_account_token},
+    error::AppError,
+    models::*,
+};
+
+pub async fn confirm_email_verification(
+    State(state): State<AppState>,
+    Json(req): Json<EmailVerificationConfirmRequest>,
+) -> Result<Json<EmailVerificationResponse>, AppError> {
+    let user_id = consume_account_token(
+        &state.pool,
+        &req.token,
+        AccountTokenPurpose::EmailVerification,
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::BadRequest("Verification link is invalid or has expired".to_string())
+    })?;
+
+    sqlx::query(
+        "UPDATE users SET email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $1",
+    )
+    .bind(user_id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(EmailVerificationResponse {
+        success: true,
+        message: "Email verified.".to_string(),
+    }))
+}
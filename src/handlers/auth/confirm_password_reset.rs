@@ -0,0 +1,48 @@
+use axum::{Json, extract::State};
+use bcrypt::{DEFAULT_COST, hash};
+
+use crate::{
+    AppState,
+    auth::{AccountTokenPurpose, consume_account_token, revoke_all_sessions},
+    error::AppError,
+    models::*,
+};
+
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    Json(req): Json<PasswordResetConfirmRequest>,
+) -> Result<Json<PasswordResetResponse>, AppError> {
+    if req.new_password.len() < 8 {
+        return Err(AppError::ValidationError(
+            "Password must be at least 8 characters".to_string(),
+        ));
+    }
+
+    let password_hash = hash(req.new_password.as_bytes(), DEFAULT_COST)
+        .map_err(|e| AppError::InternalError(e.into()))?;
+
+    let mut tx = state.pool.begin().await?;
+
+    let user_id = consume_account_token(&mut *tx, &req.token, AccountTokenPurpose::PasswordReset)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Reset link is invalid or has expired".to_string()))?;
+
+    // Receiving the reset email also proves ownership of the address
+    sqlx::query(
+        "UPDATE users SET password_hash = $1, email_verified_at = COALESCE(email_verified_at, NOW()) WHERE id = $2",
+    )
+    .bind(&password_hash)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // Anyone holding the old password may have active sessions
+    revoke_all_sessions(&state.pool, user_id).await?;
+
+    Ok(Json(PasswordResetResponse {
+        success: true,
+        message: "Password updated. Please sign in with your new password.".to_string(),
+    }))
+}
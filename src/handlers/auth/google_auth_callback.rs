@@ -1,53 +1,107 @@
 use axum::{
     extract::{Query, State},
+    http::{
+        HeaderMap,
+        header::{COOKIE, SET_COOKIE},
+    },
     response::{IntoResponse, Redirect},
 };
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{AppState, auth::create_token, error::AppError, models::*};
+use crate::{
+    AppState,
+    auth::create_session,
+    error::AppError,
+    events::{Event, publish_event},
+    models::*,
+};
+
+use super::google_auth_init::{OAUTH_STATE_COOKIE, oauth_state_cookie, oauth_state_digest};
 
 #[derive(Debug, Deserialize)]
 pub struct OAuthCallbackQuery {
     code: String,
-    #[allow(dead_code)]
-    state: String,
+    state: Option<String>,
 }
 
 pub async fn google_auth_callback(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<OAuthCallbackQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     use oauth2::basic::BasicClient;
     use oauth2::{
-        AuthUrl, AuthorizationCode, ClientId, ClientSecret, RedirectUrl, TokenResponse, TokenUrl,
+        AuthUrl, AuthorizationCode, ClientId, ClientSecret, PkceCodeVerifier, RedirectUrl,
+        TokenResponse, TokenUrl,
     };
 
+    // The state must match one issued by google_auth_init; it is consumed on first use
+    let oauth_state = query
+        .state
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| AppError::BadRequest("Missing OAuth state parameter".to_string()))?;
+
+    // ...and come back to the browser that started the flow
+    let cookie_digest = headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == OAUTH_STATE_COOKIE)
+        .map(|(_, value)| value);
+    if cookie_digest != Some(oauth_state_digest(oauth_state).as_str()) {
+        return Err(AppError::BadRequest(
+            "This sign-in was started in another browser. Please sign in again.".to_string(),
+        ));
+    }
+
+    let (pkce_verifier, expires_at): (String, time::OffsetDateTime) = sqlx::query_as(
+        "DELETE FROM oauth_states WHERE state = $1 RETURNING pkce_verifier, expires_at",
+    )
+    .bind(oauth_state)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| {
+        AppError::BadRequest(
+            "Invalid or already used OAuth state. Please sign in again.".to_string(),
+        )
+    })?;
+
+    if expires_at < time::OffsetDateTime::now_utc() {
+        return Err(AppError::BadRequest(
+            "Google sign-in request has expired. Please sign in again.".to_string(),
+        ));
+    }
+
     // Create OAuth client
     let client = BasicClient::new(
-        ClientId::new(state.oauth_config.client_id.clone()),
-        Some(ClientSecret::new(state.oauth_config.client_secret.clone())),
-        AuthUrl::new(state.oauth_config.auth_url.clone())
+        ClientId::new(state.config.google.client_id.clone()),
+        Some(ClientSecret::new(state.config.google.client_secret.clone())),
+        AuthUrl::new(state.config.google.auth_url.clone())
             .expect("Invalid authorization endpoint URL"),
         Some(
-            TokenUrl::new(state.oauth_config.token_url.clone())
+            TokenUrl::new(state.config.google.token_url.clone())
                 .expect("Invalid token endpoint URL"),
         ),
     )
     .set_redirect_uri(
-        RedirectUrl::new(state.oauth_config.redirect_uri.clone()).expect("Invalid redirect URL"),
+        RedirectUrl::new(state.config.google.redirect_uri.clone()).expect("Invalid redirect URL"),
     );
 
     // Exchange authorization code for access token
     let token_result = client
         .exchange_code(AuthorizationCode::new(query.code))
+        .set_pkce_verifier(PkceCodeVerifier::new(pkce_verifier))
         .request_async(oauth2::reqwest::async_http_client)
         .await
         .map_err(|e| AppError::InternalError(anyhow::anyhow!("Token exchange failed: {e}")))?;
 
     // Fetch user info from Google
     let user_info: GoogleUserInfo = reqwest::Client::new()
-        .get("https://www.googleapis.com/oauth2/v3/userinfo")
+        .get(&state.config.google.userinfo_url)
         .bearer_auth(token_result.access_token().secret())
         .send()
         .await
@@ -68,7 +122,7 @@ pub async fn google_auth_callback(
     let user = if let Some(_user) = existing_user {
         // User exists, keep local profile fields (full_name/image) as-is
         sqlx::query_as(
-            "UPDATE users SET email = $1 \
+            "UPDATE users SET email = $1, email_verified_at = COALESCE(email_verified_at, NOW()) \
              WHERE google_id = $2
              RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, jupyterhub_username, created_at"
         )
@@ -89,7 +143,7 @@ pub async fn google_auth_callback(
         if let Some(existing) = email_user {
             // Link Google account to existing user
             sqlx::query_as(
-                "UPDATE users SET google_id = $1, image = COALESCE($2, image) \
+                "UPDATE users SET google_id = $1, image = COALESCE($2, image), email_verified_at = COALESCE(email_verified_at, NOW()) \
                  WHERE id = $3
                  RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, jupyterhub_username, created_at"
             )
@@ -101,10 +155,12 @@ pub async fn google_auth_callback(
         } else {
             // Create new user
             let user_id = Uuid::new_v4();
+            let mut tx = state.pool.begin().await?;
+
             let user: User = sqlx::query_as(
                 r#"
-                INSERT INTO users (id, email, password_hash, full_name, google_id, image, created_at)
-                VALUES ($1, $2, NULL, $3, $4, $5, NOW())
+                INSERT INTO users (id, email, password_hash, full_name, google_id, image, email_verified_at, created_at)
+                VALUES ($1, $2, NULL, $3, $4, $5, NOW(), NOW())
                 RETURNING id, email, password_hash, full_name, phone_num, image, points, rank, role, jupyterhub_username, created_at
                 "#,
             )
@@ -113,7 +169,7 @@ pub async fn google_auth_callback(
             .bind(user_info.name.as_deref().unwrap_or(&user_info.email))
             .bind(&user_info.sub)
             .bind(&user_info.picture)
-            .fetch_one(&state.pool)
+            .fetch_one(&mut *tx)
             .await?;
 
             // Create user stats
@@ -121,9 +177,13 @@ pub async fn google_auth_callback(
                 "INSERT INTO user_stats (user_id, created_at, updated_at) VALUES ($1, NOW(), NOW())",
             )
             .bind(user_id)
-            .execute(&state.pool)
+            .execute(&mut *tx)
             .await?;
 
+            publish_event(&mut tx, &Event::user_signed_up(&user)).await?;
+
+            tx.commit().await?;
+
             user
         }
     };
@@ -140,8 +200,10 @@ pub async fn google_auth_callback(
     // Check if user needs to set a password (password_hash is NULL)
     let needs_password = user.password_hash.is_none();
 
-    // Create JWT token
-    let token = create_token(user.id)?;
+    // Start a session (access + refresh token)
+    let tokens = create_session(&state.pool, user.id).await?;
+    let token = tokens.access_token;
+    let refresh_token = tokens.refresh_token;
 
     // Encode user data
     let user_json = serde_json::to_string(&UserResponse {
@@ -155,19 +217,22 @@ pub async fn google_auth_callback(
 
     let encoded_user = urlencoding::encode(&user_json);
 
-    // Get frontend URL from environment or use default
-    let frontend_url =
-        std::env::var("FRONTEND_URL").unwrap_or_else(|_| "https://aiclub-uj.com".to_string());
+    let frontend_url = &state.config.frontend_url;
 
-    // Redirect to frontend with token and user data
+    // Redirect to frontend with token and user data. The tokens go in the fragment, which
+    // browsers keep out of server logs and `Referer` headers.
     // If user needs password or profile completion, redirect to complete-profile page
     let redirect_url = if needs_password || needs_completion {
         format!(
-            "{frontend_url}/auth/callback?token={token}&user={encoded_user}&needs_profile_completion=true&needs_password={needs_password}"
+            "{frontend_url}/auth/callback?user={encoded_user}&needs_profile_completion=true&needs_password={needs_password}#token={token}&refresh_token={refresh_token}"
         )
     } else {
-        format!("{frontend_url}/auth/callback?token={token}&user={encoded_user}")
+        format!(
+            "{frontend_url}/auth/callback?user={encoded_user}#token={token}&refresh_token={refresh_token}"
+        )
     };
 
-    Ok(Redirect::temporary(&redirect_url))
+    let cookie = oauth_state_cookie(None, state.config.environment);
+
+    Ok(([(SET_COOKIE, cookie)], Redirect::temporary(&redirect_url)))
 }
@@ -1,36 +1,96 @@
 use axum::{
     extract::State,
+    http::header::SET_COOKIE,
     response::{IntoResponse, Redirect},
 };
+use sha2::{Digest, Sha256};
 
-use crate::AppState;
+use crate::{AppState, config::Environment, error::AppError};
 
-pub async fn google_auth_init(State(state): State<AppState>) -> impl IntoResponse {
+/// How long a started Google sign-in may take before its state is rejected
+const OAUTH_STATE_TTL_MINUTES: i64 = 10;
+/// Cookie tying a sign-in to the browser that started it, so a callback URL from someone
+/// else's flow cannot sign this browser into their account
+pub(crate) const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
+/// What the state cookie holds for `state`
+pub(crate) fn oauth_state_digest(state: &str) -> String {
+    hex::encode(Sha256::digest(state.as_bytes()))
+}
+
+/// `Set-Cookie` value that stores `digest` for the length of a sign-in, or clears the cookie
+pub(crate) fn oauth_state_cookie(digest: Option<&str>, environment: Environment) -> String {
+    let (value, max_age) = match digest {
+        Some(digest) => (digest, OAUTH_STATE_TTL_MINUTES * 60),
+        None => ("", 0),
+    };
+    let secure = if environment == Environment::Production {
+        "; Secure"
+    } else {
+        ""
+    };
+
+    format!(
+        "{OAUTH_STATE_COOKIE}={value}; Max-Age={max_age}; Path=/auth/google; HttpOnly; SameSite=Lax{secure}"
+    )
+}
+
+pub async fn google_auth_init(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
     use oauth2::basic::BasicClient;
-    use oauth2::{AuthUrl, ClientId, ClientSecret, CsrfToken, RedirectUrl, Scope, TokenUrl};
+    use oauth2::{
+        AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl, Scope, TokenUrl,
+    };
 
     // Create OAuth client
     let client = BasicClient::new(
-        ClientId::new(state.oauth_config.client_id.clone()),
-        Some(ClientSecret::new(state.oauth_config.client_secret.clone())),
-        AuthUrl::new(state.oauth_config.auth_url.clone())
+        ClientId::new(state.config.google.client_id.clone()),
+        Some(ClientSecret::new(state.config.google.client_secret.clone())),
+        AuthUrl::new(state.config.google.auth_url.clone())
             .expect("Invalid authorization endpoint URL"),
         Some(
-            TokenUrl::new(state.oauth_config.token_url.clone())
+            TokenUrl::new(state.config.google.token_url.clone())
                 .expect("Invalid token endpoint URL"),
         ),
     )
     .set_redirect_uri(
-        RedirectUrl::new(state.oauth_config.redirect_uri.clone()).expect("Invalid redirect URL"),
+        RedirectUrl::new(state.config.google.redirect_uri.clone()).expect("Invalid redirect URL"),
     );
 
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
     // Generate authorization URL
-    let (auth_url, _csrf_token) = client
+    let (auth_url, csrf_token) = client
         .authorize_url(CsrfToken::new_random)
         .add_scope(Scope::new("openid".to_string()))
         .add_scope(Scope::new("email".to_string()))
         .add_scope(Scope::new("profile".to_string()))
+        .set_pkce_challenge(pkce_challenge)
         .url();
 
-    Redirect::temporary(auth_url.as_str())
+    // Drop abandoned sign-in attempts before recording this one
+    sqlx::query("DELETE FROM oauth_states WHERE expires_at < NOW()")
+        .execute(&state.pool)
+        .await?;
+
+    let expires_at =
+        time::OffsetDateTime::now_utc() + time::Duration::minutes(OAUTH_STATE_TTL_MINUTES);
+
+    sqlx::query("INSERT INTO oauth_states (state, pkce_verifier, expires_at) VALUES ($1, $2, $3)")
+        .bind(csrf_token.secret())
+        .bind(pkce_verifier.secret())
+        .bind(expires_at)
+        .execute(&state.pool)
+        .await?;
+
+    let cookie = oauth_state_cookie(
+        Some(&oauth_state_digest(csrf_token.secret())),
+        state.config.environment,
+    );
+
+    Ok((
+        [(SET_COOKIE, cookie)],
+        Redirect::temporary(auth_url.as_str()),
+    ))
 }
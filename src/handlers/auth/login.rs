@@ -1,7 +1,7 @@
 use axum::{Json, extract::State};
 use bcrypt::verify;
 
-use crate::{AppState, auth::create_token, error::AppError, models::*};
+use crate::{AppState, auth::create_session, error::AppError, models::*};
 
 pub async fn login(
     State(state): State<AppState>,
@@ -26,10 +26,11 @@ pub async fn login(
         return Err(AppError::AuthError);
     }
 
-    let token = create_token(user.id)?;
+    let tokens = create_session(&state.pool, user.id).await?;
 
     Ok(Json(AuthResponse {
-        token,
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
         user: UserResponse {
             id: user.id,
             full_name: user.full_name,
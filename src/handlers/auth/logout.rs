@@ -0,0 +1,18 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{AuthUser, revoke_session},
+    error::AppError,
+    models::*,
+};
+
+/// Sign out of the current session only
+pub async fn logout(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<LogoutResponse>, AppError> {
+    revoke_session(&state.pool, auth.user_id, auth.session_id).await?;
+
+    Ok(Json(LogoutResponse { success: true }))
+}
@@ -0,0 +1,18 @@
+use axum::{Json, extract::State};
+
+use crate::{
+    AppState,
+    auth::{AuthUser, revoke_all_sessions},
+    error::AppError,
+    models::*,
+};
+
+/// Sign out of every session the user has open
+pub async fn logout_all(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<LogoutResponse>, AppError> {
+    revoke_all_sessions(&state.pool, auth.user_id).await?;
+
+    Ok(Json(LogoutResponse { success: true }))
+}
@@ -3,3 +3,11 @@ pub mod login;
 pub mod google_auth_init;
 pub mod google_auth_callback;
 pub mod complete_profile;
+pub mod refresh_token;
+pub mod logout;
+pub mod logout_all;
+pub mod send_verification_email;
+pub mod request_email_verification;
+pub mod confirm_email_verification;
+pub mod request_password_reset;
+pub mod confirm_password_reset;
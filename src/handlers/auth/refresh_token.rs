@@ -0,0 +1,15 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::rotate_session, error::AppError, models::*};
+
+pub async fn refresh_token(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<Json<RefreshTokenResponse>, AppError> {
+    let tokens = rotate_session(&state.pool, &req.refresh_token).await?;
+
+    Ok(Json(RefreshTokenResponse {
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+    }))
+}
@@ -0,0 +1,32 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+use super::send_verification_email::send_verification_email;
+
+/// Resend the verification email for the signed-in user
+pub async fn request_email_verification(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<EmailVerificationResponse>, AppError> {
+    let (email, email_verified_at): (String, Option<time::OffsetDateTime>) =
+        sqlx::query_as("SELECT email, email_verified_at FROM users WHERE id = $1")
+            .bind(auth.user_id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    if email_verified_at.is_some() {
+        return Ok(Json(EmailVerificationResponse {
+            success: true,
+            message: "Your email is already verified.".to_string(),
+        }));
+    }
+
+    send_verification_email(&state, auth.user_id, &email).await?;
+
+    Ok(Json(EmailVerificationResponse {
+        success: true,
+        message: "Verification email sent.".to_string(),
+    }))
+}
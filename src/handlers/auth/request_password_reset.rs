@@ -0,0 +1,66 @@
+use axum::{Json, extract::State};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    auth::{AccountTokenPurpose, issue_account_token},
+    error::AppError,
+    mailer::EmailMessage,
+    models::*,
+};
+
+/// Email a password reset link. The response is the same whether or not the
+/// address belongs to an account, so it cannot be used to discover users.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    Json(req): Json<PasswordResetRequest>,
+) -> Result<Json<PasswordResetResponse>, AppError> {
+    let user: Option<(Uuid, String)> =
+        sqlx::query_as("SELECT id, email FROM users WHERE LOWER(email) = LOWER($1)")
+            .bind(req.email.trim())
+            .fetch_optional(&state.pool)
+            .await?;
+
+    // Issuing and mailing the link happen after the response, so how long it takes does
+    // not reveal whether the address has an account
+    if let Some((user_id, email)) = user {
+        tokio::spawn(async move {
+            if let Err(e) = send_password_reset(&state, user_id, email).await {
+                tracing::error!("Failed to send password reset email: {:?}", e);
+            }
+        });
+    }
+
+    Ok(Json(PasswordResetResponse {
+        success: true,
+        message: "If an account exists for that email, a reset link has been sent.".to_string(),
+    }))
+}
+
+async fn send_password_reset(
+    state: &AppState,
+    user_id: Uuid,
+    email: String,
+) -> Result<(), AppError> {
+    let token = issue_account_token(
+        &state.pool,
+        user_id,
+        AccountTokenPurpose::PasswordReset,
+        time::Duration::hours(1),
+    )
+    .await?;
+
+    let frontend_url = &state.config.frontend_url;
+
+    let message = EmailMessage {
+        to: email,
+        subject: "Reset your UJ AI Club password".to_string(),
+        body: format!(
+            "We received a request to reset your password.\n\nChoose a new password by opening the link below:\n{frontend_url}/auth/reset-password?token={token}\n\nThe link expires in 1 hour. If you did not ask for this, you can ignore this email."
+        ),
+    };
+
+    state.mailer.send(message).await?;
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    auth::{AccountTokenPurpose, issue_account_token},
+    error::AppError,
+    mailer::EmailMessage,
+};
+
+/// Email a fresh verification link to a password-based account
+pub async fn send_verification_email(
+    state: &AppState,
+    user_id: Uuid,
+    email: &str,
+) -> Result<(), AppError> {
+    let token = issue_account_token(
+        &state.pool,
+        user_id,
+        AccountTokenPurpose::EmailVerification,
+        time::Duration::hours(48),
+    )
+    .await?;
+
+    let frontend_url = &state.config.frontend_url;
+
+    state
+        .mailer
+        .send(EmailMessage {
+            to: email.to_string(),
+            subject: "Verify your UJ AI Club email".to_string(),
+            body: format!(
+                "Welcome to the UJ AI Club!\n\nConfirm your email address by opening the link below:\n{frontend_url}/auth/verify-email?token={token}\n\nThe link expires in 48 hours."
+            ),
+        })
+        .await?;
+
+    Ok(())
+}
@@ -4,11 +4,14 @@ use uuid::Uuid;
 
 use crate::{
     AppState,
-    auth::create_token,
+    auth::create_session,
     error::AppError,
+    events::{Event, publish_event},
     models::*,
 };
 
+use super::send_verification_email::send_verification_email;
+
 pub async fn signup(
     State(state): State<AppState>,
     Json(req): Json<RegisterRequest>,
@@ -27,6 +30,8 @@ pub async fn signup(
 
     let user_id = Uuid::new_v4();
 
+    let mut tx = state.pool.begin().await?;
+
     let user: User = sqlx::query_as(
         r#"
         INSERT INTO users (id, email, password_hash, full_name, phone_num, created_at)
@@ -39,20 +44,29 @@ pub async fn signup(
     .bind(Some(password_hash))
     .bind(req.full_name)
     .bind(req.phone_num)
-    .fetch_one(&state.pool)
+    .fetch_one(&mut *tx)
     .await?;
 
     sqlx::query(
         "INSERT INTO user_stats (user_id, created_at, updated_at) VALUES ($1, NOW(), NOW())",
     )
     .bind(user_id)
-    .execute(&state.pool)
+    .execute(&mut *tx)
     .await?;
 
-    let token = create_token(user.id)?;
+    publish_event(&mut tx, &Event::user_signed_up(&user)).await?;
+
+    tx.commit().await?;
+
+    if let Err(e) = send_verification_email(&state, user.id, &user.email).await {
+        tracing::error!("Failed to send verification email: {:?}", e);
+    }
+
+    let tokens = create_session(&state.pool, user.id).await?;
 
     Ok(Json(AuthResponse {
-        token,
+        token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
         user: UserResponse {
             id: user.id,
             full_name: user.full_name,
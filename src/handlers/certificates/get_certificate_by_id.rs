@@ -9,12 +9,13 @@ pub async fn get_certificate_by_id(
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<CertificateDetailResponse>, AppError> {
-    let certificate: Certificate =
-        sqlx::query_as("SELECT * FROM certificates WHERE id = $1 AND visible = true")
-            .bind(id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or(AppError::NotFound)?;
+    let certificate: Certificate = sqlx::query_as(
+        "SELECT * FROM certificates WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
 
     let quote: Option<Quote> =
         sqlx::query_as("SELECT * FROM quotes WHERE visible = true ORDER BY RANDOM() LIMIT 1")
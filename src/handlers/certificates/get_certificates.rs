@@ -5,10 +5,11 @@ use crate::{AppState, error::AppError, models::*};
 pub async fn get_certificates(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<CertificateListResponse>>, AppError> {
-    let certificates: Vec<Certificate> =
-        sqlx::query_as("SELECT * FROM certificates WHERE visible = true ORDER BY id")
-            .fetch_all(&state.pool)
-            .await?;
+    let certificates: Vec<Certificate> = sqlx::query_as(
+        "SELECT * FROM certificates WHERE visible = true AND deleted_at IS NULL ORDER BY id",
+    )
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<CertificateListResponse> = certificates
         .into_iter()
@@ -2,12 +2,15 @@ use axum::{
     Json,
     extract::{Path, State},
 };
+use sqlx::PgPool;
 
 use crate::{
     AppState,
     auth::AuthUser,
     error::AppError,
     models::*,
+    prediction::{PredictionMetric, load_prediction_task},
+    prediction_leaderboard::load_split_leaderboard,
 };
 
 /// Get challenge submission leaderboard
@@ -16,6 +19,24 @@ pub async fn get_challenge_submission_leaderboard(
     State(state): State<AppState>,
     Path(challenge_id): Path<i32>,
 ) -> Result<Json<Vec<ChallengeSubmissionLeaderboardEntry>>, AppError> {
+    let entries = load_challenge_submission_leaderboard(&state.pool, challenge_id).await?;
+
+    Ok(Json(entries))
+}
+
+/// Each user's best graded attempt at a challenge, top 50. Prediction challenges with a
+/// split test set rank the public and private scores instead.
+pub(crate) async fn load_challenge_submission_leaderboard(
+    pool: &PgPool,
+    challenge_id: i32,
+) -> Result<Vec<ChallengeSubmissionLeaderboardEntry>, AppError> {
+    if let Some(task) = load_prediction_task(pool, challenge_id).await?
+        && task.public_percent.is_some()
+        && let Some(metric) = PredictionMetric::parse(&task.metric)
+    {
+        return load_split_leaderboard(pool, challenge_id, metric, task.final_submissions).await;
+    }
+
     let entries: Vec<ChallengeSubmissionLeaderboardEntry> = sqlx::query_as(
         r#"
         WITH ranked_attempts AS (
@@ -26,6 +47,7 @@ pub async fn get_challenge_submission_leaderboard(
                     ORDER BY cs.points_awarded DESC, cs.graded_at DESC NULLS LAST, cs.created_at DESC
                 ) AS rn
             FROM challenge_submissions cs
+            JOIN challenges c ON c.id = cs.challenge_id AND c.deleted_at IS NULL
             WHERE cs.challenge_id = $1 AND cs.status = 'graded' AND cs.points_awarded > 0
         )
         SELECT 
@@ -36,6 +58,7 @@ pub async fn get_challenge_submission_leaderboard(
             ra.points_awarded,
             ra.score,
             ra.max_score,
+            ra.metric_value,
             ra.status,
             ra.graded_at,
             RANK() OVER (ORDER BY ra.points_awarded DESC) as challenge_rank
@@ -47,8 +70,8 @@ pub async fn get_challenge_submission_leaderboard(
         "#,
     )
     .bind(challenge_id)
-    .fetch_all(&state.pool)
+    .fetch_all(pool)
     .await?;
 
-    Ok(Json(entries))
+    Ok(entries)
 }
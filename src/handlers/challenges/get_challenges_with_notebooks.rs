@@ -16,7 +16,7 @@ pub async fn get_challenges_with_notebooks(
     let challenges: Vec<Challenge> = sqlx::query_as(
         r#"
         SELECT * FROM challenges 
-        WHERE visible = true 
+        WHERE visible = true AND deleted_at IS NULL
         ORDER BY week DESC, created_at DESC
         "#,
     )
@@ -29,11 +29,22 @@ pub async fn get_challenges_with_notebooks(
         let allowed_submissions = challenge.allowed_submissions.max(1);
 
         // Check if this challenge has a notebook
-        let notebook: Option<ChallengeNotebook> =
-            sqlx::query_as("SELECT * FROM challenge_notebooks WHERE challenge_id = $1")
-                .bind(challenge.id)
-                .fetch_optional(&state.pool)
-                .await?;
+        let notebook: Option<ChallengeNotebook> = sqlx::query_as(
+            "SELECT * FROM challenge_notebooks WHERE challenge_id = $1 AND deleted_at IS NULL",
+        )
+        .bind(challenge.id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+        // Prediction challenges are scored against a hidden test set instead
+        let prediction: Option<(String, i32)> = sqlx::query_as(
+            "SELECT metric, max_points FROM challenge_prediction_tasks WHERE challenge_id = $1",
+        )
+        .bind(challenge.id)
+        .fetch_optional(&state.pool)
+        .await?;
+
+        let late_policy = challenge.late_policy_settings();
 
         responses.push(ChallengeWithNotebookResponse {
             id: challenge.id,
@@ -41,11 +52,17 @@ pub async fn get_challenges_with_notebooks(
             title: challenge.title,
             description: challenge.description,
             allowed_submissions,
+            challenge_type: challenge.challenge_type,
             has_notebook: notebook.is_some(),
-            max_points: notebook.as_ref().map(|n| n.max_points),
+            metric: prediction.as_ref().map(|(metric, _)| metric.clone()),
+            max_points: notebook
+                .as_ref()
+                .map(|n| n.max_points)
+                .or(prediction.map(|(_, max_points)| max_points)),
             time_limit_minutes: notebook.as_ref().map(|n| n.time_limit_minutes),
             start_date: challenge.start_date,
             end_date: challenge.end_date,
+            late_policy,
         });
     }
 
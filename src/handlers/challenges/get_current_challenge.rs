@@ -14,7 +14,7 @@ pub async fn get_current_challenge(
     let challenge: Challenge = sqlx::query_as(
         r#"
         SELECT * FROM challenges 
-        WHERE visible = true 
+        WHERE visible = true AND deleted_at IS NULL
         AND (start_date IS NULL OR start_date <= NOW())
         AND (end_date IS NULL OR end_date >= NOW())
         ORDER BY created_at DESC 
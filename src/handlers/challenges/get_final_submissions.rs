@@ -0,0 +1,89 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+use crate::{
+    AppState,
+    auth::AuthUser,
+    error::AppError,
+    models::*,
+    prediction::{PredictionMetric, load_prediction_task},
+    prediction_leaderboard::{final_attempts, load_scored_attempts, private_revealed},
+};
+
+/// The user's attempts that can count on a challenge's private leaderboard, and which do
+pub async fn get_final_submissions(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+) -> Result<Json<FinalSubmissionsResponse>, AppError> {
+    let response = load_final_submissions(&state.pool, auth.user_id, challenge_id).await?;
+
+    Ok(Json(response))
+}
+
+/// A visible challenge with a split test set, with its metric and end date
+pub(crate) async fn load_split_challenge(
+    pool: &PgPool,
+    challenge_id: i32,
+) -> Result<(PredictionTask, PredictionMetric, Option<OffsetDateTime>), AppError> {
+    let end_date: Option<OffsetDateTime> = sqlx::query_scalar(
+        "SELECT end_date FROM challenges WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let task = load_prediction_task(pool, challenge_id)
+        .await?
+        .filter(|task| task.public_percent.is_some())
+        .ok_or_else(|| {
+            AppError::BadRequest("This challenge has no private leaderboard".to_string())
+        })?;
+    let metric = PredictionMetric::parse(&task.metric).ok_or_else(|| {
+        AppError::InternalError(anyhow::anyhow!("Unknown metric {}", task.metric))
+    })?;
+
+    Ok((task, metric, end_date))
+}
+
+pub(crate) async fn load_final_submissions(
+    pool: &PgPool,
+    user_id: uuid::Uuid,
+    challenge_id: i32,
+) -> Result<FinalSubmissionsResponse, AppError> {
+    let (task, metric, end_date) = load_split_challenge(pool, challenge_id).await?;
+    let revealed = private_revealed(end_date, OffsetDateTime::now_utc());
+
+    let attempts = load_scored_attempts(pool, challenge_id, Some(user_id)).await?;
+    let own: Vec<_> = attempts.iter().collect();
+    let finals: Vec<uuid::Uuid> = final_attempts(metric, task.final_submissions, &own)
+        .into_iter()
+        .map(|attempt| attempt.id)
+        .collect();
+
+    let attempts = attempts
+        .iter()
+        .map(|attempt| FinalSubmissionAttempt {
+            submission_id: attempt.id,
+            attempt_number: attempt.attempt_number,
+            submitted_at: Some(attempt.submitted_at),
+            public_metric_value: attempt.public_metric_value,
+            public_score: attempt.public_score,
+            metric_value: Some(attempt.metric_value).filter(|_| revealed),
+            score: Some(attempt.score).filter(|_| revealed),
+            selected: attempt.final_selected,
+            is_final: finals.contains(&attempt.id),
+        })
+        .collect();
+
+    Ok(FinalSubmissionsResponse {
+        final_submissions: task.final_submissions,
+        locked: revealed,
+        attempts,
+    })
+}
@@ -2,12 +2,14 @@ use axum::{
     Json,
     extract::{Path, State},
 };
+use sqlx::PgPool;
 
 use crate::{
     AppState,
     auth::AuthUser,
     error::AppError,
     models::*,
+    prediction_leaderboard::private_revealed,
 };
 
 /// Get user's submission for a specific challenge
@@ -16,20 +18,32 @@ pub async fn get_user_submission(
     State(state): State<AppState>,
     Path(challenge_id): Path<i32>,
 ) -> Result<Json<Option<UserSubmissionResponse>>, AppError> {
-    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(challenge_id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let response = load_user_submission(&state.pool, auth.user_id, challenge_id).await?;
+
+    Ok(Json(response))
+}
+
+/// The user's latest attempt at a challenge, with their attempt allowance
+pub(crate) async fn load_user_submission(
+    pool: &PgPool,
+    user_id: uuid::Uuid,
+    challenge_id: i32,
+) -> Result<Option<UserSubmissionResponse>, AppError> {
+    let challenge: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(challenge_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
     let allowed_submissions = challenge.allowed_submissions.max(1);
 
     let attempts_used: i64 = sqlx::query_scalar(
         "SELECT COUNT(*) FROM challenge_submissions WHERE user_id = $1 AND challenge_id = $2",
     )
-    .bind(auth.user_id)
+    .bind(user_id)
     .bind(challenge_id)
-    .fetch_one(&state.pool)
+    .fetch_one(pool)
     .await?;
 
     let attempts_remaining = (allowed_submissions as i64 - attempts_used).max(0);
@@ -42,26 +56,48 @@ pub async fn get_user_submission(
         LIMIT 1
         "#,
     )
-    .bind(auth.user_id)
+    .bind(user_id)
     .bind(challenge_id)
-    .fetch_optional(&state.pool)
+    .fetch_optional(pool)
     .await?;
 
-    let response = submission.map(|s| UserSubmissionResponse {
-        id: s.id,
-        challenge_id: s.challenge_id,
-        attempt_number: s.attempt_number,
-        status: s.status,
-        score: s.score,
-        max_score: s.max_score,
-        points_awarded: s.points_awarded,
-        started_at: s.started_at,
-        submitted_at: s.submitted_at,
-        graded_at: s.graded_at,
-        allowed_submissions,
-        attempts_used,
-        attempts_remaining,
+    let response = submission.map(|s| {
+        // Feedback is only released with the grade
+        let graded = s.status == "graded";
+        let late = s.late_penalty();
+        // The private result of a split test set, and the points that follow it, wait for
+        // the end date
+        let hidden = s.public_metric_value.is_some()
+            && !private_revealed(challenge.end_date, time::OffsetDateTime::now_utc());
+
+        UserSubmissionResponse {
+            id: s.id,
+            challenge_id: s.challenge_id,
+            attempt_number: s.attempt_number,
+            status: s.status,
+            score: s.score.filter(|_| !hidden),
+            max_score: s.max_score,
+            metric_value: s.metric_value.filter(|_| !hidden),
+            public_metric_value: s.public_metric_value,
+            public_score: s.public_score,
+            // On a split test set only the attempts counted on the private leaderboard earn points
+            points_awarded: if s.public_metric_value.is_some() && !s.points_credited {
+                0
+            } else {
+                s.points_awarded
+            },
+            started_at: s.started_at,
+            submitted_at: s.submitted_at,
+            graded_at: s.graded_at,
+            deadline_at: s.deadline_at,
+            allowed_submissions,
+            attempts_used,
+            attempts_remaining,
+            feedback: s.feedback.filter(|_| graded),
+            rubric: s.rubric_scores.filter(|_| graded).map(|r| r.0),
+            late,
+        }
     });
 
-    Ok(Json(response))
+    Ok(response)
 }
@@ -1,7 +1,12 @@
 pub mod get_current_challenge;
+pub mod get_final_submissions;
 pub mod get_challenge_leaderboard;
 pub mod get_challenges_with_notebooks;
 pub mod get_user_submission;
+pub mod select_final_submissions;
 pub mod start_challenge;
 pub mod submit_challenge;
+pub mod submit_predictions;
 pub mod get_challenge_submission_leaderboard;
+pub mod stream_challenge_submission_leaderboard;
+pub mod stream_user_submission;
@@ -0,0 +1,68 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{
+    AppState,
+    auth::AuthUser,
+    error::AppError,
+    models::*,
+    prediction_leaderboard::{load_scored_attempts, private_revealed},
+};
+
+use super::get_final_submissions::{load_final_submissions, load_split_challenge};
+
+/// Choose which of the user's attempts count on a challenge's private leaderboard
+pub async fn select_final_submissions(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+    Json(req): Json<SelectFinalSubmissionsRequest>,
+) -> Result<Json<FinalSubmissionsResponse>, AppError> {
+    let (task, _, end_date) = load_split_challenge(&state.pool, challenge_id).await?;
+
+    if private_revealed(end_date, time::OffsetDateTime::now_utc()) {
+        return Err(AppError::BadRequest(
+            "Final submissions are fixed once the challenge has ended".to_string(),
+        ));
+    }
+
+    let mut submission_ids = req.submission_ids;
+    submission_ids.sort();
+    submission_ids.dedup();
+    if submission_ids.len() > task.final_submissions as usize {
+        return Err(AppError::ValidationError(format!(
+            "You can choose at most {} final submissions",
+            task.final_submissions
+        )));
+    }
+
+    let attempts = load_scored_attempts(&state.pool, challenge_id, Some(auth.user_id)).await?;
+    if let Some(unknown) = submission_ids
+        .iter()
+        .find(|id| !attempts.iter().any(|attempt| attempt.id == **id))
+    {
+        return Err(AppError::BadRequest(format!(
+            "Submission {} is not one of your scored attempts",
+            unknown
+        )));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE challenge_submissions
+        SET final_selected = (id = ANY($3)), updated_at = NOW()
+        WHERE user_id = $1 AND challenge_id = $2
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(challenge_id)
+    .bind(&submission_ids)
+    .execute(&state.pool)
+    .await?;
+
+    let response = load_final_submissions(&state.pool, auth.user_id, challenge_id).await?;
+
+    Ok(Json(response))
+}
@@ -5,8 +5,12 @@ use axum::{
 
 use crate::{
     AppState,
+    attempts::{attempt_deadline, close_overdue_attempt, is_overdue},
     auth::AuthUser,
     error::AppError,
+    grading::{GradingRequest, dispatch_grading_job, enqueue_grading_job},
+    late_policy::ChallengeDeadline,
+    live_updates::notify_submission_changed,
     models::*,
 };
 
@@ -17,12 +21,19 @@ pub async fn start_challenge(
     Path(challenge_id): Path<i32>,
 ) -> Result<Json<StartChallengeResponse>, AppError> {
     // Verify the challenge exists and has a notebook
-    let challenge: Challenge =
-        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND visible = true")
-            .bind(challenge_id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or(AppError::NotFound)?;
+    let challenge: Challenge = sqlx::query_as(
+        "SELECT * FROM challenges WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if ChallengeType::parse(&challenge.challenge_type) == Some(ChallengeType::Prediction) {
+        return Err(AppError::BadRequest(
+            "This challenge takes a predictions CSV; upload it instead".to_string(),
+        ));
+    }
 
     let allowed_submissions = challenge.allowed_submissions.max(1);
 
@@ -35,21 +46,20 @@ pub async fn start_challenge(
             "Challenge has not started yet".to_string(),
         ));
     }
-    if let Some(end_date) = challenge.end_date
-        && now > end_date
-    {
+    // Past the end date, attempts can still start while the late policy accepts late work
+    let deadline = ChallengeDeadline::from(&challenge);
+    if deadline.is_closed(now) {
         return Err(AppError::BadRequest("Challenge has ended".to_string()));
     }
 
     // Get the notebook for this challenge
-    let notebook: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE challenge_id = $1")
-            .bind(challenge_id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or_else(|| {
-                AppError::BadRequest("This challenge does not have a notebook".to_string())
-            })?;
+    let notebook: ChallengeNotebook = sqlx::query_as(
+        "SELECT * FROM challenge_notebooks WHERE challenge_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::BadRequest("This challenge does not have a notebook".to_string()))?;
 
     // Get user info (verify user exists)
     let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
@@ -96,10 +106,21 @@ pub async fn start_challenge(
     .fetch_optional(&state.pool)
     .await?;
 
-    let (submission_id, attempt_number, attempts_used_after) = if let Some(existing) =
-        existing_in_progress
-    {
-        (existing.id, existing.attempt_number, attempts_used)
+    // An attempt whose time has run out is closed first, so a fresh one can start if any remain
+    let existing_in_progress = match existing_in_progress {
+        Some(existing) if is_overdue(existing.deadline_at, &state.config.attempts) => {
+            close_overdue_attempt(&state.pool, existing.id, &state.config.attempts).await?;
+            None
+        }
+        existing => existing,
+    };
+
+    // Create the attempt and queue its notebook together, so a new attempt is never left
+    // without the grading service eventually preparing its notebook
+    let mut tx = state.pool.begin().await?;
+
+    let (attempt, attempts_used_after) = if let Some(existing) = existing_in_progress {
+        (existing, attempts_used)
     } else {
         if attempts_used >= allowed_submissions as i64 {
             return Err(AppError::BadRequest(format!(
@@ -110,11 +131,12 @@ pub async fn start_challenge(
 
         let next_attempt_number = attempts_used as i32 + 1;
 
-        // Create new attempt
+        // Create new attempt; the deadline is fixed now so later time limit edits don't move it
+        let deadline_at = attempt_deadline(now, notebook.time_limit_minutes, deadline.closes_at());
         let new_submission: ChallengeSubmission = sqlx::query_as(
             r#"
-            INSERT INTO challenge_submissions (user_id, challenge_id, notebook_id, attempt_number, status, started_at)
-            VALUES ($1, $2, $3, $4, 'in_progress', NOW())
+            INSERT INTO challenge_submissions (user_id, challenge_id, notebook_id, attempt_number, status, started_at, deadline_at)
+            VALUES ($1, $2, $3, $4, 'in_progress', $5, $6)
             RETURNING *
             "#
         )
@@ -122,7 +144,9 @@ pub async fn start_challenge(
         .bind(challenge_id)
         .bind(notebook.id)
         .bind(next_attempt_number)
-        .fetch_one(&state.pool)
+        .bind(now)
+        .bind(deadline_at)
+        .fetch_one(&mut *tx)
         .await?;
 
         // Count first challenge engagement once
@@ -131,65 +155,40 @@ pub async fn start_challenge(
                 "UPDATE user_stats SET challenges_taken = challenges_taken + 1, updated_at = NOW() WHERE user_id = $1"
             )
             .bind(auth.user_id)
-            .execute(&state.pool)
+            .execute(&mut *tx)
             .await?;
         }
 
-        (
-            new_submission.id,
-            new_submission.attempt_number,
-            attempts_used + 1,
-        )
+        notify_submission_changed(&mut tx, &new_submission).await?;
+
+        (new_submission, attempts_used + 1)
     };
 
+    // Queue the grading service call that prepares the notebook in user's workspace
+    // This copies and processes the notebook (removes solutions) for the user
+    // If it is slow or failing, the pre_spawn_hook might still copy it
+    let job_id = enqueue_grading_job(
+        &mut tx,
+        &GradingRequest::prepare_notebook(&jupyterhub_username, &notebook),
+        Some(attempt.id),
+        Some(notebook.id),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    dispatch_grading_job(state.pool.clone(), state.grading.clone(), job_id);
+
     let attempts_remaining = (allowed_submissions as i64 - attempts_used_after).max(0);
 
     // Create a JWT token for JupyterHub SSO
     let jupyterhub_token =
         crate::auth::create_jupyterhub_token(auth.user_id, &jupyterhub_username)?;
 
-    // Call grading service to prepare the notebook in user's workspace
-    // This copies and processes the notebook (removes solutions) for the user
-    let grading_service_url = std::env::var("GRADING_SERVICE_URL")
-        .unwrap_or_else(|_| "http://localhost:9100".to_string());
-
-    let client = reqwest::Client::new();
-    let prepare_url = format!(
-        "{}/prepare-notebook/{}/{}",
-        grading_service_url, jupyterhub_username, notebook.assignment_name
-    );
-
-    // Try to prepare the notebook, but don't fail if grading service is unavailable
-    // The pre_spawn_hook might still copy it
-    match client
-        .post(&prepare_url)
-        .json(&serde_json::json!({
-            "notebookPath": notebook.notebook_path,
-            "notebookFilename": notebook.notebook_filename
-        }))
-        .send()
-        .await
-    {
-        Ok(resp) => {
-            if resp.status().is_success() {
-                tracing::info!("Notebook prepared for user {}", jupyterhub_username);
-            } else {
-                tracing::warn!(
-                    "Failed to prepare notebook: {} - will rely on pre_spawn_hook",
-                    resp.status()
-                );
-            }
-        }
-        Err(e) => {
-            tracing::warn!("Could not reach grading service to prepare notebook: {}", e);
-        }
-    }
-
     // Generate JupyterHub URL
     // Use notebook_filename (the original filename) since the pre-spawn hook copies notebooks
     // with their original filename (stripping only the UUID prefix)
-    let jupyterhub_base_url = std::env::var("JUPYTERHUB_URL")
-        .unwrap_or_else(|_| "https://jupyter.aiclub-uj.com".to_string());
+    let jupyterhub_base_url = &state.config.jupyterhub_url;
 
     // URL pattern: login -> spawn -> notebook
     // The spawn endpoint ensures the server is started before redirecting to the notebook
@@ -206,10 +205,13 @@ pub async fn start_challenge(
     Ok(Json(StartChallengeResponse {
         success: true,
         jupyterhub_url,
-        submission_id,
-        attempt_number,
+        submission_id: attempt.id,
+        attempt_number: attempt.attempt_number,
         attempts_used: attempts_used_after,
         attempts_remaining,
         token: jupyterhub_token,
+        started_at: attempt.started_at,
+        deadline_at: attempt.deadline_at,
+        time_limit_minutes: notebook.time_limit_minutes,
     }))
 }
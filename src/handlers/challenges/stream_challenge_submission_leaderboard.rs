@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, State},
+    response::sse::{KeepAlive, Sse},
+};
+use futures_util::Stream;
+
+use crate::{
+    AppState,
+    auth::AuthUser,
+    error::AppError,
+    live_updates::{LiveUpdate, snapshot_stream},
+};
+
+use super::get_challenge_submission_leaderboard::load_challenge_submission_leaderboard;
+
+/// Stream a challenge's leaderboard as server-sent `leaderboard` events: the current
+/// standings first, then again whenever credited points for the challenge change
+pub async fn stream_challenge_submission_leaderboard(
+    _auth: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<axum::response::sse::Event, axum::Error>>>, AppError> {
+    let updates = state.live_updates.subscribe();
+    let initial = load_challenge_submission_leaderboard(&state.pool, challenge_id).await?;
+
+    let pool = state.pool.clone();
+    let stream = snapshot_stream(
+        updates,
+        "leaderboard",
+        initial,
+        move |update| matches!(update, LiveUpdate::Leaderboard { challenge_id: id } if *id == challenge_id),
+        move || {
+            let pool = pool.clone();
+            async move { load_challenge_submission_leaderboard(&pool, challenge_id).await }
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Path, State},
+    response::sse::{KeepAlive, Sse},
+};
+use futures_util::Stream;
+
+use crate::{
+    AppState,
+    auth::AuthUser,
+    error::AppError,
+    live_updates::{LiveUpdate, snapshot_stream},
+};
+
+use super::get_user_submission::load_user_submission;
+
+/// Stream the user's submission for a challenge as server-sent `submission` events: the
+/// current state first, then again whenever its status changes (started, submitted, graded)
+pub async fn stream_user_submission(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+) -> Result<Sse<impl Stream<Item = Result<axum::response::sse::Event, axum::Error>>>, AppError> {
+    let updates = state.live_updates.subscribe();
+    let initial = load_user_submission(&state.pool, auth.user_id, challenge_id).await?;
+
+    let user_id = auth.user_id;
+    let pool = state.pool.clone();
+    let stream = snapshot_stream(
+        updates,
+        "submission",
+        initial,
+        move |update| {
+            matches!(update, LiveUpdate::Submission { user_id: owner, challenge_id: id, .. }
+                if *owner == user_id && *id == challenge_id)
+        },
+        move || {
+            let pool = pool.clone();
+            async move { load_user_submission(&pool, user_id, challenge_id).await }
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -5,8 +5,13 @@ use axum::{
 
 use crate::{
     AppState,
+    attempts::{TimeoutAction, close_overdue_attempt, is_overdue},
     auth::AuthUser,
     error::AppError,
+    events::{Event, publish_event},
+    grading::{GradingRequest, dispatch_grading_job, enqueue_grading_job},
+    late_policy::ChallengeDeadline,
+    live_updates::notify_submission_changed,
     models::*,
 };
 
@@ -17,23 +22,25 @@ pub async fn submit_challenge(
     State(state): State<AppState>,
     Path(challenge_id): Path<i32>,
 ) -> Result<Json<SubmitChallengeResponse>, AppError> {
-    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
-        .bind(challenge_id)
-        .fetch_optional(&state.pool)
-        .await?
-        .ok_or(AppError::NotFound)?;
+    let challenge: Challenge =
+        sqlx::query_as("SELECT * FROM challenges WHERE id = $1 AND deleted_at IS NULL")
+            .bind(challenge_id)
+            .fetch_optional(&state.pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
 
     let allowed_submissions = challenge.allowed_submissions.max(1);
+    let deadline = ChallengeDeadline::from(&challenge);
+    let grading_mode = challenge.parsed_grading_mode()?;
 
     // Get the notebook info
-    let notebook: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE challenge_id = $1")
-            .bind(challenge_id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or_else(|| {
-                AppError::BadRequest("This challenge does not have a notebook".to_string())
-            })?;
+    let notebook: ChallengeNotebook = sqlx::query_as(
+        "SELECT * FROM challenge_notebooks WHERE challenge_id = $1 AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::BadRequest("This challenge does not have a notebook".to_string()))?;
 
     let attempts_used: i64 = sqlx::query_scalar(
         "SELECT COUNT(*) FROM challenge_submissions WHERE user_id = $1 AND challenge_id = $2",
@@ -59,39 +66,64 @@ pub async fn submit_challenge(
     .fetch_optional(&state.pool)
     .await?;
 
-    let submission = if let Some(submission) = submission {
-        submission
-    } else {
-        let latest_submission: Option<ChallengeSubmission> = sqlx::query_as(
-            r#"
-            SELECT * FROM challenge_submissions
-            WHERE user_id = $1 AND challenge_id = $2
-            ORDER BY attempt_number DESC
-            LIMIT 1
-            "#,
+    let Some(submission) = submission else {
+        return no_in_progress_attempt(
+            &state,
+            auth.user_id,
+            challenge_id,
+            attempts_used,
+            attempts_remaining,
+            &deadline,
         )
-        .bind(auth.user_id)
-        .bind(challenge_id)
-        .fetch_optional(&state.pool)
-        .await?;
+        .await;
+    };
 
-        if let Some(latest) = latest_submission {
-            if latest.status == "grading_pending" {
-                return Ok(Json(SubmitChallengeResponse {
-                    success: true,
-                    message: "Your submission is pending manual grading by an admin.".to_string(),
-                    status: "grading_pending".to_string(),
-                    attempt_number: latest.attempt_number,
-                    attempts_used,
-                    attempts_remaining,
-                }));
+    // Past the deadline and grace period the attempt is closed per the timeout policy instead
+    if is_overdue(submission.deadline_at, &state.config.attempts) {
+        let closed_as = match close_overdue_attempt(
+            &state.pool,
+            submission.id,
+            &state.config.attempts,
+        )
+        .await?
+        {
+            Some(action) => Some(action),
+            // The sweeper closed it first; report what it did
+            None => {
+                let status: String =
+                    sqlx::query_scalar("SELECT status FROM challenge_submissions WHERE id = $1")
+                        .bind(submission.id)
+                        .fetch_one(&state.pool)
+                        .await?;
+
+                match status.as_str() {
+                    "in_progress" => None,
+                    "expired" => Some(TimeoutAction::Expire),
+                    _ => Some(TimeoutAction::Submit),
+                }
             }
-        }
+        };
 
-        return Err(AppError::BadRequest(
-            "No in-progress attempt found. Start the challenge before submitting.".to_string(),
-        ));
-    };
+        let outcome = match closed_as {
+            Some(TimeoutAction::Expire) => " The attempt has expired.",
+            Some(TimeoutAction::Submit) => {
+                " Your notebook was submitted automatically at the deadline."
+            }
+            None => "",
+        };
+
+        return Err(AppError::BadRequest(format!(
+            "The time limit for this attempt has passed.{}",
+            outcome
+        )));
+    }
+
+    // Timed attempts never run past the point the challenge closes, so only untimed ones
+    // can still be open once the late policy stops accepting work
+    let now = time::OffsetDateTime::now_utc();
+    if submission.deadline_at.is_none() && deadline.is_closed(now) {
+        return Err(AppError::BadRequest("Challenge has ended".to_string()));
+    }
 
     // Get the user's JupyterHub username
     let user: User = sqlx::query_as("SELECT * FROM users WHERE id = $1")
@@ -103,65 +135,114 @@ pub async fn submit_challenge(
         .jupyterhub_username
         .ok_or_else(|| AppError::BadRequest("JupyterHub username not set".to_string()))?;
 
-    // Update submission status to "grading_pending"
-    sqlx::query(
+    // Mark the attempt pending and queue the grading call together, so a submission is
+    // never left pending without the grading service eventually hearing about it
+    let mut tx = state.pool.begin().await?;
+
+    // Only an attempt still in progress can be submitted; a concurrent submit or the
+    // timeout sweeper may have closed it since it was read
+    let updated = sqlx::query(
         r#"
         UPDATE challenge_submissions
         SET status = 'grading_pending',
-            submitted_at = NOW(),
+            submitted_at = $2,
             updated_at = NOW()
-        WHERE id = $1
+        WHERE id = $1 AND status = 'in_progress'
         "#,
     )
     .bind(submission.id)
-    .execute(&state.pool)
-    .await?
-    ;
-
-    // Call JupyterHub API to trigger submission/grading
-    // The grading service will watch for the submission and grade it
-    let grading_service_url = std::env::var("GRADING_SERVICE_URL")
-        .unwrap_or_else(|_| "http://uj-ai-club-grading:9100".to_string());
-
-    // Call grading service API to trigger submission/grading
-    // This copies the notebook from user's workspace to nbgrader exchange and grades it
-    let client = reqwest::Client::new();
-    let trigger_url = format!(
-        "{}/submit/{}/{}",
-        grading_service_url, jupyterhub_username, notebook.assignment_name
-    );
-
-    // Include the notebook filename and path in the request
-    let payload = serde_json::json!({
-        "notebookFilename": notebook.notebook_filename,
-        "notebookPath": notebook.notebook_path
-    });
-
-    match client.post(&trigger_url).json(&payload).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                tracing::info!(
-                    "Grading triggered for user {} on assignment {}",
-                    jupyterhub_username,
-                    notebook.assignment_name
-                );
-            } else {
-                let status = response.status();
-                let error_text = response.text().await.unwrap_or_default();
-                tracing::warn!("Failed to trigger grading: {} - {}", status, error_text);
-            }
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        tx.rollback().await?;
+        return no_in_progress_attempt(
+            &state,
+            auth.user_id,
+            challenge_id,
+            attempts_used,
+            attempts_remaining,
+            &deadline,
+        )
+        .await;
+    }
+
+    // The grading service copies the notebook to the nbgrader exchange and grades it
+    let job_id = enqueue_grading_job(
+        &mut tx,
+        &GradingRequest::submit(&jupyterhub_username, &notebook),
+        Some(submission.id),
+        Some(notebook.id),
+    )
+    .await?;
+
+    publish_event(&mut tx, &Event::submission_submitted(&submission)).await?;
+    notify_submission_changed(&mut tx, &submission).await?;
+
+    tx.commit().await?;
+
+    dispatch_grading_job(state.pool.clone(), state.grading.clone(), job_id);
+
+    let message = match grading_mode {
+        GradingMode::Auto => "Submission received and will be graded automatically.",
+        GradingMode::AutoWithReview => {
+            "Submission received. It will be graded automatically and reviewed by an admin."
         }
-        Err(e) => {
-            tracing::warn!("Failed to call grading service: {}", e);
+        GradingMode::Manual => {
+            "Submission received and marked as grading pending. An admin will review it manually."
         }
-    }
+    };
 
     Ok(Json(SubmitChallengeResponse {
         success: true,
-        message: "Submission received and marked as grading pending. An admin will review it manually.".to_string(),
+        message: message.to_string(),
         status: "grading_pending".to_string(),
         attempt_number: submission.attempt_number,
         attempts_used,
         attempts_remaining,
+        late: deadline.assess(now),
     }))
 }
+
+/// Response when the user has no attempt in progress: an attempt already submitted is
+/// reported as pending, anything else has to be started first
+async fn no_in_progress_attempt(
+    state: &AppState,
+    user_id: uuid::Uuid,
+    challenge_id: i32,
+    attempts_used: i64,
+    attempts_remaining: i64,
+    deadline: &ChallengeDeadline,
+) -> Result<Json<SubmitChallengeResponse>, AppError> {
+    let latest_submission: Option<ChallengeSubmission> = sqlx::query_as(
+        r#"
+        SELECT * FROM challenge_submissions
+        WHERE user_id = $1 AND challenge_id = $2
+        ORDER BY attempt_number DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    if let Some(latest) = latest_submission
+        && latest.status == "grading_pending"
+    {
+        return Ok(Json(SubmitChallengeResponse {
+            success: true,
+            message: "Your submission is pending manual grading by an admin.".to_string(),
+            status: "grading_pending".to_string(),
+            attempt_number: latest.attempt_number,
+            attempts_used,
+            attempts_remaining,
+            late: latest.submitted_at.and_then(|at| deadline.assess(at)),
+        }));
+    }
+
+    Err(AppError::BadRequest(
+        "No in-progress attempt found. Start the challenge before submitting.".to_string(),
+    ))
+}
@@ -0,0 +1,297 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, State},
+};
+
+use crate::{
+    AppState,
+    auth::AuthUser,
+    error::AppError,
+    events::{Event, publish_event},
+    grading::points_for_score,
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    late_policy::{ChallengeDeadline, apply_late_penalty},
+    live_updates::{notify_leaderboard_changed, notify_submission_changed},
+    models::*,
+    notifications::{NewNotification, create_notification},
+    points::record_point_transaction,
+    prediction::{
+        PredictionMetric, evaluate, load_ground_truth, load_prediction_task, pair_predictions,
+        public_ids, read_prediction_csv,
+    },
+    prediction_leaderboard::private_revealed,
+};
+
+/// Submit a predictions CSV (`file`) for a prediction challenge. The file is checked against
+/// the hidden test set and scored straight away; each upload that scores uses an attempt.
+/// On a split test set only the public result is shown until the end date, and points wait
+/// for the private leaderboard to be revealed.
+pub async fn submit_predictions(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(challenge_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<Json<SubmitPredictionsResponse>, AppError> {
+    let challenge: Challenge = sqlx::query_as(
+        "SELECT * FROM challenges WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(challenge_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if ChallengeType::parse(&challenge.challenge_type) != Some(ChallengeType::Prediction) {
+        return Err(AppError::BadRequest(
+            "This challenge is a notebook; start it instead".to_string(),
+        ));
+    }
+
+    let allowed_submissions = challenge.allowed_submissions.max(1);
+
+    let now = time::OffsetDateTime::now_utc();
+    if let Some(start_date) = challenge.start_date
+        && now < start_date
+    {
+        return Err(AppError::BadRequest(
+            "Challenge has not started yet".to_string(),
+        ));
+    }
+    let deadline = ChallengeDeadline::from(&challenge);
+    if deadline.is_closed(now) {
+        return Err(AppError::BadRequest("Challenge has ended".to_string()));
+    }
+
+    let task = load_prediction_task(&state.pool, challenge_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::BadRequest("This challenge is not accepting predictions yet".to_string())
+        })?;
+    let metric = PredictionMetric::parse(&task.metric).ok_or_else(|| {
+        AppError::InternalError(anyhow::anyhow!("Unknown metric {}", task.metric))
+    })?;
+
+    let mut upload: Option<(String, Vec<u8>)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::InternalError(e.into()))?
+    {
+        if field.name() == Some("file") {
+            let filename = field.file_name().unwrap_or("predictions.csv").to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::InternalError(e.into()))?;
+            upload = Some((filename, bytes.to_vec()));
+        }
+    }
+    let (filename, predictions) = upload
+        .ok_or_else(|| AppError::BadRequest("A predictions CSV file is required".to_string()))?;
+
+    // A file that does not match the test set is rejected without using an attempt
+    let ground_truth = load_ground_truth(&state.pool, task.id).await?;
+    let truth = read_prediction_csv(&ground_truth, &task.id_column, &task.target_column)?;
+    let rows = read_prediction_csv(&predictions, &task.id_column, &task.target_column)?;
+    let pairs = pair_predictions(&truth, rows)?;
+
+    // On a split test set the stored result is the private one
+    let (metric_value, public_metric_value) = match task.public_percent {
+        Some(percent) => {
+            let public = public_ids(challenge_id, &truth, percent);
+            let (public_pairs, private_pairs): (Vec<_>, Vec<_>) = pairs
+                .into_iter()
+                .partition(|(expected, _)| public.contains(expected.id.as_str()));
+            (
+                evaluate(metric, &private_pairs)?,
+                Some(evaluate(metric, &public_pairs)?),
+            )
+        }
+        None => (evaluate(metric, &pairs)?, None),
+    };
+    let hidden = public_metric_value.is_some() && !private_revealed(challenge.end_date, now);
+
+    let score = metric.score(metric_value, task.baseline);
+    let public_score = public_metric_value.map(|value| metric.score(value, task.baseline));
+    let late = deadline.assess(now);
+    let points = apply_late_penalty(
+        points_for_score(score, 100.0, task.max_points),
+        late.as_ref(),
+    );
+
+    // Scores go through the challenge's grading mode like nbgrader results do
+    let grading_mode = challenge.parsed_grading_mode()?;
+    let (status, proposed_points, credit) = match grading_mode {
+        GradingMode::Manual => ("grading_pending", 0, false),
+        GradingMode::AutoWithReview => ("grading_pending", points, false),
+        GradingMode::Auto => ("graded", points, !hidden),
+    };
+
+    let mut tx = state.pool.begin().await?;
+
+    // Hold off a new test set until this attempt is stored, and refuse a score computed
+    // against one that was replaced while the file was being read
+    let task_updated_at: Option<time::OffsetDateTime> = sqlx::query_scalar(
+        r#"
+        SELECT t.updated_at
+        FROM challenges c
+        JOIN challenge_prediction_tasks t ON t.challenge_id = c.id
+        WHERE c.id = $1
+        FOR SHARE OF c
+        "#,
+    )
+    .bind(challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if task_updated_at != Some(task.updated_at) {
+        return Err(AppError::Conflict(
+            "The test set changed while your predictions were scored; upload them again"
+                .to_string(),
+        ));
+    }
+
+    let attempts_used: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM challenge_submissions WHERE user_id = $1 AND challenge_id = $2",
+    )
+    .bind(auth.user_id)
+    .bind(challenge_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if attempts_used >= allowed_submissions as i64 {
+        return Err(AppError::BadRequest(format!(
+            "Submission limit reached for this challenge ({} attempts)",
+            allowed_submissions
+        )));
+    }
+
+    let inserted = sqlx::query_as(
+        r#"
+        INSERT INTO challenge_submissions (
+            user_id, challenge_id, attempt_number, status, score, max_score, metric_value,
+            points_awarded, points_credited, started_at, submitted_at, graded_at,
+            late_minutes, late_penalty_percent, late_practice_only,
+            public_metric_value, public_score
+        )
+        VALUES ($1, $2, $3, $4, $5, 100.0, $6, $7, $8, $9, $9,
+                CASE WHEN $4 = 'graded' THEN $9 END, $10, $11, $12, $13, $14)
+        RETURNING *
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(challenge_id)
+    .bind(attempts_used as i32 + 1)
+    .bind(status)
+    .bind(score)
+    .bind(metric_value)
+    .bind(proposed_points)
+    .bind(credit)
+    .bind(now)
+    .bind(late.as_ref().map(|l| l.minutes_late))
+    .bind(late.as_ref().map_or(0.0, |l| l.penalty_percent))
+    .bind(late.as_ref().is_some_and(|l| l.practice_only))
+    .bind(public_metric_value)
+    .bind(public_score)
+    .fetch_one(&mut *tx)
+    .await;
+
+    // Two uploads at once would both claim the next attempt number
+    let submission: ChallengeSubmission = match inserted {
+        Ok(submission) => submission,
+        Err(sqlx::Error::Database(db_err)) if db_err.code().as_deref() == Some("23505") => {
+            return Err(AppError::Conflict(
+                "Another upload for this challenge is being scored; try again".to_string(),
+            ));
+        }
+        Err(e) => return Err(AppError::DatabaseError(e)),
+    };
+
+    sqlx::query(
+        "INSERT INTO challenge_prediction_files (submission_id, filename, content) VALUES ($1, $2, $3)",
+    )
+    .bind(submission.id)
+    .bind(&filename)
+    .bind(&predictions)
+    .execute(&mut *tx)
+    .await?;
+
+    // Count first challenge engagement once
+    if attempts_used == 0 {
+        sqlx::query(
+            "UPDATE user_stats SET challenges_taken = challenges_taken + 1, updated_at = NOW() WHERE user_id = $1",
+        )
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    publish_event(&mut tx, &Event::submission_submitted(&submission)).await?;
+
+    if credit {
+        record_point_transaction(
+            &mut tx,
+            auth.user_id,
+            points,
+            &format!(
+                "Auto-graded: {} (attempt {})",
+                challenge.title, submission.attempt_number
+            ),
+            Some(submission.id),
+            None,
+        )
+        .await?;
+
+        publish_event(&mut tx, &Event::submission_graded(&submission)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::submission_graded(&submission, &challenge.title),
+        )
+        .await?;
+    }
+
+    // A split test set ranks public scores before any points are credited
+    if credit || public_metric_value.is_some() {
+        notify_leaderboard_changed(&mut tx, challenge_id).await?;
+    }
+
+    notify_submission_changed(&mut tx, &submission).await?;
+
+    tx.commit().await?;
+
+    if credit {
+        update_user_ranks(&state.pool).await?;
+    }
+
+    let attempts_used = attempts_used + 1;
+    let message = match grading_mode {
+        GradingMode::Auto if hidden => {
+            "Predictions scored. Points follow the private leaderboard, revealed when the challenge ends."
+                .to_string()
+        }
+        GradingMode::Auto => format!("Predictions scored: {} points awarded", points),
+        GradingMode::AutoWithReview => {
+            "Predictions scored. The points will be credited once an admin reviews them."
+                .to_string()
+        }
+        GradingMode::Manual => {
+            "Predictions scored and marked as grading pending. An admin will review them."
+                .to_string()
+        }
+    };
+
+    Ok(Json(SubmitPredictionsResponse {
+        success: true,
+        message,
+        submission_id: submission.id,
+        status: submission.status,
+        attempt_number: submission.attempt_number,
+        attempts_used,
+        attempts_remaining: (allowed_submissions as i64 - attempts_used).max(0),
+        metric: task.metric,
+        metric_value: Some(metric_value).filter(|_| !hidden),
+        score: Some(score).filter(|_| !hidden),
+        public_metric_value,
+        public_score,
+        points_awarded: if credit { points } else { 0 },
+        late,
+    }))
+}
@@ -10,22 +10,42 @@ pub mod users;
 pub mod webhooks;
 
 pub use admin::{
+    admin_adjust_user_points, admin_bulk_grade_submissions, admin_bulk_grade_submissions_multipart,
     admin_create_certificate, admin_create_certificate_multipart, admin_create_challenge,
     admin_create_notebook_multipart, admin_create_resource, admin_create_resource_multipart,
-    admin_delete_certificate, admin_delete_challenge, admin_delete_notebook, admin_delete_resource,
-    admin_get_certificate_by_id, admin_get_certificates, admin_get_challenge_by_id,
-    admin_get_challenges, admin_get_notebook_by_challenge, admin_get_notebook_edit_url,
-    admin_get_notebooks, admin_get_resource_by_id, admin_get_resources,
+    admin_create_webhook, admin_delete_certificate, admin_delete_challenge, admin_delete_notebook,
+    admin_delete_resource, admin_delete_webhook, admin_export_challenge_grades,
+    admin_export_submissions, admin_get_audit_log, admin_get_certificate_by_id,
+    admin_get_certificates, admin_get_challenge_by_id, admin_get_challenge_rubric,
+    admin_get_challenges, admin_get_grading_jobs, admin_get_grading_service_health,
+    admin_get_notebook_by_challenge, admin_get_notebook_edit_url, admin_get_notebooks,
+    admin_get_prediction_task, admin_get_resource_by_id, admin_get_resources, admin_get_roles,
     admin_get_submission_access, admin_get_submission_file, admin_get_submissions,
-    admin_grade_submission, admin_patch_certificate_visibility, admin_patch_challenge_visibility,
-    admin_patch_resource_visibility, admin_sync_notebook_to_nbgrader, admin_update_certificate,
-    admin_update_certificate_multipart, admin_update_challenge, admin_update_notebook,
-    admin_update_resource, admin_update_resource_multipart,
+    admin_get_trashed_certificates, admin_get_trashed_challenges, admin_get_trashed_notebooks,
+    admin_get_trashed_resources, admin_get_user_by_id, admin_get_users,
+    admin_get_webhook_deliveries, admin_get_webhooks, admin_grade_submission,
+    admin_patch_certificate_visibility, admin_patch_challenge_visibility,
+    admin_patch_resource_visibility, admin_purge_certificate, admin_purge_challenge,
+    admin_purge_notebook, admin_purge_resource, admin_recompute_challenge_points,
+    admin_recompute_points, admin_reset_user_jupyterhub_username, admin_restore_certificate,
+    admin_restore_challenge, admin_restore_notebook, admin_restore_resource,
+    admin_retry_grading_job, admin_retry_webhook_delivery, admin_suspend_user,
+    admin_sync_notebook_to_nbgrader, admin_unsuspend_user, admin_update_certificate,
+    admin_update_certificate_multipart, admin_update_challenge, admin_update_challenge_rubric,
+    admin_update_notebook, admin_update_resource, admin_update_resource_multipart,
+    admin_update_user_role, admin_update_webhook, admin_upload_prediction_task,
 };
 pub use auth::complete_profile::complete_profile;
+pub use auth::confirm_email_verification::confirm_email_verification;
+pub use auth::confirm_password_reset::confirm_password_reset;
 pub use auth::google_auth_callback::google_auth_callback;
 pub use auth::google_auth_init::google_auth_init;
 pub use auth::login::login;
+pub use auth::logout::logout;
+pub use auth::logout_all::logout_all;
+pub use auth::refresh_token::refresh_token;
+pub use auth::request_email_verification::request_email_verification;
+pub use auth::request_password_reset::request_password_reset;
 pub use auth::signup::signup;
 pub use certificates::get_certificate_by_id::get_certificate_by_id;
 pub use certificates::get_certificates::get_certificates;
@@ -33,15 +53,27 @@ pub use challenges::get_challenge_leaderboard::get_challenge_leaderboard;
 pub use challenges::get_challenge_submission_leaderboard::get_challenge_submission_leaderboard;
 pub use challenges::get_challenges_with_notebooks::get_challenges_with_notebooks;
 pub use challenges::get_current_challenge::get_current_challenge;
+pub use challenges::get_final_submissions::get_final_submissions;
 pub use challenges::get_user_submission::get_user_submission;
+pub use challenges::select_final_submissions::select_final_submissions;
 pub use challenges::start_challenge::start_challenge;
+pub use challenges::stream_challenge_submission_leaderboard::stream_challenge_submission_leaderboard;
+pub use challenges::stream_user_submission::stream_user_submission;
 pub use challenges::submit_challenge::submit_challenge;
+pub use challenges::submit_predictions::submit_predictions;
 pub use create_contact::create_contact;
 pub use get_leaderboards::get_leaderboards;
 pub use health_check::health_check;
 pub use resources::get_resource_by_id::get_resource_by_id;
 pub use resources::get_resources::get_resources;
+pub use users::get_notification_preferences::get_notification_preferences;
+pub use users::get_unread_notification_count::get_unread_notification_count;
+pub use users::get_user_notifications::get_user_notifications;
+pub use users::get_user_point_history::get_user_point_history;
 pub use users::get_user_profile::get_user_profile;
+pub use users::mark_all_notifications_read::mark_all_notifications_read;
+pub use users::mark_notification_read::mark_notification_read;
+pub use users::update_notification_preferences::update_notification_preferences;
 pub use users::update_user_password::update_user_password;
 pub use users::update_user_profile::update_user_profile;
 pub use users::upload_user_avatar::upload_user_avatar;
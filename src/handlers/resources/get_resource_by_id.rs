@@ -9,12 +9,13 @@ pub async fn get_resource_by_id(
     State(state): State<AppState>,
     Path(id): Path<i32>,
 ) -> Result<Json<ResourceDetailResponse>, AppError> {
-    let resource: Resource =
-        sqlx::query_as("SELECT * FROM resources WHERE id = $1 AND visible = true")
-            .bind(id)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or(AppError::NotFound)?;
+    let resource: Resource = sqlx::query_as(
+        "SELECT * FROM resources WHERE id = $1 AND visible = true AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
 
     // Fetch a random quote from the quotes table
     let quote: Option<Quote> =
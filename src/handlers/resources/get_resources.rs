@@ -5,10 +5,11 @@ use crate::{AppState, error::AppError, models::*};
 pub async fn get_resources(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<ResourceListResponse>>, AppError> {
-    let resources: Vec<Resource> =
-        sqlx::query_as("SELECT * FROM resources WHERE visible = true ORDER BY id")
-            .fetch_all(&state.pool)
-            .await?;
+    let resources: Vec<Resource> = sqlx::query_as(
+        "SELECT * FROM resources WHERE visible = true AND deleted_at IS NULL ORDER BY id",
+    )
+    .fetch_all(&state.pool)
+    .await?;
 
     let responses: Vec<ResourceListResponse> = resources
         .into_iter()
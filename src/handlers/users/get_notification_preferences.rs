@@ -0,0 +1,26 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// Which notifications the signed-in user receives; everything until they opt out
+pub async fn get_notification_preferences(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<NotificationPreferences>, AppError> {
+    let preferences: Option<NotificationPreferences> = sqlx::query_as(
+        r#"
+        SELECT submission_graded, challenge_published, certificate_published
+        FROM notification_preferences
+        WHERE user_id = $1
+        "#,
+    )
+    .bind(auth.user_id)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    Ok(Json(preferences.unwrap_or(NotificationPreferences {
+        submission_graded: true,
+        challenge_published: true,
+        certificate_published: true,
+    })))
+}
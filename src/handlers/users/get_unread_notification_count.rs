@@ -0,0 +1,18 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// How many of the signed-in user's notifications are unread, for a badge
+pub async fn get_unread_notification_count(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<UnreadNotificationCountResponse>, AppError> {
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(auth.user_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(UnreadNotificationCountResponse { count }))
+}
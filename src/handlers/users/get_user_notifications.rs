@@ -0,0 +1,51 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    /// Only notifications not yet read
+    unread: Option<bool>,
+    limit: Option<i64>,
+}
+
+/// The signed-in user's notifications, newest first, with their unread count
+pub async fn get_user_notifications(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<NotificationQuery>,
+) -> Result<Json<NotificationListResponse>, AppError> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+
+    let notifications: Vec<Notification> = sqlx::query_as(
+        r#"
+        SELECT *
+        FROM notifications
+        WHERE user_id = $1
+          AND (NOT $2 OR read_at IS NULL)
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(query.unread.unwrap_or(false))
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let unread_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(auth.user_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(NotificationListResponse {
+        notifications,
+        unread_count,
+    }))
+}
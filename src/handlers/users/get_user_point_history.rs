@@ -0,0 +1,46 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// The signed-in user's points ledger, newest first
+pub async fn get_user_point_history(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<PointHistoryResponse>, AppError> {
+    let points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let transactions: Vec<PointTransactionResponse> = sqlx::query_as(
+        r#"
+        SELECT
+            pt.id,
+            pt.amount,
+            pt.reason,
+            CASE
+                WHEN pt.submission_id IS NOT NULL THEN 'submission'
+                WHEN pt.created_by IS NOT NULL THEN 'admin'
+                ELSE 'system'
+            END AS source,
+            pt.submission_id,
+            cs.challenge_id,
+            c.title AS challenge_title,
+            pt.created_at
+        FROM point_transactions pt
+        LEFT JOIN challenge_submissions cs ON cs.id = pt.submission_id
+        LEFT JOIN challenges c ON c.id = cs.challenge_id
+        WHERE pt.user_id = $1
+        ORDER BY pt.created_at DESC, pt.id
+        "#,
+    )
+    .bind(auth.user_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(PointHistoryResponse {
+        points,
+        transactions,
+    }))
+}
@@ -23,11 +23,27 @@ pub async fn get_user_profile(
         .await?
         .ok_or(AppError::NotFound)?;
 
+    let email_verified: bool =
+        sqlx::query_scalar("SELECT email_verified_at IS NOT NULL FROM users WHERE id = $1")
+            .bind(auth.user_id)
+            .fetch_one(&state.pool)
+            .await?;
+
+    let permissions: Vec<String> = sqlx::query_scalar(
+        "SELECT permission FROM role_permissions WHERE role = $1 ORDER BY permission",
+    )
+    .bind(&user.role)
+    .fetch_all(&state.pool)
+    .await?;
+
     Ok(Json(UserProfileResponse {
         rank: user.rank,
         name: user.full_name,
         points: user.points,
         image: user.image,
+        email_verified,
+        role: user.role,
+        permissions,
         stats: UserStatsResponse {
             best_subject: stats.best_subject,
             improveable: stats.improveable,
@@ -0,0 +1,21 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// Mark every unread notification of the signed-in user read
+pub async fn mark_all_notifications_read(
+    auth: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<MarkAllNotificationsReadResponse>, AppError> {
+    let result = sqlx::query(
+        "UPDATE notifications SET read_at = NOW() WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(auth.user_id)
+    .execute(&state.pool)
+    .await?;
+
+    Ok(Json(MarkAllNotificationsReadResponse {
+        success: true,
+        updated: result.rows_affected(),
+    }))
+}
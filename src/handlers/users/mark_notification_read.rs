@@ -0,0 +1,29 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// Mark one of the signed-in user's notifications read; reading it again keeps the first time
+pub async fn mark_notification_read(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<Notification>, AppError> {
+    let notification: Notification = sqlx::query_as(
+        r#"
+        UPDATE notifications
+        SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(auth.user_id)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    Ok(Json(notification))
+}
@@ -2,3 +2,10 @@ pub mod get_user_profile;
 pub mod update_user_profile;
 pub mod upload_user_avatar;
 pub mod update_user_password;
+pub mod get_user_point_history;
+pub mod get_user_notifications;
+pub mod get_unread_notification_count;
+pub mod mark_notification_read;
+pub mod mark_all_notifications_read;
+pub mod get_notification_preferences;
+pub mod update_notification_preferences;
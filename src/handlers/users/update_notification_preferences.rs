@@ -0,0 +1,32 @@
+use axum::{Json, extract::State};
+
+use crate::{AppState, auth::AuthUser, error::AppError, models::*};
+
+/// Switch kinds of notification on or off; omitted kinds keep their current setting
+pub async fn update_notification_preferences(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Json(req): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<NotificationPreferences>, AppError> {
+    let preferences: NotificationPreferences = sqlx::query_as(
+        r#"
+        INSERT INTO notification_preferences
+            (user_id, submission_graded, challenge_published, certificate_published)
+        VALUES ($1, COALESCE($2, TRUE), COALESCE($3, TRUE), COALESCE($4, TRUE))
+        ON CONFLICT (user_id) DO UPDATE
+        SET submission_graded = COALESCE($2, notification_preferences.submission_graded),
+            challenge_published = COALESCE($3, notification_preferences.challenge_published),
+            certificate_published = COALESCE($4, notification_preferences.certificate_published),
+            updated_at = NOW()
+        RETURNING submission_graded, challenge_published, certificate_published
+        "#,
+    )
+    .bind(auth.user_id)
+    .bind(req.submission_graded)
+    .bind(req.challenge_published)
+    .bind(req.certificate_published)
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok(Json(preferences))
+}
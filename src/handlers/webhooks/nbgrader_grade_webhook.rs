@@ -1,27 +1,148 @@
-use axum::{Json, extract::State};
+use axum::{Json, body::Bytes, extract::State, http::HeaderMap};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
-use crate::{AppState, error::AppError, models::*};
+use crate::{
+    AppState,
+    error::AppError,
+    events::{Event, publish_event},
+    grading::points_for_score,
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    late_policy::{ChallengeDeadline, apply_late_penalty},
+    live_updates::{notify_leaderboard_changed, notify_submission_changed},
+    models::*,
+    notifications::{NewNotification, create_notification},
+    points::record_point_transaction,
+    signing::{self, DELIVERY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
 
-/// Webhook endpoint for nbgrader to report grades
+const SOURCE: &str = "nbgrader";
+
+/// Webhook endpoint for nbgrader to report grades.
+/// Deliveries are signed with `NBGRADER_WEBHOOK_SECRET` (see [`signing`]) and each delivery ID
+/// is processed at most once. Without a secret (development only) signatures are not checked.
 pub async fn nbgrader_grade_webhook(
     State(state): State<AppState>,
-    Json(payload): Json<NbgraderWebhookPayload>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<Json<NbgraderWebhookResponse>, AppError> {
-    // Verify webhook secret (allow empty for development)
-    let expected_secret = std::env::var("NBGRADER_WEBHOOK_SECRET").unwrap_or_default();
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let delivery_id = header(DELIVERY_HEADER).map(str::to_string);
+    let raw_body = String::from_utf8_lossy(&body).into_owned();
 
-    // Only verify if secret is configured (non-empty)
-    if !expected_secret.is_empty() && payload.webhook_secret != expected_secret {
-        return Err(AppError::AuthError);
-    }
+    let signature_valid = match &state.config.nbgrader_webhook_secret {
+        Some(secret) => {
+            let verified = signing::verify(
+                secret,
+                header(SIGNATURE_HEADER),
+                header(TIMESTAMP_HEADER),
+                delivery_id.as_deref(),
+                &body,
+                OffsetDateTime::now_utc().unix_timestamp(),
+            )
+            .map_err(|e| e.to_string());
+
+            if let Err(error) = verified {
+                tracing::warn!("Rejected nbgrader webhook delivery: {}", error);
+                record_rejected_delivery(&state.pool, delivery_id.as_deref(), &error, &raw_body)
+                    .await?;
+                return Err(AppError::AuthError);
+            }
+
+            true
+        }
+        None => false,
+    };
+
+    // Claim the delivery ID; one that is already being or has been processed is a replay
+    let log_id: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        INSERT INTO webhook_deliveries (source, delivery_id, status, signature_valid, body)
+        VALUES ($1, $2, 'processing', $3, $4)
+        ON CONFLICT (source, delivery_id) WHERE status IN ('processing', 'processed')
+        DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(SOURCE)
+    .bind(&delivery_id)
+    .bind(signature_valid)
+    .bind(&raw_body)
+    .fetch_optional(&state.pool)
+    .await?;
+
+    let Some(log_id) = log_id else {
+        record_rejected_delivery(
+            &state.pool,
+            delivery_id.as_deref(),
+            "Replayed delivery",
+            &raw_body,
+        )
+        .await?;
+        return Err(AppError::Conflict(
+            "This delivery has already been received".to_string(),
+        ));
+    };
+
+    let result = match serde_json::from_slice::<NbgraderWebhookPayload>(&body) {
+        Ok(payload) => apply_grade(&state, payload).await,
+        Err(e) => Err(AppError::BadRequest(format!(
+            "Invalid webhook payload: {e}"
+        ))),
+    };
+
+    // A failed delivery releases its ID so the sender can retry it
+    let (status, error) = match &result {
+        Ok(_) => ("processed", None),
+        Err(e) => ("failed", Some(format!("{e:?}"))),
+    };
+    sqlx::query(
+        "UPDATE webhook_deliveries SET status = $2, error = $3, completed_at = NOW() WHERE id = $1",
+    )
+    .bind(log_id)
+    .bind(status)
+    .bind(error)
+    .execute(&state.pool)
+    .await?;
+
+    result.map(Json)
+}
 
+async fn record_rejected_delivery(
+    pool: &PgPool,
+    delivery_id: Option<&str>,
+    error: &str,
+    body: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (source, delivery_id, status, error, body, completed_at)
+        VALUES ($1, $2, 'rejected', $3, $4, NOW())
+        "#,
+    )
+    .bind(SOURCE)
+    .bind(delivery_id)
+    .bind(error)
+    .bind(body)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn apply_grade(
+    state: &AppState,
+    payload: NbgraderWebhookPayload,
+) -> Result<NbgraderWebhookResponse, AppError> {
     // Find the notebook by assignment name
-    let notebook: ChallengeNotebook =
-        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE assignment_name = $1")
-            .bind(&payload.assignment_name)
-            .fetch_optional(&state.pool)
-            .await?
-            .ok_or_else(|| AppError::NotFound)?;
+    let notebook: ChallengeNotebook = sqlx::query_as(
+        "SELECT * FROM challenge_notebooks WHERE assignment_name = $1 AND deleted_at IS NULL",
+    )
+    .bind(&payload.assignment_name)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound)?;
 
     // Find the user by jupyterhub username
     let user: User = sqlx::query_as("SELECT * FROM users WHERE jupyterhub_username = $1")
@@ -30,42 +151,151 @@ pub async fn nbgrader_grade_webhook(
         .await?
         .ok_or_else(|| AppError::NotFound)?;
 
-    // Update latest in-progress or pending submission metadata; keep it pending for manual grading
-    let _submission: ChallengeSubmission = sqlx::query_as(
+    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
+        .bind(notebook.challenge_id)
+        .fetch_one(&state.pool)
+        .await?;
+    let grading_mode = challenge.parsed_grading_mode()?;
+
+    let mut tx = state.pool.begin().await?;
+
+    #[derive(sqlx::FromRow)]
+    struct WebhookTarget {
+        id: uuid::Uuid,
+        attempt_number: i32,
+        points_awarded: i32,
+        points_credited: bool,
+        submitted_at: Option<OffsetDateTime>,
+    }
+
+    // Lock the latest in-progress or pending attempt so a concurrent manual grade
+    // or a retried delivery cannot interleave with this update
+    let target: WebhookTarget = sqlx::query_as(
+        r#"
+        SELECT id, attempt_number, points_awarded, points_credited, submitted_at
+        FROM challenge_submissions
+        WHERE user_id = $1 AND challenge_id = $2
+          AND status IN ('in_progress', 'grading_pending')
+        ORDER BY attempt_number DESC
+        LIMIT 1
+        FOR UPDATE
+        "#,
+    )
+    .bind(user.id)
+    .bind(notebook.challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound)?;
+
+    // Attempts still in progress are submitted by this grade
+    let submitted_at = target.submitted_at.unwrap_or_else(OffsetDateTime::now_utc);
+    let late = ChallengeDeadline::from(&challenge).assess(submitted_at);
+    let points = apply_late_penalty(
+        points_for_score(payload.score, payload.max_score, notebook.max_points),
+        late.as_ref(),
+    );
+
+    // Manual challenges only keep the nbgrader score; auto_with_review also proposes the
+    // points for the reviewing admin, and auto credits them straight away
+    let (status, proposed_points, credit) = match grading_mode {
+        GradingMode::Manual => ("grading_pending", None, false),
+        GradingMode::AutoWithReview => ("grading_pending", Some(points), false),
+        GradingMode::Auto => ("graded", Some(points), true),
+    };
+
+    let submission: ChallengeSubmission = sqlx::query_as(
         r#"
         UPDATE challenge_submissions
-        SET status = 'grading_pending',
-            score = $1, 
-            max_score = $2, 
+        SET status = $1,
+            score = $2,
+            max_score = $3,
             nbgrader_submission_id = $4,
-            submitted_at = COALESCE(submitted_at, NOW()),
+            points_awarded = COALESCE($5, points_awarded),
+            points_credited = points_credited OR $6,
+            graded_at = CASE WHEN $6 THEN NOW() ELSE graded_at END,
+            submitted_at = $8,
+            late_minutes = $9,
+            late_penalty_percent = $10,
+            late_practice_only = $11,
             updated_at = NOW()
-        WHERE id = (
-            SELECT id
-            FROM challenge_submissions
-            WHERE user_id = $5 AND challenge_id = $6
-              AND status IN ('in_progress', 'grading_pending')
-            ORDER BY attempt_number DESC
-            LIMIT 1
-        )
+        WHERE id = $7
         RETURNING *
         "#,
     )
+    .bind(status)
     .bind(payload.score)
     .bind(payload.max_score)
     .bind(&payload.submission_id)
-    .bind(user.id)
-    .bind(notebook.challenge_id)
-    .fetch_optional(&state.pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound)?;
+    .bind(proposed_points)
+    .bind(credit)
+    .bind(target.id)
+    .bind(submitted_at)
+    .bind(late.as_ref().map(|l| l.minutes_late))
+    .bind(late.as_ref().map_or(0.0, |l| l.penalty_percent))
+    .bind(late.as_ref().is_some_and(|l| l.practice_only))
+    .fetch_one(&mut *tx)
+    .await?;
 
-    Ok(Json(NbgraderWebhookResponse {
-        success: true,
-        points_awarded: 0,
-        message: format!(
+    let points_awarded = if credit {
+        let delta_points = if target.points_credited {
+            points - target.points_awarded
+        } else {
+            points
+        };
+        let reason = format!(
+            "Auto-graded: {} (attempt {})",
+            challenge.title, target.attempt_number
+        );
+
+        record_point_transaction(
+            &mut tx,
+            user.id,
+            delta_points,
+            &reason,
+            Some(target.id),
+            None,
+        )
+        .await?;
+
+        publish_event(&mut tx, &Event::submission_graded(&submission)).await?;
+        create_notification(
+            &mut tx,
+            &NewNotification::submission_graded(&submission, &challenge.title),
+        )
+        .await?;
+        notify_leaderboard_changed(&mut tx, submission.challenge_id).await?;
+
+        points
+    } else {
+        0
+    };
+
+    notify_submission_changed(&mut tx, &submission).await?;
+
+    tx.commit().await?;
+
+    if credit {
+        update_user_ranks(&state.pool).await?;
+    }
+
+    let message = match grading_mode {
+        GradingMode::Manual => format!(
             "Submission received for {} and marked as grading_pending",
             notebook.assignment_name
         ),
-    }))
+        GradingMode::AutoWithReview => format!(
+            "Submission received for {}; {} points proposed pending review",
+            notebook.assignment_name, points
+        ),
+        GradingMode::Auto => format!(
+            "Submission for {} graded automatically: {} points awarded",
+            notebook.assignment_name, points
+        ),
+    };
+
+    Ok(NbgraderWebhookResponse {
+        success: true,
+        points_awarded,
+        message,
+    })
 }
@@ -0,0 +1,186 @@
+use time::{Duration, OffsetDateTime};
+
+use crate::{
+    error::AppError,
+    models::{Challenge, LatePenalty, LatePolicySettings},
+};
+
+/// What happens to work submitted after a challenge's end date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatePolicy {
+    /// Nothing is accepted after the end date
+    Cutoff,
+    /// Late work is accepted at full credit during the grace window, then not at all
+    Grace,
+    /// The penalty grows pro rata with every minute past the grace window
+    Linear,
+    /// The penalty grows by the full percentage for every started period past the grace window
+    Stepped,
+    /// Late work is graded but earns no points
+    PracticeOnly,
+}
+
+impl LatePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatePolicy::Cutoff => "cutoff",
+            LatePolicy::Grace => "grace",
+            LatePolicy::Linear => "linear",
+            LatePolicy::Stepped => "stepped",
+            LatePolicy::PracticeOnly => "practice_only",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "cutoff" => Some(LatePolicy::Cutoff),
+            "grace" => Some(LatePolicy::Grace),
+            "linear" => Some(LatePolicy::Linear),
+            "stepped" => Some(LatePolicy::Stepped),
+            "practice_only" => Some(LatePolicy::PracticeOnly),
+            _ => None,
+        }
+    }
+}
+
+/// The unit a late penalty percentage is charged per
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyPeriod {
+    Hour,
+    Day,
+}
+
+impl PenaltyPeriod {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "hour" => Some(PenaltyPeriod::Hour),
+            "day" => Some(PenaltyPeriod::Day),
+            _ => None,
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        match self {
+            PenaltyPeriod::Hour => Duration::HOUR,
+            PenaltyPeriod::Day => Duration::DAY,
+        }
+    }
+}
+
+/// A challenge's end date and late policy, selectable alongside a submission
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChallengeDeadline {
+    pub end_date: Option<OffsetDateTime>,
+    pub late_policy: String,
+    pub late_grace_minutes: i32,
+    pub late_penalty_percent: f64,
+    pub late_penalty_period: String,
+}
+
+impl From<&Challenge> for ChallengeDeadline {
+    fn from(challenge: &Challenge) -> Self {
+        ChallengeDeadline {
+            end_date: challenge.end_date,
+            late_policy: challenge.late_policy.clone(),
+            late_grace_minutes: challenge.late_grace_minutes,
+            late_penalty_percent: challenge.late_penalty_percent,
+            late_penalty_period: challenge.late_penalty_period.clone(),
+        }
+    }
+}
+
+impl ChallengeDeadline {
+    fn policy(&self) -> LatePolicy {
+        LatePolicy::parse(&self.late_policy).unwrap_or(LatePolicy::Cutoff)
+    }
+
+    fn grace(&self) -> Duration {
+        Duration::minutes(self.late_grace_minutes.max(0) as i64)
+    }
+
+    /// When the challenge stops accepting attempts; `None` while late work is always accepted
+    pub fn closes_at(&self) -> Option<OffsetDateTime> {
+        let end_date = self.end_date?;
+
+        match self.policy() {
+            LatePolicy::Cutoff => Some(end_date),
+            LatePolicy::Grace => Some(end_date + self.grace()),
+            LatePolicy::Linear | LatePolicy::Stepped | LatePolicy::PracticeOnly => None,
+        }
+    }
+
+    /// Whether attempts can no longer be started or submitted at `at`
+    pub fn is_closed(&self, at: OffsetDateTime) -> bool {
+        self.closes_at().is_some_and(|closes_at| at > closes_at)
+    }
+
+    /// The penalty for work submitted at `submitted_at`; `None` when it was on time
+    pub fn assess(&self, submitted_at: OffsetDateTime) -> Option<LatePenalty> {
+        let end_date = self.end_date?;
+        if submitted_at <= end_date {
+            return None;
+        }
+
+        let late = submitted_at - end_date;
+        // A submission a few seconds late counts as a minute late
+        let minutes_late = ((late.whole_seconds() + 59) / 60).min(i32::MAX as i64) as i32;
+        let penalised = late - self.grace();
+        let period = PenaltyPeriod::parse(&self.late_penalty_period)
+            .unwrap_or(PenaltyPeriod::Day)
+            .duration();
+
+        let (penalty_percent, practice_only) = match self.policy() {
+            _ if !penalised.is_positive() => (0.0, false),
+            LatePolicy::Cutoff | LatePolicy::Grace => (0.0, false),
+            LatePolicy::Linear => (self.late_penalty_percent * (penalised / period), false),
+            LatePolicy::Stepped => (
+                self.late_penalty_percent * (penalised / period).ceil(),
+                false,
+            ),
+            LatePolicy::PracticeOnly => (100.0, true),
+        };
+
+        Some(LatePenalty {
+            minutes_late,
+            penalty_percent: (penalty_percent.clamp(0.0, 100.0) * 100.0).round() / 100.0,
+            practice_only,
+        })
+    }
+}
+
+/// `points` less the late penalty, if any
+pub fn apply_late_penalty(points: i32, late: Option<&LatePenalty>) -> i32 {
+    match late {
+        Some(late) => (points as f64 * (1.0 - late.penalty_percent / 100.0)).round() as i32,
+        None => points,
+    }
+}
+
+/// Check a late policy sent for a challenge
+pub fn validate_late_policy(settings: &LatePolicySettings) -> Result<(), AppError> {
+    let invalid = |message: &str| Err(AppError::ValidationError(message.to_string()));
+
+    let Some(policy) = LatePolicy::parse(&settings.policy) else {
+        return invalid(
+            "latePolicy.policy must be one of cutoff, grace, linear, stepped, practice_only",
+        );
+    };
+    if settings.grace_minutes < 0 {
+        return invalid("latePolicy.graceMinutes cannot be negative");
+    }
+    if policy == LatePolicy::Grace && settings.grace_minutes == 0 {
+        return invalid("latePolicy.graceMinutes must be set for the grace policy");
+    }
+    if !(0.0..=100.0).contains(&settings.penalty_percent) {
+        return invalid("latePolicy.penaltyPercent must be between 0 and 100");
+    }
+    if matches!(policy, LatePolicy::Linear | LatePolicy::Stepped) && settings.penalty_percent <= 0.0
+    {
+        return invalid("latePolicy.penaltyPercent must be set for linear and stepped policies");
+    }
+    if PenaltyPeriod::parse(&settings.penalty_period).is_none() {
+        return invalid("latePolicy.penaltyPeriod must be hour or day");
+    }
+
+    Ok(())
+}
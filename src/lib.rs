@@ -1,12 +1,33 @@
+pub mod attempts;
+pub mod audit;
 pub mod auth;
+pub mod config;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod grading;
+pub mod grading_service;
 #[path = "handlers/mod.rs"]
 pub mod handlers;
+pub mod late_policy;
+pub mod live_updates;
+pub mod mailer;
 pub mod models;
+pub mod notifications;
+pub mod outbound_webhooks;
+pub mod pagination;
+pub mod points;
+pub mod prediction;
+pub mod prediction_leaderboard;
+pub mod rubric;
+pub mod signing;
+pub mod trash;
 
 use axum::{
     Router,
     extract::FromRef,
+    http::HeaderName,
+    middleware,
     routing::{delete, get, patch, post, put},
 };
 use std::sync::Arc;
@@ -20,12 +41,16 @@ pub struct OAuthConfig {
     pub redirect_uri: String,
     pub auth_url: String,
     pub token_url: String,
+    pub userinfo_url: String,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: sqlx::PgPool,
-    pub oauth_config: Arc<OAuthConfig>,
+    pub config: Arc<config::Config>,
+    pub mailer: Arc<dyn mailer::Mailer>,
+    pub grading: Arc<dyn grading_service::GradingServiceClient>,
+    pub live_updates: live_updates::LiveUpdates,
 }
 
 // Implement FromRef to allow extracting PgPool from AppState
@@ -35,29 +60,46 @@ impl FromRef<AppState> for sqlx::PgPool {
     }
 }
 
-pub fn create_app(pool: sqlx::PgPool) -> Router {
-    let google_client_id = std::env::var("GOOGLE_CLIENT_ID").expect("GOOGLE_CLIENT_ID must be set");
-    let google_client_secret =
-        std::env::var("GOOGLE_CLIENT_SECRET").expect("GOOGLE_CLIENT_SECRET must be set");
-    let google_redirect_uri =
-        std::env::var("GOOGLE_REDIRECT_URI").expect("GOOGLE_REDIRECT_URI must be set");
+pub fn create_app(pool: sqlx::PgPool, config: config::Config) -> Router {
+    auth::init_jwt_secret(&config.jwt_secret);
 
-    let oauth_config = Arc::new(OAuthConfig {
-        client_id: google_client_id,
-        client_secret: google_client_secret,
-        redirect_uri: google_redirect_uri,
-        auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
-        token_url: "https://oauth2.googleapis.com/token".to_string(),
-    });
+    let grading = grading_service::grading_service_from_config(&config.grading_service_url);
+
+    attempts::spawn_attempt_sweeper(pool.clone(), config.attempts.clone());
+    grading::spawn_grading_worker(
+        pool.clone(),
+        grading.clone(),
+        config.grading_job_poll_interval_seconds,
+    );
+    outbound_webhooks::spawn_webhook_worker(
+        pool.clone(),
+        config.webhook_delivery_poll_interval_seconds,
+    );
+    prediction_leaderboard::spawn_leaderboard_revealer(
+        pool.clone(),
+        config.leaderboard_reveal_interval_seconds,
+    );
+
+    let live_updates = live_updates::LiveUpdates::new();
+    live_updates::spawn_live_update_listener(pool.clone(), live_updates.clone());
 
     let app_state = AppState {
-        pool: pool.clone(),
-        oauth_config,
+        pool,
+        mailer: mailer::mailer_from_config(&config.mail),
+        grading,
+        live_updates,
+        config: Arc::new(config),
     };
+
+    create_router(app_state)
+}
+
+pub fn create_router(app_state: AppState) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([HeaderName::from_static(audit::REQUEST_ID_HEADER)]);
 
     Router::new()
         // Health
@@ -65,9 +107,28 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
         // Auth
         // .route("/auth/signup", post(handlers::signup))
         .route("/auth/login", post(handlers::login))
+        .route("/auth/refresh", post(handlers::refresh_token))
+        .route("/auth/logout", post(handlers::logout))
+        .route("/auth/logout-all", post(handlers::logout_all))
         .route("/auth/google", get(handlers::google_auth_init))
         .route("/auth/google/callback", get(handlers::google_auth_callback))
         .route("/auth/complete-profile", post(handlers::complete_profile))
+        .route(
+            "/auth/password-reset/request",
+            post(handlers::request_password_reset),
+        )
+        .route(
+            "/auth/password-reset/confirm",
+            post(handlers::confirm_password_reset),
+        )
+        .route(
+            "/auth/verify-email/request",
+            post(handlers::request_email_verification),
+        )
+        .route(
+            "/auth/verify-email/confirm",
+            post(handlers::confirm_email_verification),
+        )
         // Public content
         .route("/leaderboards", get(handlers::get_leaderboards))
         .route("/resources", get(handlers::get_resources))
@@ -86,12 +147,28 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/challenges/:id/leaderboard",
             get(handlers::get_challenge_submission_leaderboard),
         )
+        .route(
+            "/challenges/:id/leaderboard/stream",
+            get(handlers::stream_challenge_submission_leaderboard),
+        )
         .route(
             "/challenges/:id/submission",
             get(handlers::get_user_submission),
         )
+        .route(
+            "/challenges/:id/submission/stream",
+            get(handlers::stream_user_submission),
+        )
         .route("/challenges/:id/start", post(handlers::start_challenge))
         .route("/challenges/:id/submit", post(handlers::submit_challenge))
+        .route(
+            "/challenges/:id/predictions",
+            post(handlers::submit_predictions),
+        )
+        .route(
+            "/challenges/:id/final-submissions",
+            get(handlers::get_final_submissions).put(handlers::select_final_submissions),
+        )
         // Users
         .route(
             "/users/profile",
@@ -99,6 +176,28 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
         )
         .route("/users/avatar", post(handlers::upload_user_avatar))
         .route("/users/password", put(handlers::update_user_password))
+        .route(
+            "/users/points/history",
+            get(handlers::get_user_point_history),
+        )
+        .route("/users/notifications", get(handlers::get_user_notifications))
+        .route(
+            "/users/notifications/unread-count",
+            get(handlers::get_unread_notification_count),
+        )
+        .route(
+            "/users/notifications/read-all",
+            post(handlers::mark_all_notifications_read),
+        )
+        .route(
+            "/users/notifications/preferences",
+            get(handlers::get_notification_preferences)
+                .put(handlers::update_notification_preferences),
+        )
+        .route(
+            "/users/notifications/:id/read",
+            post(handlers::mark_notification_read),
+        )
         // Webhooks
         .route(
             "/webhooks/nbgrader/grade",
@@ -126,6 +225,18 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/resources/:id/visibility",
             patch(handlers::admin_patch_resource_visibility),
         )
+        .route(
+            "/admin/resources/trash",
+            get(handlers::admin_get_trashed_resources),
+        )
+        .route(
+            "/admin/resources/:id/restore",
+            post(handlers::admin_restore_resource),
+        )
+        .route(
+            "/admin/resources/:id/purge",
+            delete(handlers::admin_purge_resource),
+        )
         // Admin: certificates
         .route("/admin/certificates", get(handlers::admin_get_certificates))
         .route(
@@ -148,6 +259,18 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/certificates/:id/visibility",
             patch(handlers::admin_patch_certificate_visibility),
         )
+        .route(
+            "/admin/certificates/trash",
+            get(handlers::admin_get_trashed_certificates),
+        )
+        .route(
+            "/admin/certificates/:id/restore",
+            post(handlers::admin_restore_certificate),
+        )
+        .route(
+            "/admin/certificates/:id/purge",
+            delete(handlers::admin_purge_certificate),
+        )
         // Admin: challenges
         .route("/admin/challenges", get(handlers::admin_get_challenges))
         .route("/admin/challenges", post(handlers::admin_create_challenge))
@@ -171,6 +294,38 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/challenges/:id/notebook",
             get(handlers::admin_get_notebook_by_challenge),
         )
+        .route(
+            "/admin/challenges/:id/rubric",
+            get(handlers::admin_get_challenge_rubric).put(handlers::admin_update_challenge_rubric),
+        )
+        .route(
+            "/admin/challenges/:id/prediction",
+            get(handlers::admin_get_prediction_task).put(handlers::admin_upload_prediction_task),
+        )
+        .route(
+            "/admin/challenges/:id/grades",
+            post(handlers::admin_bulk_grade_submissions),
+        )
+        .route(
+            "/admin/challenges/:id/grades/upload",
+            post(handlers::admin_bulk_grade_submissions_multipart),
+        )
+        .route(
+            "/admin/challenges/:id/grades/export",
+            get(handlers::admin_export_challenge_grades),
+        )
+        .route(
+            "/admin/challenges/trash",
+            get(handlers::admin_get_trashed_challenges),
+        )
+        .route(
+            "/admin/challenges/:id/restore",
+            post(handlers::admin_restore_challenge),
+        )
+        .route(
+            "/admin/challenges/:id/purge",
+            delete(handlers::admin_purge_challenge),
+        )
         // Admin: notebooks
         .route("/admin/notebooks", get(handlers::admin_get_notebooks))
         .route(
@@ -190,8 +345,24 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/notebooks/:id/sync",
             post(handlers::admin_sync_notebook_to_nbgrader),
         )
+        .route(
+            "/admin/notebooks/trash",
+            get(handlers::admin_get_trashed_notebooks),
+        )
+        .route(
+            "/admin/notebooks/:id/restore",
+            post(handlers::admin_restore_notebook),
+        )
+        .route(
+            "/admin/notebooks/:id/purge",
+            delete(handlers::admin_purge_notebook),
+        )
         // Admin: submissions
         .route("/admin/submissions", get(handlers::admin_get_submissions))
+        .route(
+            "/admin/submissions/export",
+            get(handlers::admin_export_submissions),
+        )
         .route(
             "/admin/submissions/:id/access",
             get(handlers::admin_get_submission_access),
@@ -204,8 +375,72 @@ pub fn create_app(pool: sqlx::PgPool) -> Router {
             "/admin/submissions/:id/grade",
             post(handlers::admin_grade_submission),
         )
+        // Admin: grading jobs
+        .route("/admin/grading-jobs", get(handlers::admin_get_grading_jobs))
+        .route(
+            "/admin/grading-jobs/:id/retry",
+            post(handlers::admin_retry_grading_job),
+        )
+        .route(
+            "/admin/grading-service/health",
+            get(handlers::admin_get_grading_service_health),
+        )
+        // Admin: outbound webhooks
+        .route(
+            "/admin/webhooks",
+            get(handlers::admin_get_webhooks).post(handlers::admin_create_webhook),
+        )
+        .route(
+            "/admin/webhooks/:id",
+            put(handlers::admin_update_webhook).delete(handlers::admin_delete_webhook),
+        )
+        .route(
+            "/admin/webhooks/:id/deliveries",
+            get(handlers::admin_get_webhook_deliveries),
+        )
+        .route(
+            "/admin/webhook-deliveries/:id/retry",
+            post(handlers::admin_retry_webhook_delivery),
+        )
+        // Admin: roles
+        .route("/admin/roles", get(handlers::admin_get_roles))
+        .route(
+            "/admin/users/:id/role",
+            put(handlers::admin_update_user_role),
+        )
+        // Admin: users
+        .route("/admin/users", get(handlers::admin_get_users))
+        .route("/admin/users/:id", get(handlers::admin_get_user_by_id))
+        .route(
+            "/admin/users/:id/suspend",
+            post(handlers::admin_suspend_user),
+        )
+        .route(
+            "/admin/users/:id/unsuspend",
+            post(handlers::admin_unsuspend_user),
+        )
+        .route(
+            "/admin/users/:id/jupyterhub-username/reset",
+            post(handlers::admin_reset_user_jupyterhub_username),
+        )
+        .route(
+            "/admin/users/:id/points",
+            post(handlers::admin_adjust_user_points),
+        )
+        // Admin: points
+        .route(
+            "/admin/points/recompute",
+            post(handlers::admin_recompute_points),
+        )
+        .route(
+            "/admin/challenges/:id/points/recompute",
+            post(handlers::admin_recompute_challenge_points),
+        )
+        // Admin: audit log
+        .route("/admin/audit-log", get(handlers::admin_get_audit_log))
         // Static
         .nest_service("/uploads", ServeDir::new("uploads"))
+        .layer(middleware::from_fn(audit::assign_request_id))
         .layer(cors)
         .with_state(app_state)
 }
@@ -0,0 +1,179 @@
+use std::{future::Future, time::Duration};
+
+use axum::response::sse::Event;
+use futures_util::{Stream, StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, postgres::PgListener};
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{error::AppError, models::ChallengeSubmission};
+
+/// Postgres channel the backend instances notify each other on
+pub const LIVE_UPDATES_CHANNEL: &str = "live_updates";
+
+/// Updates buffered per subscriber; a subscriber that falls further behind reloads its snapshot
+const SUBSCRIBER_BUFFER: usize = 256;
+const RECONNECT_DELAY_SECONDS: u64 = 5;
+
+/// Something changed that open streams may need to reload. Only identifiers are sent: each
+/// stream reloads what it shows, so a dropped or duplicated notification cannot leave it wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LiveUpdate {
+    Submission {
+        submission_id: Uuid,
+        user_id: Uuid,
+        challenge_id: i32,
+    },
+    Leaderboard {
+        challenge_id: i32,
+    },
+}
+
+/// Fans out updates received from Postgres to the streams open on this instance
+#[derive(Clone)]
+pub struct LiveUpdates {
+    sender: broadcast::Sender<LiveUpdate>,
+}
+
+impl Default for LiveUpdates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiveUpdates {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBER_BUFFER);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Hand an update to this instance's streams; there may be none listening
+    pub fn publish(&self, update: LiveUpdate) {
+        let _ = self.sender.send(update);
+    }
+}
+
+/// Tell every instance about `update`. Postgres delivers it when the surrounding
+/// transaction commits, and drops it if the transaction rolls back.
+async fn notify(conn: &mut PgConnection, update: &LiveUpdate) -> Result<(), AppError> {
+    let payload = serde_json::to_string(update).map_err(anyhow::Error::from)?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(LIVE_UPDATES_CHANNEL)
+        .bind(payload)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// The status, score, or points of a submission changed
+pub async fn notify_submission_changed(
+    conn: &mut PgConnection,
+    submission: &ChallengeSubmission,
+) -> Result<(), AppError> {
+    notify(
+        conn,
+        &LiveUpdate::Submission {
+            submission_id: submission.id,
+            user_id: submission.user_id,
+            challenge_id: submission.challenge_id,
+        },
+    )
+    .await
+}
+
+/// Credited points changed for a challenge, so its leaderboard may have moved
+pub async fn notify_leaderboard_changed(
+    conn: &mut PgConnection,
+    challenge_id: i32,
+) -> Result<(), AppError> {
+    notify(conn, &LiveUpdate::Leaderboard { challenge_id }).await
+}
+
+/// Relay notifications from Postgres to this instance's streams, reconnecting if the
+/// listening connection drops
+pub fn spawn_live_update_listener(pool: PgPool, live_updates: LiveUpdates) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = relay_live_updates(&pool, &live_updates).await {
+                tracing::error!("Live update listener failed: {:?}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(RECONNECT_DELAY_SECONDS)).await;
+        }
+    });
+}
+
+async fn relay_live_updates(pool: &PgPool, live_updates: &LiveUpdates) -> Result<(), AppError> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(LIVE_UPDATES_CHANNEL).await?;
+
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str(notification.payload()) {
+            Ok(update) => live_updates.publish(update),
+            Err(e) => tracing::warn!(
+                "Ignoring malformed live update {:?}: {}",
+                notification.payload(),
+                e
+            ),
+        }
+    }
+}
+
+fn snapshot_event<T: Serialize>(event_name: &str, snapshot: &T) -> Result<Event, axum::Error> {
+    Event::default().event(event_name).json_data(snapshot)
+}
+
+/// Server-sent events carrying `initial` and then a freshly loaded snapshot each time an
+/// update matching `is_relevant` arrives. Subscribe to `updates` before loading `initial`, so
+/// nothing in between is missed. The stream ends if a reload fails; clients reconnect.
+pub fn snapshot_stream<T, R, F, Fut>(
+    updates: broadcast::Receiver<LiveUpdate>,
+    event_name: &'static str,
+    initial: T,
+    is_relevant: R,
+    load: F,
+) -> impl Stream<Item = Result<Event, axum::Error>> + Send + 'static
+where
+    T: Serialize + Send + 'static,
+    R: Fn(&LiveUpdate) -> bool + Send + 'static,
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, AppError>> + Send,
+{
+    let first = snapshot_event(event_name, &initial);
+
+    let reloads = stream::unfold(
+        (updates, is_relevant, load),
+        move |(mut updates, is_relevant, load)| async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) if is_relevant(&update) => break,
+                    Ok(_) => continue,
+                    // Missed updates may have been relevant
+                    Err(RecvError::Lagged(_)) => break,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+
+            match load().await {
+                Ok(snapshot) => Some((
+                    snapshot_event(event_name, &snapshot),
+                    (updates, is_relevant, load),
+                )),
+                Err(e) => {
+                    tracing::error!("Failed to reload {} for live stream: {:?}", event_name, e);
+                    None
+                }
+            }
+        },
+    );
+
+    stream::once(async move { first }).chain(reloads)
+}
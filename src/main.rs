@@ -1,6 +1,8 @@
 use sqlx::postgres::PgPoolOptions;
-use std::net::SocketAddr;
-use uj_ai_club_backend::create_app;
+use std::path::PathBuf;
+use uj_ai_club_backend::{config::Config, create_app};
+
+const USAGE: &str = "Usage: uj-ai-club-backend [--config <path>] [--check-config]";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -8,30 +10,50 @@ async fn main() -> anyhow::Result<()> {
 
     tracing_subscriber::fmt::init();
 
-    let database_url = std::env::var("DATABASE_URL").unwrap_or_else(|_| {
-        let pg_user = std::env::var("POSTGRES_USER").unwrap_or_else(|_| "uj_ai_club".to_string());
-        let pg_pass = std::env::var("POSTGRES_PASSWORD").unwrap();
-        let pg_db = std::env::var("POSTGRES_DB").unwrap_or_else(|_| "uj_ai_club".to_string());
-        let pg_host = std::env::var("POSTGRES_HOST").unwrap_or_else(|_| "postgres".to_string());
-
-        format!("postgres://{pg_user}:{pg_pass}@{pg_host}:5432/{pg_db}")
-    });
-    let server_addr =
-        std::env::var("SERVER_ADDRESS").unwrap_or_else(|_| "0.0.0.0:8000".to_string());
+    let mut config_path: Option<PathBuf> = None;
+    let mut check_config = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check-config" => check_config = true,
+            "--config" => match args.next() {
+                Some(path) => config_path = Some(PathBuf::from(path)),
+                None => anyhow::bail!("--config needs a path\n{USAGE}"),
+            },
+            _ => anyhow::bail!("Unknown argument '{arg}'\n{USAGE}"),
+        }
+    }
+
+    let config = match Config::load(config_path.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if check_config {
+        println!("{}", config.redacted());
+        return Ok(());
+    }
 
     let pool = PgPoolOptions::new()
         .max_connections(5)
-        .connect(&database_url)
+        .connect(&config.database_url)
         .await?;
 
-    let app = create_app(pool);
-
-    let addr: SocketAddr = server_addr.parse()?;
+    let addr = config.server_address;
+    let app = create_app(pool, config);
 
-    tracing::info!("Starting server on {}", addr); // test push
+    tracing::info!("Starting server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Peer addresses are recorded in the audit log when there is no proxy in front
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -2,8 +2,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::error::AppError;
+
 // Custom deserializer for date strings to OffsetDateTime
-mod date_format {
+pub(crate) mod date_format {
     use serde::{self, Deserialize, Deserializer};
     use time::{Date, OffsetDateTime, Time, UtcOffset};
 
@@ -81,6 +83,35 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetConfirmRequest {
+    pub token: String,
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PasswordResetResponse {
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailVerificationConfirmRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailVerificationResponse {
+    pub success: bool,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginRequest {
     pub email: String,
@@ -90,9 +121,29 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshTokenResponse {
+    pub token: String,
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogoutResponse {
+    pub success: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -241,10 +292,144 @@ pub struct Challenge {
     pub start_date: Option<time::OffsetDateTime>,
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: bool,
+    pub grading_mode: String,
+    pub challenge_type: String,
+    pub late_policy: String,
+    pub late_grace_minutes: i32,
+    pub late_penalty_percent: f64,
+    pub late_penalty_period: String,
     pub created_at: time::OffsetDateTime,
     pub updated_at: time::OffsetDateTime,
 }
 
+impl Challenge {
+    /// The stored grading mode; the column's check constraint only allows known values, so
+    /// anything else is a server error rather than a reason to fall back to manual grading
+    pub fn parsed_grading_mode(&self) -> Result<GradingMode, AppError> {
+        GradingMode::parse(&self.grading_mode).ok_or_else(|| {
+            AppError::InternalError(anyhow::anyhow!(
+                "Challenge {} has unknown grading mode {}",
+                self.id,
+                self.grading_mode
+            ))
+        })
+    }
+
+    pub fn late_policy_settings(&self) -> LatePolicySettings {
+        LatePolicySettings {
+            policy: self.late_policy.clone(),
+            grace_minutes: self.late_grace_minutes,
+            penalty_percent: self.late_penalty_percent,
+            penalty_period: self.late_penalty_period.clone(),
+        }
+    }
+}
+
+/// What happens to work submitted after a challenge's end date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatePolicySettings {
+    /// `cutoff`, `grace`, `linear`, `stepped` or `practice_only`
+    pub policy: String,
+    /// Minutes after the end date during which late work gets full credit
+    #[serde(rename = "graceMinutes", default)]
+    pub grace_minutes: i32,
+    /// Percentage of the points taken off per `penaltyPeriod` late (`linear` and `stepped`)
+    #[serde(rename = "penaltyPercent", default)]
+    pub penalty_percent: f64,
+    /// `hour` or `day`
+    #[serde(rename = "penaltyPeriod", default = "default_penalty_period")]
+    pub penalty_period: String,
+}
+
+fn default_penalty_period() -> String {
+    "day".to_string()
+}
+
+/// How late a submission was and what that cost when its points were computed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatePenalty {
+    #[serde(rename = "minutesLate")]
+    pub minutes_late: i32,
+    /// Percentage of the points taken off
+    #[serde(rename = "penaltyPercent")]
+    pub penalty_percent: f64,
+    /// Late work under a `practice_only` policy is graded but earns no points
+    #[serde(rename = "practiceOnly")]
+    pub practice_only: bool,
+}
+
+impl LatePenalty {
+    /// The penalty stored on a submission; `None` when it was on time or is not graded yet
+    pub fn recorded(
+        late_minutes: Option<i32>,
+        penalty_percent: f64,
+        practice_only: bool,
+    ) -> Option<Self> {
+        late_minutes.map(|minutes_late| LatePenalty {
+            minutes_late,
+            penalty_percent,
+            practice_only,
+        })
+    }
+}
+
+/// How nbgrader results become points for a challenge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradingMode {
+    /// Scores are stored and an admin grades every submission
+    Manual,
+    /// The nbgrader score is converted to points and credited straight away
+    Auto,
+    /// The converted points are proposed and credited once an admin grades the submission
+    AutoWithReview,
+}
+
+impl GradingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GradingMode::Manual => "manual",
+            GradingMode::Auto => "auto",
+            GradingMode::AutoWithReview => "auto_with_review",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "manual" => Some(GradingMode::Manual),
+            "auto" => Some(GradingMode::Auto),
+            "auto_with_review" => Some(GradingMode::AutoWithReview),
+            _ => None,
+        }
+    }
+}
+
+/// How students work on a challenge and how it is graded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeType {
+    /// A JupyterHub notebook graded by nbgrader
+    Notebook,
+    /// A predictions CSV scored against a hidden ground truth
+    Prediction,
+}
+
+impl ChallengeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChallengeType::Notebook => "notebook",
+            ChallengeType::Prediction => "prediction",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "notebook" => Some(ChallengeType::Notebook),
+            "prediction" => Some(ChallengeType::Prediction),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ChallengeResponse {
     pub id: i32,
@@ -281,6 +466,12 @@ pub struct UserProfileResponse {
     pub name: String,
     pub points: i32,
     pub image: Option<String>,
+    /// Shown so the client can prompt for verification; nothing is gated on it, since an
+    /// unverified member has to stay signed in to ask for the email again
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    pub role: String,
+    pub permissions: Vec<String>,
     pub stats: UserStatsResponse,
 }
 
@@ -455,6 +646,12 @@ pub struct AdminChallengeResponse {
     #[serde(rename = "endDate")]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: bool,
+    #[serde(rename = "gradingMode")]
+    pub grading_mode: String,
+    #[serde(rename = "challengeType")]
+    pub challenge_type: String,
+    #[serde(rename = "latePolicy")]
+    pub late_policy: LatePolicySettings,
     #[serde(rename = "createdAt")]
     pub created_at: time::OffsetDateTime,
     #[serde(rename = "updatedAt")]
@@ -475,6 +672,15 @@ pub struct AdminCreateChallengeRequest {
     #[serde(rename = "endDate", deserialize_with = "date_format::deserialize")]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: Option<bool>,
+    /// `manual`, `auto` or `auto_with_review`
+    #[serde(rename = "gradingMode")]
+    pub grading_mode: Option<String>,
+    /// `notebook` or `prediction`
+    #[serde(rename = "challengeType")]
+    pub challenge_type: Option<String>,
+    /// Replaces the late policy; defaults to `cutoff` on create
+    #[serde(rename = "latePolicy")]
+    pub late_policy: Option<LatePolicySettings>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -491,6 +697,15 @@ pub struct AdminUpdateChallengeRequest {
     #[serde(rename = "endDate", deserialize_with = "date_format::deserialize")]
     pub end_date: Option<time::OffsetDateTime>,
     pub visible: Option<bool>,
+    /// `manual`, `auto` or `auto_with_review`
+    #[serde(rename = "gradingMode")]
+    pub grading_mode: Option<String>,
+    /// `notebook` or `prediction`
+    #[serde(rename = "challengeType")]
+    pub challenge_type: Option<String>,
+    /// Replaces the late policy; defaults to `cutoff` on create
+    #[serde(rename = "latePolicy")]
+    pub late_policy: Option<LatePolicySettings>,
 }
 
 #[derive(Debug, Serialize)]
@@ -501,6 +716,19 @@ pub struct AdminItemResponse<T> {
 #[derive(Debug, Serialize)]
 pub struct AdminItemsResponse<T> {
     pub items: Vec<T>,
+    /// Totals for lists that are paged
+    #[serde(flatten)]
+    pub pagination: Option<Pagination>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pagination {
+    pub total: i64,
+    pub page: i64,
+    #[serde(rename = "pageSize")]
+    pub page_size: i64,
+    #[serde(rename = "totalPages")]
+    pub total_pages: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -599,7 +827,8 @@ pub struct ChallengeSubmission {
     pub id: Uuid,
     pub user_id: Uuid,
     pub challenge_id: i32,
-    pub notebook_id: i32,
+    /// `None` for prediction challenges
+    pub notebook_id: Option<i32>,
     pub attempt_number: i32,
     pub status: String,
     pub score: Option<f64>,
@@ -612,10 +841,30 @@ pub struct ChallengeSubmission {
     pub graded_at: Option<time::OffsetDateTime>,
     pub manual_graded_by: Option<Uuid>,
     pub manual_graded_at: Option<time::OffsetDateTime>,
+    pub deadline_at: Option<time::OffsetDateTime>,
+    pub feedback: Option<String>,
+    pub rubric_scores: Option<sqlx::types::Json<Vec<RubricScore>>>,
+    pub late_minutes: Option<i32>,
+    pub late_penalty_percent: f64,
+    pub late_practice_only: bool,
+    pub metric_value: Option<f64>,
+    pub public_metric_value: Option<f64>,
+    pub public_score: Option<f64>,
+    pub final_selected: bool,
     pub created_at: time::OffsetDateTime,
     pub updated_at: time::OffsetDateTime,
 }
 
+impl ChallengeSubmission {
+    pub fn late_penalty(&self) -> Option<LatePenalty> {
+        LatePenalty::recorded(
+            self.late_minutes,
+            self.late_penalty_percent,
+            self.late_practice_only,
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SubmissionStatus {
@@ -624,6 +873,7 @@ pub enum SubmissionStatus {
     GradingPending,
     Graded,
     Error,
+    Expired,
 }
 
 impl SubmissionStatus {
@@ -634,6 +884,7 @@ impl SubmissionStatus {
             SubmissionStatus::GradingPending => "grading_pending",
             SubmissionStatus::Graded => "graded",
             SubmissionStatus::Error => "error",
+            SubmissionStatus::Expired => "expired",
         }
     }
 
@@ -644,6 +895,7 @@ impl SubmissionStatus {
             "grading_pending" => Some(SubmissionStatus::GradingPending),
             "graded" => Some(SubmissionStatus::Graded),
             "error" => Some(SubmissionStatus::Error),
+            "expired" => Some(SubmissionStatus::Expired),
             _ => None,
         }
     }
@@ -674,8 +926,12 @@ pub struct ChallengeWithNotebookResponse {
     pub description: String,
     #[serde(rename = "allowedSubmissions")]
     pub allowed_submissions: i32,
+    #[serde(rename = "challengeType")]
+    pub challenge_type: String,
     #[serde(rename = "hasNotebook")]
     pub has_notebook: bool,
+    /// What prediction challenges are scored by
+    pub metric: Option<String>,
     #[serde(rename = "maxPoints")]
     pub max_points: Option<i32>,
     #[serde(rename = "timeLimitMinutes")]
@@ -684,6 +940,9 @@ pub struct ChallengeWithNotebookResponse {
     pub start_date: Option<time::OffsetDateTime>,
     #[serde(rename = "endDate", serialize_with = "iso8601_option::serialize")]
     pub end_date: Option<time::OffsetDateTime>,
+    /// What happens to attempts submitted after `endDate`
+    #[serde(rename = "latePolicy")]
+    pub late_policy: LatePolicySettings,
 }
 
 #[derive(Debug, Serialize)]
@@ -697,6 +956,15 @@ pub struct UserSubmissionResponse {
     pub score: Option<f64>,
     #[serde(rename = "maxScore")]
     pub max_score: Option<f64>,
+    /// The raw metric of a scored predictions file
+    #[serde(rename = "metricValue")]
+    pub metric_value: Option<f64>,
+    /// The result on the public rows of a split test set; `score` and `metricValue` stay
+    /// hidden until the private leaderboard is revealed
+    #[serde(rename = "publicMetricValue")]
+    pub public_metric_value: Option<f64>,
+    #[serde(rename = "publicScore")]
+    pub public_score: Option<f64>,
     #[serde(rename = "pointsAwarded")]
     pub points_awarded: i32,
     #[serde(rename = "startedAt", serialize_with = "iso8601_option::serialize")]
@@ -705,12 +973,20 @@ pub struct UserSubmissionResponse {
     pub submitted_at: Option<time::OffsetDateTime>,
     #[serde(rename = "gradedAt", serialize_with = "iso8601_option::serialize")]
     pub graded_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "deadline", serialize_with = "iso8601_option::serialize")]
+    pub deadline_at: Option<time::OffsetDateTime>,
     #[serde(rename = "allowedSubmissions")]
     pub allowed_submissions: i32,
     #[serde(rename = "attemptsUsed")]
     pub attempts_used: i64,
     #[serde(rename = "attemptsRemaining")]
     pub attempts_remaining: i64,
+    /// Only shown once the attempt is graded
+    pub feedback: Option<String>,
+    /// How the attempt scored against the challenge rubric, once graded
+    pub rubric: Option<Vec<RubricScore>>,
+    /// Set once the points for a late attempt are worked out
+    pub late: Option<LatePenalty>,
 }
 
 #[derive(Debug, Serialize)]
@@ -727,6 +1003,13 @@ pub struct StartChallengeResponse {
     #[serde(rename = "attemptsRemaining")]
     pub attempts_remaining: i64,
     pub token: String,
+    #[serde(rename = "startedAt", serialize_with = "iso8601_option::serialize")]
+    pub started_at: Option<time::OffsetDateTime>,
+    /// When the attempt must be submitted by; `None` for untimed notebooks
+    #[serde(rename = "deadline", serialize_with = "iso8601_option::serialize")]
+    pub deadline_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "timeLimitMinutes")]
+    pub time_limit_minutes: i32,
 }
 
 #[derive(Debug, Deserialize)]
@@ -741,9 +1024,6 @@ pub struct NbgraderWebhookPayload {
     #[serde(rename = "maxScore")]
     pub max_score: f64,
     pub timestamp: Option<String>,
-    /// Secret key to verify the webhook is from JupyterHub
-    #[serde(rename = "webhookSecret")]
-    pub webhook_secret: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -765,6 +1045,115 @@ pub struct SubmitChallengeResponse {
     pub attempts_used: i64,
     #[serde(rename = "attemptsRemaining")]
     pub attempts_remaining: i64,
+    /// The penalty that will apply when a late attempt is graded
+    pub late: Option<LatePenalty>,
+}
+
+/// The hidden test set of a prediction challenge; the ground truth itself is not loaded
+#[derive(Debug, Serialize, FromRow)]
+pub struct PredictionTask {
+    pub id: i32,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    /// `accuracy`, `macro_f1`, `rmse`, `mae`, `log_loss` or `auc`
+    pub metric: String,
+    #[serde(rename = "idColumn")]
+    pub id_column: String,
+    #[serde(rename = "targetColumn")]
+    pub target_column: String,
+    /// The metric value that earns no points
+    pub baseline: Option<f64>,
+    #[serde(rename = "maxPoints")]
+    pub max_points: i32,
+    #[serde(rename = "groundTruthFilename")]
+    pub ground_truth_filename: String,
+    #[serde(rename = "rowCount")]
+    pub row_count: i32,
+    /// Share of the rows ranked publicly while the challenge runs; `None` scores every row
+    #[serde(rename = "publicPercent")]
+    pub public_percent: Option<i32>,
+    /// How many attempts each student may count towards the private leaderboard
+    #[serde(rename = "finalSubmissions")]
+    pub final_submissions: i32,
+    /// When points for the private leaderboard were credited
+    #[serde(rename = "revealedAt", serialize_with = "iso8601_option::serialize")]
+    pub revealed_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmitPredictionsResponse {
+    pub success: bool,
+    pub message: String,
+    #[serde(rename = "submissionId")]
+    pub submission_id: Uuid,
+    pub status: String,
+    #[serde(rename = "attemptNumber")]
+    pub attempt_number: i32,
+    #[serde(rename = "attemptsUsed")]
+    pub attempts_used: i64,
+    #[serde(rename = "attemptsRemaining")]
+    pub attempts_remaining: i64,
+    pub metric: String,
+    /// Hidden until the end date when the test set has a private part
+    #[serde(rename = "metricValue")]
+    pub metric_value: Option<f64>,
+    /// Out of 100
+    pub score: Option<f64>,
+    /// The result on the public rows of a split test set
+    #[serde(rename = "publicMetricValue")]
+    pub public_metric_value: Option<f64>,
+    #[serde(rename = "publicScore")]
+    pub public_score: Option<f64>,
+    /// Points credited now; 0 while the challenge's grading mode waits for review or
+    /// the private leaderboard is still hidden
+    #[serde(rename = "pointsAwarded")]
+    pub points_awarded: i32,
+    pub late: Option<LatePenalty>,
+}
+
+/// One of a student's attempts that can count on a private leaderboard
+#[derive(Debug, Serialize)]
+pub struct FinalSubmissionAttempt {
+    #[serde(rename = "submissionId")]
+    pub submission_id: Uuid,
+    #[serde(rename = "attemptNumber")]
+    pub attempt_number: i32,
+    #[serde(rename = "submittedAt", serialize_with = "iso8601_option::serialize")]
+    pub submitted_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "publicMetricValue")]
+    pub public_metric_value: f64,
+    #[serde(rename = "publicScore")]
+    pub public_score: f64,
+    /// Shown once the private leaderboard is revealed
+    #[serde(rename = "metricValue")]
+    pub metric_value: Option<f64>,
+    pub score: Option<f64>,
+    /// Chosen by the student
+    pub selected: bool,
+    /// Counts on the private leaderboard: the chosen attempts, or else the best public ones
+    #[serde(rename = "final")]
+    pub is_final: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinalSubmissionsResponse {
+    /// How many attempts may be chosen
+    #[serde(rename = "finalSubmissions")]
+    pub final_submissions: i32,
+    /// Choices are fixed once the challenge ends
+    pub locked: bool,
+    pub attempts: Vec<FinalSubmissionAttempt>,
+}
+
+/// Replaces the chosen attempts; an empty list goes back to the best public ones
+#[derive(Debug, Deserialize)]
+pub struct SelectFinalSubmissionsRequest {
+    #[serde(rename = "submissionIds")]
+    pub submission_ids: Vec<Uuid>,
 }
 
 // Admin types for notebook management
@@ -855,6 +1244,11 @@ pub struct AdminSubmissionResponse {
     pub score: Option<f64>,
     #[serde(rename = "maxScore")]
     pub max_score: Option<f64>,
+    /// On a split test set, `metricValue` is the private result and this the public one
+    #[serde(rename = "metricValue")]
+    pub metric_value: Option<f64>,
+    #[serde(rename = "publicMetricValue")]
+    pub public_metric_value: Option<f64>,
     #[serde(rename = "pointsAwarded")]
     pub points_awarded: i32,
     #[serde(rename = "pointsCredited")]
@@ -865,6 +1259,9 @@ pub struct AdminSubmissionResponse {
     pub submitted_at: Option<time::OffsetDateTime>,
     #[serde(rename = "gradedAt", serialize_with = "iso8601_option::serialize")]
     pub graded_at: Option<time::OffsetDateTime>,
+    pub feedback: Option<String>,
+    pub rubric: Option<Vec<RubricScore>>,
+    pub late: Option<LatePenalty>,
 }
 
 #[derive(Debug, Serialize)]
@@ -877,9 +1274,105 @@ pub struct AdminSubmissionAccessResponse {
     pub message: String,
 }
 
+/// Grade with a `score` out of 100, or with `rubric` scores from which the score is worked out
 #[derive(Debug, Deserialize)]
 pub struct AdminGradeSubmissionRequest {
+    pub score: Option<f64>,
+    pub rubric: Option<Vec<AdminRubricScoreRequest>>,
+    /// Written feedback for the student; replaces any earlier feedback
+    pub feedback: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminRubricScoreRequest {
+    #[serde(rename = "criterionId")]
+    pub criterion_id: i32,
+    pub points: f64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminBulkGradeRow {
+    #[serde(rename = "submissionId")]
+    pub submission_id: Uuid,
     pub score: f64,
+    /// Replaces the earlier feedback when given; blank clears it
+    pub feedback: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminBulkGradeRequest {
+    pub grades: Vec<AdminBulkGradeRow>,
+}
+
+/// Either every row was graded, or none were and `errors` says why
+#[derive(Debug, Serialize)]
+pub struct AdminBulkGradeResponse {
+    pub success: bool,
+    pub message: String,
+    pub graded: Vec<BulkGradeResult>,
+    pub errors: Vec<BulkGradeError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkGradeResult {
+    pub row: usize,
+    #[serde(rename = "submissionId")]
+    pub submission_id: Uuid,
+    pub score: Option<f64>,
+    #[serde(rename = "pointsAwarded")]
+    pub points_awarded: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkGradeError {
+    /// 1-based position in the request, or the line number in an uploaded CSV
+    pub row: usize,
+    #[serde(rename = "submissionId")]
+    pub submission_id: Option<Uuid>,
+    pub message: String,
+}
+
+/// A rubric criterion as it was scored for one submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RubricScore {
+    #[serde(rename = "criterionId")]
+    pub criterion_id: i32,
+    pub title: String,
+    pub weight: f64,
+    #[serde(rename = "maxPoints")]
+    pub max_points: f64,
+    pub points: f64,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct RubricCriterion {
+    pub id: i32,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: i32,
+    pub position: i32,
+    pub title: String,
+    pub description: Option<String>,
+    pub weight: f64,
+    #[serde(rename = "maxPoints")]
+    pub max_points: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminRubricCriterionRequest {
+    pub title: String,
+    pub description: Option<String>,
+    /// Defaults to 1
+    pub weight: Option<f64>,
+    #[serde(rename = "maxPoints")]
+    pub max_points: f64,
+}
+
+/// Replaces the whole rubric; an empty list removes it
+#[derive(Debug, Deserialize)]
+pub struct AdminUpdateRubricRequest {
+    pub criteria: Vec<AdminRubricCriterionRequest>,
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -891,9 +1384,19 @@ pub struct ChallengeSubmissionLeaderboardEntry {
     pub points_awarded: i32,
     pub score: Option<f64>,
     pub max_score: Option<f64>,
+    pub metric_value: Option<f64>,
     pub status: String,
     pub graded_at: Option<time::OffsetDateTime>,
     pub challenge_rank: i64,
+    /// `public` or `private` for a prediction challenge with a split test set
+    #[sqlx(default)]
+    pub leaderboard: Option<String>,
+    /// On a private leaderboard, the student's rank on the public one
+    #[sqlx(default)]
+    pub public_rank: Option<i64>,
+    /// Places gained (or lost, if negative) from the public to the private leaderboard
+    #[sqlx(default)]
+    pub rank_change: Option<i64>,
 }
 
 // Admin JupyterHub access response
@@ -912,3 +1415,359 @@ pub struct AdminSyncNotebookResponse {
     pub success: bool,
     pub message: String,
 }
+
+// ============================================
+// Roles and permissions
+// ============================================
+
+#[derive(Debug, Serialize)]
+pub struct AdminRoleResponse {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminUpdateUserRoleRequest {
+    pub role: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserRoleResponse {
+    pub id: Uuid,
+    #[serde(rename = "fullName")]
+    pub full_name: String,
+    pub email: String,
+    pub role: String,
+    pub permissions: Vec<String>,
+}
+
+// ============================================
+// Admin user management
+// ============================================
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AdminUserResponse {
+    pub id: Uuid,
+    pub email: String,
+    #[serde(rename = "fullName")]
+    pub full_name: String,
+    #[serde(rename = "phoneNum")]
+    pub phone_num: Option<String>,
+    pub image: Option<String>,
+    pub university: Option<String>,
+    pub major: Option<String>,
+    pub role: String,
+    pub points: i32,
+    pub rank: i32,
+    #[serde(rename = "jupyterhubUsername")]
+    pub jupyterhub_username: Option<String>,
+    /// Informational for admins; access does not depend on it
+    #[serde(rename = "emailVerified")]
+    pub email_verified: bool,
+    #[serde(rename = "suspendedAt", serialize_with = "iso8601_option::serialize")]
+    pub suspended_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "suspendedReason")]
+    pub suspended_reason: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminUserDetailResponse {
+    pub user: AdminUserResponse,
+    pub stats: UserStatsResponse,
+    pub submissions: Vec<AdminSubmissionResponse>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminSuspendUserRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminResetJupyterHubUsernameRequest {
+    #[serde(rename = "jupyterhubUsername")]
+    pub jupyterhub_username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminAdjustPointsRequest {
+    pub delta: i32,
+    pub reason: String,
+}
+
+// ============================================
+// Points ledger
+// ============================================
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PointTransactionResponse {
+    pub id: Uuid,
+    pub amount: i32,
+    pub reason: String,
+    /// `submission` for grading, `admin` for manual adjustments, `system` for migrated balances
+    pub source: String,
+    #[serde(rename = "submissionId")]
+    pub submission_id: Option<Uuid>,
+    #[serde(rename = "challengeId")]
+    pub challenge_id: Option<i32>,
+    #[serde(rename = "challengeTitle")]
+    pub challenge_title: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PointHistoryResponse {
+    pub points: i32,
+    pub transactions: Vec<PointTransactionResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminRecomputePointsResponse {
+    pub success: bool,
+    #[serde(rename = "usersUpdated")]
+    pub users_updated: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminRecomputeChallengePointsResponse {
+    pub success: bool,
+    #[serde(rename = "submissionsUpdated")]
+    pub submissions_updated: i64,
+    /// Net change to students' points
+    #[serde(rename = "pointsDelta")]
+    pub points_delta: i64,
+}
+
+// Grading service outbox
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct GradingJob {
+    pub id: Uuid,
+    /// `prepare_notebook`, `submit` or `setup_assignment`
+    pub kind: String,
+    pub payload: serde_json::Value,
+    #[serde(rename = "submissionId")]
+    pub submission_id: Option<Uuid>,
+    #[serde(rename = "notebookId")]
+    pub notebook_id: Option<i32>,
+    /// `pending`, `succeeded` or `failed` (gave up after `max_attempts`)
+    pub status: String,
+    pub attempts: i32,
+    #[serde(rename = "maxAttempts")]
+    pub max_attempts: i32,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: time::OffsetDateTime,
+    #[serde(rename = "completedAt", serialize_with = "iso8601_option::serialize")]
+    pub completed_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminGradingQueueResponse {
+    pub pending: i64,
+    pub failed: i64,
+    pub succeeded: i64,
+    pub items: Vec<GradingJob>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminGradingServiceHealthResponse {
+    pub healthy: bool,
+    pub message: String,
+}
+
+// Outbound webhooks
+
+/// An integration notified of platform events. The signing secret is never returned.
+#[derive(Debug, Serialize, FromRow)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub name: String,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Vec<String>,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+    #[serde(rename = "createdBy")]
+    pub created_by: Option<Uuid>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminCreateWebhookRequest {
+    pub name: String,
+    pub url: String,
+    /// Shared with the receiver, which verifies the `x-webhook-signature` header with it
+    pub secret: String,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Vec<String>,
+    #[serde(rename = "isActive")]
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminUpdateWebhookRequest {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    #[serde(rename = "eventTypes")]
+    pub event_types: Option<Vec<String>>,
+    #[serde(rename = "isActive")]
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct OutboundWebhookDelivery {
+    pub id: Uuid,
+    #[serde(rename = "subscriptionId")]
+    pub subscription_id: Uuid,
+    #[serde(rename = "eventType")]
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// `pending`, `succeeded` or `failed` (gave up after `max_attempts`)
+    pub status: String,
+    pub attempts: i32,
+    #[serde(rename = "maxAttempts")]
+    pub max_attempts: i32,
+    /// HTTP status of the last response, if the receiver answered
+    #[serde(rename = "responseStatus")]
+    pub response_status: Option<i32>,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "nextAttemptAt")]
+    pub next_attempt_at: time::OffsetDateTime,
+    #[serde(rename = "completedAt", serialize_with = "iso8601_option::serialize")]
+    pub completed_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: time::OffsetDateTime,
+}
+
+// Notifications
+
+/// An in-app notification for the signed-in user
+#[derive(Debug, Serialize, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub user_id: Uuid,
+    /// `submission_graded`, `challenge_published` or `certificate_published`
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub data: serde_json::Value,
+    #[serde(rename = "readAt", serialize_with = "iso8601_option::serialize")]
+    pub read_at: Option<time::OffsetDateTime>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotificationListResponse {
+    pub notifications: Vec<Notification>,
+    #[serde(rename = "unreadCount")]
+    pub unread_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnreadNotificationCountResponse {
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MarkAllNotificationsReadResponse {
+    pub success: bool,
+    pub updated: u64,
+}
+
+/// Which kinds of notification the user receives
+#[derive(Debug, Serialize, FromRow)]
+pub struct NotificationPreferences {
+    #[serde(rename = "submissionGraded")]
+    pub submission_graded: bool,
+    #[serde(rename = "challengePublished")]
+    pub challenge_published: bool,
+    #[serde(rename = "certificatePublished")]
+    pub certificate_published: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    #[serde(rename = "submissionGraded")]
+    pub submission_graded: Option<bool>,
+    #[serde(rename = "challengePublished")]
+    pub challenge_published: Option<bool>,
+    #[serde(rename = "certificatePublished")]
+    pub certificate_published: Option<bool>,
+}
+
+// Audit log
+
+/// An admin action, with the fields of its target that it changed
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    #[serde(rename = "actorId")]
+    pub actor_id: Option<Uuid>,
+    #[serde(rename = "actorName")]
+    pub actor_name: Option<String>,
+    #[serde(rename = "actorEmail")]
+    pub actor_email: Option<String>,
+    /// `<target type>.<verb>`, e.g. `challenge.delete`
+    pub action: String,
+    #[serde(rename = "targetType")]
+    pub target_type: String,
+    #[serde(rename = "targetId")]
+    pub target_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    #[serde(rename = "ipAddress")]
+    pub ip_address: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: time::OffsetDateTime,
+}
+
+// Trash
+
+/// A deleted challenge, notebook, resource or certificate that can still be restored
+#[derive(Debug, Serialize, FromRow)]
+pub struct TrashedItem {
+    pub id: i32,
+    /// The notebook's assignment name, or the title of anything else
+    pub title: String,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: time::OffsetDateTime,
+    /// Submissions a purge would delete with it
+    pub submissions: i64,
+    #[serde(rename = "gradedSubmissions")]
+    pub graded_submissions: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminPurgeQuery {
+    /// Purge even when graded submissions would be deleted
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminPurgeResponse {
+    pub success: bool,
+    #[serde(rename = "submissionsDeleted")]
+    pub submissions_deleted: i64,
+}
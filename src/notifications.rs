@@ -0,0 +1,114 @@
+use serde_json::{Value, json};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::{Certificate, Challenge, ChallengeSubmission},
+};
+
+/// Kinds of in-app notification; each can be switched off in the user's preferences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    SubmissionGraded,
+    ChallengePublished,
+    CertificatePublished,
+}
+
+impl NotificationKind {
+    /// Stored in `notifications.kind`; also the `notification_preferences` column that
+    /// opts in to this kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::SubmissionGraded => "submission_graded",
+            NotificationKind::ChallengePublished => "challenge_published",
+            NotificationKind::CertificatePublished => "certificate_published",
+        }
+    }
+}
+
+/// A notification to create, for one user or for every active member
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    /// `None` announces it to everyone
+    pub recipient: Option<Uuid>,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub body: String,
+    pub data: Value,
+}
+
+impl NewNotification {
+    pub fn submission_graded(submission: &ChallengeSubmission, challenge_title: &str) -> Self {
+        NewNotification {
+            recipient: Some(submission.user_id),
+            kind: NotificationKind::SubmissionGraded,
+            title: "Submission graded".to_string(),
+            body: format!(
+                "Your attempt {} at {} was graded: {} points awarded.",
+                submission.attempt_number, challenge_title, submission.points_awarded
+            ),
+            data: json!({
+                "submissionId": submission.id,
+                "challengeId": submission.challenge_id,
+            }),
+        }
+    }
+
+    pub fn challenge_published(challenge: &Challenge) -> Self {
+        NewNotification {
+            recipient: None,
+            kind: NotificationKind::ChallengePublished,
+            title: "New challenge".to_string(),
+            body: format!("{} is now open.", challenge.title),
+            data: json!({ "challengeId": challenge.id }),
+        }
+    }
+
+    pub fn certificate_published(certificate: &Certificate) -> Self {
+        NewNotification {
+            recipient: None,
+            kind: NotificationKind::CertificatePublished,
+            title: "New certificate".to_string(),
+            body: format!(
+                "{} {} earned {} ({}).",
+                certificate.first_name,
+                certificate.second_name,
+                certificate.title,
+                certificate.course_title
+            ),
+            data: json!({ "certificateId": certificate.id }),
+        }
+    }
+}
+
+/// Create `notification` for its recipients, skipping suspended users and anyone who
+/// switched this kind off. Run it in the transaction of the change it reports.
+pub async fn create_notification(
+    conn: &mut PgConnection,
+    notification: &NewNotification,
+) -> Result<u64, AppError> {
+    let query = format!(
+        r#"
+        INSERT INTO notifications (user_id, kind, title, body, data)
+        SELECT u.id, $1, $2, $3, $4
+        FROM users u
+        LEFT JOIN notification_preferences p ON p.user_id = u.id
+        WHERE u.suspended_at IS NULL
+          AND COALESCE(p.{}, TRUE)
+          AND ($5::UUID IS NULL OR u.id = $5)
+        "#,
+        notification.kind.as_str()
+    );
+
+    let result = sqlx::query(&query)
+        .bind(notification.kind.as_str())
+        .bind(&notification.title)
+        .bind(&notification.body)
+        .bind(&notification.data)
+        .bind(notification.recipient)
+        .execute(conn)
+        .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    models::OutboundWebhookDelivery,
+    signing::{self, DELIVERY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+
+/// Names the event type, so receivers can route without parsing the body
+pub const EVENT_HEADER: &str = "x-webhook-event";
+
+const BASE_BACKOFF_SECONDS: i64 = 30;
+const MAX_BACKOFF_SECONDS: i64 = 3600;
+/// A claimed delivery is hidden from other workers for this long, in case it never reports back
+const CLAIM_TIMEOUT_SECONDS: i64 = 120;
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+const BATCH_SIZE: i64 = 20;
+/// Receivers' error bodies are kept in the delivery log up to this length
+const MAX_ERROR_LENGTH: usize = 500;
+
+/// HTTP client used to deliver webhooks
+pub fn webhook_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+        .build()
+        .expect("failed to build webhook HTTP client")
+}
+
+/// Delay before the next attempt after `attempts` failed ones
+fn backoff_seconds(attempts: i32) -> i64 {
+    let exponent = attempts.clamp(1, 20) as u32 - 1;
+    (BASE_BACKOFF_SECONDS << exponent).min(MAX_BACKOFF_SECONDS)
+}
+
+#[derive(sqlx::FromRow)]
+struct ClaimedDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: serde_json::Value,
+    attempts: i32,
+    max_attempts: i32,
+    url: String,
+    secret: String,
+}
+
+/// Deliver every due webhook, oldest first; returns how many were attempted.
+/// Deliveries of paused subscriptions wait until the subscription is active again.
+pub async fn process_due_webhook_deliveries(
+    pool: &PgPool,
+    client: &reqwest::Client,
+) -> Result<usize, AppError> {
+    let claimed: Vec<ClaimedDelivery> = sqlx::query_as(
+        r#"
+        UPDATE outbound_webhook_deliveries d
+        SET attempts = d.attempts + 1,
+            next_attempt_at = NOW() + make_interval(secs => $2),
+            updated_at = NOW()
+        FROM webhook_subscriptions s
+        WHERE s.id = d.subscription_id
+          AND d.id IN (
+            SELECT d2.id
+            FROM outbound_webhook_deliveries d2
+            JOIN webhook_subscriptions s2 ON s2.id = d2.subscription_id
+            WHERE d2.status = 'pending' AND d2.next_attempt_at <= NOW() AND s2.is_active
+            ORDER BY d2.next_attempt_at
+            LIMIT $1
+            FOR UPDATE OF d2 SKIP LOCKED
+          )
+        RETURNING d.id, d.event_type, d.payload, d.attempts, d.max_attempts, s.url, s.secret
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .bind(CLAIM_TIMEOUT_SECONDS as f64)
+    .fetch_all(pool)
+    .await?;
+
+    let count = claimed.len();
+    for delivery in claimed {
+        send_claimed_delivery(pool, client, delivery).await?;
+    }
+
+    Ok(count)
+}
+
+async fn send_claimed_delivery(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    delivery: ClaimedDelivery,
+) -> Result<(), AppError> {
+    let body = delivery.payload.to_string();
+    let timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let delivery_id = delivery.id.to_string();
+
+    let result = client
+        .post(&delivery.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, &delivery_id)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            signing::sign(&delivery.secret, timestamp, &delivery_id, body.as_bytes()),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    let (response_status, error) = match result {
+        Ok(response) if response.status().is_success() => (Some(response.status()), None),
+        Ok(response) => {
+            let status = response.status();
+            let text: String = response
+                .text()
+                .await
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_ERROR_LENGTH)
+                .collect();
+            (Some(status), Some(format!("{status} - {text}")))
+        }
+        Err(e) => (e.status(), Some(format!("{e:#}"))),
+    };
+    let response_status = response_status.map(|status| status.as_u16() as i32);
+
+    match error {
+        None => {
+            sqlx::query(
+                r#"
+                UPDATE outbound_webhook_deliveries
+                SET status = 'succeeded', response_status = $2, last_error = NULL,
+                    completed_at = NOW(), updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(response_status)
+            .execute(pool)
+            .await?;
+        }
+        Some(error) => {
+            let gave_up = delivery.attempts >= delivery.max_attempts;
+            if gave_up {
+                tracing::error!(
+                    "Webhook delivery {} failed after {} attempts: {}",
+                    delivery.id,
+                    delivery.attempts,
+                    error
+                );
+            } else {
+                tracing::warn!(
+                    "Webhook delivery {} attempt {} failed: {}",
+                    delivery.id,
+                    delivery.attempts,
+                    error
+                );
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE outbound_webhook_deliveries
+                SET status = CASE WHEN $2 THEN 'failed' ELSE 'pending' END,
+                    response_status = $3,
+                    last_error = $4,
+                    next_attempt_at = NOW() + make_interval(secs => $5),
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery.id)
+            .bind(gave_up)
+            .bind(response_status)
+            .bind(&error)
+            .bind(backoff_seconds(delivery.attempts) as f64)
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Make a delivery due on the next poll, including one that already gave up
+pub async fn retry_webhook_delivery(
+    pool: &PgPool,
+    delivery_id: Uuid,
+) -> Result<OutboundWebhookDelivery, AppError> {
+    let delivery: OutboundWebhookDelivery =
+        sqlx::query_as("SELECT * FROM outbound_webhook_deliveries WHERE id = $1")
+            .bind(delivery_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(AppError::NotFound)?;
+
+    if delivery.status == "succeeded" {
+        return Err(AppError::BadRequest(
+            "This delivery has already succeeded".to_string(),
+        ));
+    }
+
+    let delivery = sqlx::query_as(
+        r#"
+        UPDATE outbound_webhook_deliveries
+        SET status = 'pending', next_attempt_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND status <> 'succeeded'
+        RETURNING *
+        "#,
+    )
+    .bind(delivery_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+/// Periodically deliver queued webhooks
+pub fn spawn_webhook_worker(pool: PgPool, interval_seconds: u64) {
+    tokio::spawn(async move {
+        let client = webhook_client();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = process_due_webhook_deliveries(&pool, &client).await {
+                tracing::error!("Failed to deliver webhooks: {:?}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,152 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::Deserialize;
+
+use crate::{error::AppError, models::Pagination};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawListParams {
+    page: Option<i64>,
+    #[serde(rename = "pageSize")]
+    page_size: Option<i64>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+/// Paging and sorting of an admin list: `?page=2&pageSize=50&sort=createdAt&order=desc`.
+/// Filters are left to each endpoint's own query struct.
+#[derive(Debug, Clone)]
+pub struct ListParams {
+    pub page: i64,
+    pub page_size: i64,
+    sort: Option<String>,
+    order: Option<SortOrder>,
+}
+
+impl ListParams {
+    pub fn limit(&self) -> i64 {
+        self.page_size
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page - 1) * self.page_size
+    }
+
+    /// `ORDER BY` clause for the requested sort key. `columns` maps each sortable key to its
+    /// SQL expression; without a key the list is sorted by `default`. `tiebreaker` keeps the
+    /// order stable between pages.
+    pub fn order_by(
+        &self,
+        columns: &[(&str, &str)],
+        default: (&str, SortOrder),
+        tiebreaker: &str,
+    ) -> Result<String, AppError> {
+        let key = self.sort.as_deref().unwrap_or(default.0);
+        let (_, column) = columns
+            .iter()
+            .find(|(name, _)| *name == key)
+            .ok_or_else(|| {
+                let keys: Vec<&str> = columns.iter().map(|(name, _)| *name).collect();
+                AppError::BadRequest(format!(
+                    "Cannot sort by {key}; expected one of {}",
+                    keys.join(", ")
+                ))
+            })?;
+        let order = self.order.unwrap_or(if self.sort.is_some() {
+            SortOrder::Asc
+        } else {
+            default.1
+        });
+
+        Ok(format!(
+            "ORDER BY {column} {order} NULLS LAST, {tiebreaker} {order}",
+            order = order.as_sql()
+        ))
+    }
+
+    /// Where this page sits among `total` matching items
+    pub fn pagination(&self, total: i64) -> Pagination {
+        Pagination {
+            total,
+            page: self.page,
+            page_size: self.page_size,
+            total_pages: (total + self.page_size - 1) / self.page_size,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ListParams
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(raw) = Query::<RawListParams>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        let page = raw.page.unwrap_or(1);
+        if page < 1 {
+            return Err(AppError::BadRequest("page must be at least 1".to_string()));
+        }
+
+        let page_size = raw.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+        if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+            return Err(AppError::BadRequest(format!(
+                "pageSize must be between 1 and {MAX_PAGE_SIZE}"
+            )));
+        }
+        // `offset()` multiplies the two, so a huge page number would overflow
+        if (page - 1).checked_mul(page_size).is_none() {
+            return Err(AppError::BadRequest("page is too large".to_string()));
+        }
+
+        let order = match raw.order.as_deref() {
+            None => None,
+            Some("asc") => Some(SortOrder::Asc),
+            Some("desc") => Some(SortOrder::Desc),
+            Some(_) => {
+                return Err(AppError::BadRequest(
+                    "order must be asc or desc".to_string(),
+                ));
+            }
+        };
+
+        Ok(Self {
+            page,
+            page_size,
+            sort: raw.sort.filter(|s| !s.is_empty()),
+            order,
+        })
+    }
+}
+
+/// `ILIKE` pattern for a free-text filter; `None` when there is nothing to match
+pub fn search_pattern(search: Option<String>) -> Option<String> {
+    search
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("%{s}%"))
+}
@@ -0,0 +1,63 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// Append an entry to the points ledger and apply it to the user's cached balance.
+/// Run it inside the transaction that caused the change so both commit together.
+pub async fn record_point_transaction(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    amount: i32,
+    reason: &str,
+    submission_id: Option<Uuid>,
+    created_by: Option<Uuid>,
+) -> Result<(), AppError> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO point_transactions (user_id, amount, reason, submission_id, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(user_id)
+    .bind(amount)
+    .bind(reason)
+    .bind(submission_id)
+    .bind(created_by)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE users SET points = points + $1 WHERE id = $2")
+        .bind(amount)
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Reset every user's points to the sum of their ledger entries.
+/// Returns how many users had drifted from the ledger.
+pub async fn recompute_all_points(pool: &PgPool) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users u
+        SET points = ledger.total
+        FROM (
+            SELECT users.id, COALESCE(SUM(pt.amount), 0)::INTEGER AS total
+            FROM users
+            LEFT JOIN point_transactions pt ON pt.user_id = users.id
+            GROUP BY users.id
+        ) AS ledger
+        WHERE u.id = ledger.id AND u.points <> ledger.total
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,450 @@
+use std::collections::{HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+use crate::{error::AppError, models::PredictionTask};
+
+/// Probabilities are clipped this far from 0 and 1 so log-loss stays finite
+const LOG_LOSS_EPSILON: f64 = 1e-15;
+
+/// A challenge's prediction task, without its ground truth
+pub async fn load_prediction_task<'e, E>(
+    executor: E,
+    challenge_id: i32,
+) -> Result<Option<PredictionTask>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let task = sqlx::query_as(
+        r#"
+        SELECT id, challenge_id, metric, id_column, target_column, baseline, max_points,
+               ground_truth_filename, row_count, public_percent, final_submissions, revealed_at,
+               created_at, updated_at
+        FROM challenge_prediction_tasks
+        WHERE challenge_id = $1
+        "#,
+    )
+    .bind(challenge_id)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(task)
+}
+
+/// The hidden ground truth CSV of a prediction task
+pub async fn load_ground_truth<'e, E>(executor: E, task_id: i32) -> Result<Vec<u8>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let ground_truth =
+        sqlx::query_scalar("SELECT ground_truth FROM challenge_prediction_tasks WHERE id = $1")
+            .bind(task_id)
+            .fetch_one(executor)
+            .await?;
+
+    Ok(ground_truth)
+}
+
+/// How a predictions file is compared with the ground truth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredictionMetric {
+    /// Share of labels predicted exactly
+    Accuracy,
+    /// F1 averaged over every label, each label counting equally
+    MacroF1,
+    /// Root mean squared error of numeric predictions
+    Rmse,
+    /// Mean absolute error of numeric predictions
+    Mae,
+    /// Binary cross-entropy of predicted probabilities for 0/1 labels
+    LogLoss,
+    /// Area under the ROC curve of predicted probabilities for 0/1 labels
+    Auc,
+}
+
+impl PredictionMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PredictionMetric::Accuracy => "accuracy",
+            PredictionMetric::MacroF1 => "macro_f1",
+            PredictionMetric::Rmse => "rmse",
+            PredictionMetric::Mae => "mae",
+            PredictionMetric::LogLoss => "log_loss",
+            PredictionMetric::Auc => "auc",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "accuracy" => Some(PredictionMetric::Accuracy),
+            "macro_f1" => Some(PredictionMetric::MacroF1),
+            "rmse" => Some(PredictionMetric::Rmse),
+            "mae" => Some(PredictionMetric::Mae),
+            "log_loss" => Some(PredictionMetric::LogLoss),
+            "auc" => Some(PredictionMetric::Auc),
+            _ => None,
+        }
+    }
+
+    /// Accuracy, macro-F1 and AUC are better higher; the error metrics are better lower
+    pub fn higher_is_better(&self) -> bool {
+        matches!(
+            self,
+            PredictionMetric::Accuracy | PredictionMetric::MacroF1 | PredictionMetric::Auc
+        )
+    }
+
+    /// Check the ground truth values this metric needs: numbers for the regression metrics,
+    /// 0/1 labels with both classes present for log-loss and AUC
+    pub fn validate_ground_truth(&self, rows: &[PredictionRow]) -> Result<(), AppError> {
+        match self {
+            PredictionMetric::Accuracy | PredictionMetric::MacroF1 => Ok(()),
+            PredictionMetric::Rmse | PredictionMetric::Mae => {
+                for row in rows {
+                    number(row)?;
+                }
+                Ok(())
+            }
+            PredictionMetric::LogLoss | PredictionMetric::Auc => {
+                let mut classes = HashSet::new();
+                for row in rows {
+                    classes.insert(binary_label(row)?);
+                }
+                if classes.len() < 2 {
+                    return Err(AppError::BadRequest(
+                        "The ground truth must contain both 0 and 1 labels".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Score out of 100 for a metric value. `baseline` is the value that earns nothing:
+    /// 0 by default for accuracy, macro-F1 and AUC, and required for the error metrics.
+    pub fn score(&self, value: f64, baseline: Option<f64>) -> f64 {
+        let fraction = if self.higher_is_better() {
+            let baseline = baseline.unwrap_or(0.0);
+            (value - baseline) / (1.0 - baseline)
+        } else {
+            match baseline {
+                Some(baseline) if baseline > 0.0 => 1.0 - value / baseline,
+                _ => 0.0,
+            }
+        };
+
+        fraction.clamp(0.0, 1.0) * 100.0
+    }
+
+    /// Check an admin-chosen baseline for this metric
+    pub fn validate_baseline(&self, baseline: Option<f64>) -> Result<(), AppError> {
+        let valid = match (self.higher_is_better(), baseline) {
+            (true, None) => true,
+            (true, Some(baseline)) => (0.0..1.0).contains(&baseline),
+            (false, Some(baseline)) => baseline.is_finite() && baseline > 0.0,
+            (false, None) => false,
+        };
+
+        if valid {
+            Ok(())
+        } else if self.higher_is_better() {
+            Err(AppError::ValidationError(format!(
+                "baseline for {} must be at least 0 and below 1",
+                self.as_str()
+            )))
+        } else {
+            Err(AppError::ValidationError(format!(
+                "{} needs a positive baseline: the error that earns no points",
+                self.as_str()
+            )))
+        }
+    }
+}
+
+/// One `id,value` row of a ground truth or predictions file
+#[derive(Debug, Clone)]
+pub struct PredictionRow {
+    /// Line of the file the row was read from
+    pub line: u64,
+    pub id: String,
+    pub value: String,
+}
+
+/// Read the `id_column` and `target_column` of a CSV file. Column names match case-insensitively;
+/// every row needs both values and each id may appear only once.
+pub fn read_prediction_csv(
+    data: &[u8],
+    id_column: &str,
+    target_column: &str,
+) -> Result<Vec<PredictionRow>, AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::BadRequest(format!("Invalid CSV: {e}")))?
+        .clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(name))
+            .ok_or_else(|| AppError::BadRequest(format!("The CSV needs a \"{name}\" column")))
+    };
+    let id_index = column(id_column)?;
+    let target_index = column(target_column)?;
+
+    let mut rows = Vec::new();
+    let mut seen = HashSet::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| AppError::BadRequest(format!("Invalid CSV: {e}")))?;
+        let line = record.position().map_or(0, |p| p.line());
+        let id = record.get(id_index).unwrap_or_default();
+        let value = record.get(target_index).unwrap_or_default();
+
+        if id.is_empty() || value.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Line {line}: both {id_column} and {target_column} are required"
+            )));
+        }
+        if !seen.insert(id.to_string()) {
+            return Err(AppError::BadRequest(format!(
+                "Line {line}: id {id} appears more than once"
+            )));
+        }
+
+        rows.push(PredictionRow {
+            line,
+            id: id.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    if rows.is_empty() {
+        return Err(AppError::BadRequest("The CSV has no rows".to_string()));
+    }
+
+    Ok(rows)
+}
+
+/// Line `predictions` up with the ground truth. There must be exactly one prediction for
+/// every ground truth id, and no others.
+pub fn pair_predictions(
+    truth: &[PredictionRow],
+    predictions: Vec<PredictionRow>,
+) -> Result<Vec<PredictionPair<'_>>, AppError> {
+    let mut by_id: HashMap<String, PredictionRow> = predictions
+        .into_iter()
+        .map(|row| (row.id.clone(), row))
+        .collect();
+
+    let mut pairs = Vec::with_capacity(truth.len());
+    let mut missing = Vec::new();
+    for expected in truth {
+        match by_id.remove(&expected.id) {
+            Some(predicted) => pairs.push((expected, predicted)),
+            None => missing.push(expected.id.as_str()),
+        }
+    }
+
+    if let Some(first) = missing.first() {
+        return Err(AppError::BadRequest(format!(
+            "{} ids from the test set have no prediction, starting with {first}",
+            missing.len()
+        )));
+    }
+    if let Some(unknown) = by_id.values().min_by_key(|row| row.line) {
+        return Err(AppError::BadRequest(format!(
+            "Line {}: id {} is not in the test set",
+            unknown.line, unknown.id
+        )));
+    }
+
+    Ok(pairs)
+}
+
+/// A ground truth row and the prediction made for it
+pub type PredictionPair<'a> = (&'a PredictionRow, PredictionRow);
+
+/// The metric over paired rows
+pub fn evaluate(metric: PredictionMetric, pairs: &[PredictionPair<'_>]) -> Result<f64, AppError> {
+    let value = match metric {
+        PredictionMetric::Accuracy => {
+            let correct = pairs
+                .iter()
+                .filter(|(expected, predicted)| expected.value == predicted.value)
+                .count();
+            correct as f64 / pairs.len() as f64
+        }
+        PredictionMetric::MacroF1 => macro_f1(pairs),
+        PredictionMetric::Rmse | PredictionMetric::Mae => {
+            let mut total = 0.0;
+            for (expected, predicted) in pairs {
+                let error = number(predicted)? - number(expected)?;
+                total += if metric == PredictionMetric::Rmse {
+                    error * error
+                } else {
+                    error.abs()
+                };
+            }
+            let mean = total / pairs.len() as f64;
+            if metric == PredictionMetric::Rmse {
+                mean.sqrt()
+            } else {
+                mean
+            }
+        }
+        PredictionMetric::LogLoss => {
+            let mut total = 0.0;
+            for (expected, predicted) in pairs {
+                let p = probability(predicted)?.clamp(LOG_LOSS_EPSILON, 1.0 - LOG_LOSS_EPSILON);
+                total -= if binary_label(expected)? {
+                    p.ln()
+                } else {
+                    (1.0 - p).ln()
+                };
+            }
+            total / pairs.len() as f64
+        }
+        PredictionMetric::Auc => {
+            let mut scored = Vec::with_capacity(pairs.len());
+            for (expected, predicted) in pairs {
+                scored.push((probability(predicted)?, binary_label(expected)?));
+            }
+            auc(scored)
+        }
+    };
+
+    Ok(value)
+}
+
+/// The ids of the public rows when `public_percent` of the test set is ranked publicly.
+/// Rows are ordered by a hash of the challenge and their id, so the split stays the same
+/// between scorings and when the same test set is uploaded again; both parts get a row.
+pub fn public_ids(
+    challenge_id: i32,
+    truth: &[PredictionRow],
+    public_percent: i32,
+) -> HashSet<&str> {
+    let mut hashed: Vec<([u8; 32], &str)> = truth
+        .iter()
+        .map(|row| {
+            let digest = Sha256::digest(format!("{challenge_id}:{}", row.id).as_bytes());
+            (digest.into(), row.id.as_str())
+        })
+        .collect();
+    hashed.sort();
+
+    let public_rows = (truth.len() as f64 * public_percent as f64 / 100.0).round() as usize;
+    let public_rows = public_rows.clamp(1, truth.len().saturating_sub(1).max(1));
+
+    hashed
+        .into_iter()
+        .take(public_rows)
+        .map(|(_, id)| id)
+        .collect()
+}
+
+fn number(row: &PredictionRow) -> Result<f64, AppError> {
+    row.value
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| {
+            AppError::BadRequest(format!("Line {}: {} is not a number", row.line, row.value))
+        })
+}
+
+fn probability(row: &PredictionRow) -> Result<f64, AppError> {
+    number(row)
+        .ok()
+        .filter(|p| (0.0..=1.0).contains(p))
+        .ok_or_else(|| {
+            AppError::BadRequest(format!(
+                "Line {}: {} is not a probability between 0 and 1",
+                row.line, row.value
+            ))
+        })
+}
+
+fn binary_label(row: &PredictionRow) -> Result<bool, AppError> {
+    match number(row) {
+        Ok(0.0) => Ok(false),
+        Ok(1.0) => Ok(true),
+        _ => Err(AppError::BadRequest(format!(
+            "Line {}: label {} must be 0 or 1",
+            row.line, row.value
+        ))),
+    }
+}
+
+/// F1 per label over every label that is either expected or predicted, averaged
+fn macro_f1(pairs: &[PredictionPair<'_>]) -> f64 {
+    #[derive(Default)]
+    struct Counts {
+        true_positives: usize,
+        false_positives: usize,
+        false_negatives: usize,
+    }
+
+    let mut labels: HashMap<&str, Counts> = HashMap::new();
+    for (expected, predicted) in pairs {
+        if expected.value == predicted.value {
+            labels
+                .entry(expected.value.as_str())
+                .or_default()
+                .true_positives += 1;
+        } else {
+            labels
+                .entry(expected.value.as_str())
+                .or_default()
+                .false_negatives += 1;
+            labels
+                .entry(predicted.value.as_str())
+                .or_default()
+                .false_positives += 1;
+        }
+    }
+
+    let total: f64 = labels
+        .values()
+        .map(|c| {
+            let denominator = 2 * c.true_positives + c.false_positives + c.false_negatives;
+            if denominator == 0 {
+                0.0
+            } else {
+                2.0 * c.true_positives as f64 / denominator as f64
+            }
+        })
+        .sum();
+
+    total / labels.len() as f64
+}
+
+/// Probability that a random positive is ranked above a random negative, ties counting half
+fn auc(mut scored: Vec<(f64, bool)>) -> f64 {
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let positives = scored.iter().filter(|(_, positive)| *positive).count() as f64;
+    let negatives = scored.len() as f64 - positives;
+    if positives == 0.0 || negatives == 0.0 {
+        return 0.5;
+    }
+
+    // Sum of the (1-based, tie-averaged) ranks of the positives
+    let mut positive_rank_sum = 0.0;
+    let mut start = 0;
+    while start < scored.len() {
+        let mut end = start;
+        while end + 1 < scored.len() && scored[end + 1].0 == scored[start].0 {
+            end += 1;
+        }
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        let tied_positives = scored[start..=end].iter().filter(|(_, p)| *p).count();
+        positive_rank_sum += rank * tied_positives as f64;
+        start = end + 1;
+    }
+
+    (positive_rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives)
+}
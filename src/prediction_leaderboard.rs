@@ -0,0 +1,384 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    events::{Event, publish_event},
+    handlers::webhooks::update_user_ranks::update_user_ranks,
+    live_updates::{notify_leaderboard_changed, notify_submission_changed},
+    models::{Challenge, ChallengeSubmission, ChallengeSubmissionLeaderboardEntry, GradingMode},
+    notifications::{NewNotification, create_notification},
+    points::record_point_transaction,
+    prediction::PredictionMetric,
+};
+
+/// Whether the private results of a split test set are visible: once the end date passes
+pub fn private_revealed(end_date: Option<OffsetDateTime>, at: OffsetDateTime) -> bool {
+    end_date.is_some_and(|end_date| at >= end_date)
+}
+
+/// A scored attempt at a split prediction challenge, made in time to count on the private
+/// leaderboard
+#[derive(Debug, sqlx::FromRow)]
+pub struct ScoredAttempt {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub full_name: String,
+    pub image: Option<String>,
+    pub attempt_number: i32,
+    pub status: String,
+    pub points_awarded: i32,
+    pub points_credited: bool,
+    pub score: f64,
+    pub metric_value: f64,
+    pub public_score: f64,
+    pub public_metric_value: f64,
+    pub final_selected: bool,
+    pub submitted_at: OffsetDateTime,
+    pub graded_at: Option<OffsetDateTime>,
+}
+
+/// Scored attempts at a challenge submitted by its end date, of one user or everyone
+pub async fn load_scored_attempts<'e, E>(
+    executor: E,
+    challenge_id: i32,
+    user_id: Option<Uuid>,
+) -> Result<Vec<ScoredAttempt>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let attempts = sqlx::query_as(
+        r#"
+        SELECT cs.id, cs.user_id, u.full_name, u.image, cs.attempt_number, cs.status,
+               cs.points_awarded, cs.points_credited, cs.score, cs.metric_value,
+               cs.public_score, cs.public_metric_value, cs.final_selected, cs.submitted_at,
+               cs.graded_at
+        FROM challenge_submissions cs
+        JOIN challenges c ON c.id = cs.challenge_id AND c.deleted_at IS NULL
+        JOIN users u ON u.id = cs.user_id
+        WHERE cs.challenge_id = $1
+          AND ($2::uuid IS NULL OR cs.user_id = $2)
+          AND cs.status IN ('graded', 'grading_pending')
+          AND cs.score IS NOT NULL AND cs.metric_value IS NOT NULL
+          AND cs.public_score IS NOT NULL AND cs.public_metric_value IS NOT NULL
+          AND cs.submitted_at IS NOT NULL
+          AND (c.end_date IS NULL OR cs.submitted_at <= c.end_date)
+        ORDER BY cs.submitted_at
+        "#,
+    )
+    .bind(challenge_id)
+    .bind(user_id)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(attempts)
+}
+
+/// Best first by metric value, earlier submissions winning ties
+fn compare(
+    metric: PredictionMetric,
+    a: (f64, OffsetDateTime),
+    b: (f64, OffsetDateTime),
+) -> Ordering {
+    let by_value = if metric.higher_is_better() {
+        b.0.total_cmp(&a.0)
+    } else {
+        a.0.total_cmp(&b.0)
+    };
+
+    by_value.then(a.1.cmp(&b.1))
+}
+
+/// The attempts that count for one student on the private leaderboard: the ones they chose,
+/// or else their best `final_submissions` on the public rows
+pub fn final_attempts<'a>(
+    metric: PredictionMetric,
+    final_submissions: i32,
+    attempts: &[&'a ScoredAttempt],
+) -> Vec<&'a ScoredAttempt> {
+    let selected: Vec<&ScoredAttempt> = attempts
+        .iter()
+        .copied()
+        .filter(|attempt| attempt.final_selected)
+        .collect();
+    if !selected.is_empty() {
+        return selected;
+    }
+
+    let mut by_public: Vec<&ScoredAttempt> = attempts.to_vec();
+    by_public.sort_by(|a, b| {
+        compare(
+            metric,
+            (a.public_metric_value, a.submitted_at),
+            (b.public_metric_value, b.submitted_at),
+        )
+    });
+    by_public.truncate(final_submissions.max(1) as usize);
+    by_public
+}
+
+/// Each student's best attempt by `value`, best first, ranked like SQL `RANK()`
+fn rank_best(
+    metric: PredictionMetric,
+    mut attempts: Vec<&ScoredAttempt>,
+    value: fn(&ScoredAttempt) -> f64,
+) -> Vec<(i64, &ScoredAttempt)> {
+    attempts.sort_by(|a, b| {
+        compare(
+            metric,
+            (value(a), a.submitted_at),
+            (value(b), b.submitted_at),
+        )
+    });
+
+    let mut seen = HashSet::new();
+    let mut ranked: Vec<(i64, &ScoredAttempt)> = Vec::new();
+    for attempt in attempts {
+        if !seen.insert(attempt.user_id) {
+            continue;
+        }
+        let rank = match ranked.last() {
+            Some((rank, previous)) if value(previous) == value(attempt) => *rank,
+            _ => ranked.len() as i64 + 1,
+        };
+        ranked.push((rank, attempt));
+    }
+
+    ranked
+}
+
+/// Every student's counted attempt on the private leaderboard, ranked
+fn rank_private(
+    metric: PredictionMetric,
+    final_submissions: i32,
+    attempts: &[ScoredAttempt],
+) -> Vec<(i64, &ScoredAttempt)> {
+    let mut by_user: HashMap<Uuid, Vec<&ScoredAttempt>> = HashMap::new();
+    for attempt in attempts {
+        by_user.entry(attempt.user_id).or_default().push(attempt);
+    }
+
+    let finals = by_user
+        .values()
+        .flat_map(|own| final_attempts(metric, final_submissions, own))
+        .collect();
+
+    rank_best(metric, finals, |attempt| attempt.metric_value)
+}
+
+/// The leaderboard of a challenge with a split test set: public scores while it runs, then
+/// the private ranking of each student's final attempts, with where they stood publicly
+pub async fn load_split_leaderboard(
+    pool: &PgPool,
+    challenge_id: i32,
+    metric: PredictionMetric,
+    final_submissions: i32,
+) -> Result<Vec<ChallengeSubmissionLeaderboardEntry>, AppError> {
+    let end_date: Option<OffsetDateTime> =
+        sqlx::query_scalar("SELECT end_date FROM challenges WHERE id = $1")
+            .bind(challenge_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+    let attempts = load_scored_attempts(pool, challenge_id, None).await?;
+    let public = rank_best(metric, attempts.iter().collect(), |attempt| {
+        attempt.public_metric_value
+    });
+
+    let entry = |rank: i64, attempt: &ScoredAttempt, leaderboard: &str| {
+        ChallengeSubmissionLeaderboardEntry {
+            challenge_id,
+            user_id: attempt.user_id,
+            full_name: attempt.full_name.clone(),
+            image: attempt.image.clone(),
+            points_awarded: 0,
+            score: Some(attempt.public_score),
+            max_score: Some(100.0),
+            metric_value: Some(attempt.public_metric_value),
+            status: attempt.status.clone(),
+            graded_at: attempt.graded_at,
+            challenge_rank: rank,
+            leaderboard: Some(leaderboard.to_string()),
+            public_rank: None,
+            rank_change: None,
+        }
+    };
+
+    // Points follow the private scores, so they stay hidden with them
+    if !private_revealed(end_date, OffsetDateTime::now_utc()) {
+        return Ok(public
+            .into_iter()
+            .take(50)
+            .map(|(rank, attempt)| entry(rank, attempt, "public"))
+            .collect());
+    }
+
+    let entries = rank_private(metric, final_submissions, &attempts)
+        .into_iter()
+        .take(50)
+        .map(|(rank, attempt)| {
+            let public_rank = public
+                .iter()
+                .find(|(_, best)| best.user_id == attempt.user_id)
+                .map(|(public_rank, _)| *public_rank);
+
+            ChallengeSubmissionLeaderboardEntry {
+                points_awarded: if attempt.points_credited {
+                    attempt.points_awarded
+                } else {
+                    0
+                },
+                score: Some(attempt.score),
+                metric_value: Some(attempt.metric_value),
+                public_rank,
+                rank_change: public_rank.map(|public_rank| public_rank - rank),
+                ..entry(rank, attempt, "private")
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Reveal the private leaderboard of a challenge whose end date has passed. Under auto
+/// grading, each student's counted final attempt is credited now; points were held back
+/// until the private scores could be shown. Returns whether the challenge was revealed.
+pub async fn reveal_private_leaderboard(
+    pool: &PgPool,
+    challenge_id: i32,
+) -> Result<bool, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let task: Option<(String, i32)> = sqlx::query_as(
+        r#"
+        SELECT pt.metric, pt.final_submissions
+        FROM challenge_prediction_tasks pt
+        JOIN challenges c ON c.id = pt.challenge_id
+        WHERE pt.challenge_id = $1 AND pt.public_percent IS NOT NULL AND pt.revealed_at IS NULL
+          AND c.end_date <= NOW() AND c.deleted_at IS NULL
+        FOR UPDATE OF pt
+        "#,
+    )
+    .bind(challenge_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((metric, final_submissions)) = task else {
+        return Ok(false);
+    };
+    let metric = PredictionMetric::parse(&metric)
+        .ok_or_else(|| AppError::InternalError(anyhow::anyhow!("Unknown metric {metric}")))?;
+
+    let challenge: Challenge = sqlx::query_as("SELECT * FROM challenges WHERE id = $1")
+        .bind(challenge_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let grading_mode = challenge.parsed_grading_mode()?;
+
+    let mut credited = 0;
+    if grading_mode == GradingMode::Auto {
+        let attempts = load_scored_attempts(&mut *tx, challenge_id, None).await?;
+        for (_, attempt) in rank_private(metric, final_submissions, &attempts) {
+            if attempt.status != "graded" || attempt.points_credited {
+                continue;
+            }
+
+            let submission: ChallengeSubmission = sqlx::query_as(
+                r#"
+                UPDATE challenge_submissions
+                SET points_credited = true, graded_at = COALESCE(graded_at, NOW()), updated_at = NOW()
+                WHERE id = $1
+                RETURNING *
+                "#,
+            )
+            .bind(attempt.id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            record_point_transaction(
+                &mut tx,
+                submission.user_id,
+                submission.points_awarded,
+                &format!(
+                    "Private leaderboard: {} (attempt {})",
+                    challenge.title, submission.attempt_number
+                ),
+                Some(submission.id),
+                None,
+            )
+            .await?;
+
+            publish_event(&mut tx, &Event::submission_graded(&submission)).await?;
+            create_notification(
+                &mut tx,
+                &NewNotification::submission_graded(&submission, &challenge.title),
+            )
+            .await?;
+            notify_submission_changed(&mut tx, &submission).await?;
+            credited += 1;
+        }
+    }
+
+    // `updated_at` tracks the test set itself, which prediction uploads check before storing
+    sqlx::query(
+        "UPDATE challenge_prediction_tasks SET revealed_at = NOW() WHERE challenge_id = $1",
+    )
+    .bind(challenge_id)
+    .execute(&mut *tx)
+    .await?;
+    notify_leaderboard_changed(&mut tx, challenge_id).await?;
+
+    tx.commit().await?;
+
+    if credited > 0 {
+        update_user_ranks(pool).await?;
+    }
+
+    Ok(true)
+}
+
+/// Reveal every private leaderboard whose end date has passed; returns how many were revealed
+pub async fn reveal_due_leaderboards(pool: &PgPool) -> Result<usize, AppError> {
+    let due: Vec<i32> = sqlx::query_scalar(
+        r#"
+        SELECT pt.challenge_id
+        FROM challenge_prediction_tasks pt
+        JOIN challenges c ON c.id = pt.challenge_id
+        WHERE pt.public_percent IS NOT NULL AND pt.revealed_at IS NULL
+          AND c.end_date <= NOW() AND c.deleted_at IS NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut revealed = 0;
+    for challenge_id in due {
+        if reveal_private_leaderboard(pool, challenge_id).await? {
+            revealed += 1;
+        }
+    }
+
+    Ok(revealed)
+}
+
+/// Periodically reveal private leaderboards as their challenges end
+pub fn spawn_leaderboard_revealer(pool: PgPool, interval_seconds: u64) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_seconds));
+
+        loop {
+            interval.tick().await;
+
+            match reveal_due_leaderboards(&pool).await {
+                Ok(0) => {}
+                Ok(revealed) => tracing::info!("Revealed {} private leaderboards", revealed),
+                Err(e) => tracing::error!("Failed to reveal private leaderboards: {:?}", e),
+            }
+        }
+    });
+}
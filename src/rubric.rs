@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::AppError,
+    models::{AdminRubricScoreRequest, RubricCriterion, RubricScore},
+};
+
+/// A challenge's rubric criteria, in the order they are presented
+pub async fn load_rubric<'e, E>(
+    executor: E,
+    challenge_id: i32,
+) -> Result<Vec<RubricCriterion>, AppError>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let criteria = sqlx::query_as(
+        r#"
+        SELECT id, challenge_id, position, title, description, weight, max_points
+        FROM challenge_rubric_criteria
+        WHERE challenge_id = $1
+        ORDER BY position, id
+        "#,
+    )
+    .bind(challenge_id)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(criteria)
+}
+
+/// Check `scores` against the rubric and work out the score out of 100 they add up to.
+/// Every criterion must be scored exactly once, between 0 and its maximum points;
+/// each one counts towards the total in proportion to its weight.
+pub fn score_rubric(
+    criteria: &[RubricCriterion],
+    scores: Vec<AdminRubricScoreRequest>,
+) -> Result<(f64, Vec<RubricScore>), AppError> {
+    if criteria.is_empty() {
+        return Err(AppError::BadRequest(
+            "This challenge has no rubric; grade it with a score instead".to_string(),
+        ));
+    }
+
+    let mut by_criterion: HashMap<i32, AdminRubricScoreRequest> = HashMap::new();
+    for score in scores {
+        let criterion_id = score.criterion_id;
+        if by_criterion.insert(criterion_id, score).is_some() {
+            return Err(AppError::BadRequest(format!(
+                "Criterion {criterion_id} is scored more than once"
+            )));
+        }
+    }
+
+    let mut breakdown = Vec::with_capacity(criteria.len());
+    let mut weighted = 0.0;
+    let mut total_weight = 0.0;
+
+    for criterion in criteria {
+        let score = by_criterion.remove(&criterion.id).ok_or_else(|| {
+            AppError::BadRequest(format!("Criterion \"{}\" has no score", criterion.title))
+        })?;
+        if !(0.0..=criterion.max_points).contains(&score.points) {
+            return Err(AppError::BadRequest(format!(
+                "Points for \"{}\" must be between 0 and {}",
+                criterion.title, criterion.max_points
+            )));
+        }
+
+        weighted += criterion.weight * score.points / criterion.max_points;
+        total_weight += criterion.weight;
+        breakdown.push(RubricScore {
+            criterion_id: criterion.id,
+            title: criterion.title.clone(),
+            weight: criterion.weight,
+            max_points: criterion.max_points,
+            points: score.points,
+            comment: score
+                .comment
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty()),
+        });
+    }
+
+    if let Some(unknown) = by_criterion.keys().min() {
+        return Err(AppError::BadRequest(format!(
+            "Criterion {unknown} is not part of this challenge's rubric"
+        )));
+    }
+
+    let score = (weighted / total_weight * 10_000.0).round() / 100.0;
+
+    Ok((score, breakdown))
+}
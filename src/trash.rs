@@ -0,0 +1,200 @@
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+
+use crate::{error::AppError, models::TrashedItem};
+
+/// Content that deleting moves to the trash, from where it can be restored or purged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashKind {
+    Challenge,
+    Notebook,
+    Resource,
+    Certificate,
+}
+
+impl TrashKind {
+    /// Also the target type recorded in the audit log
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashKind::Challenge => "challenge",
+            TrashKind::Notebook => "notebook",
+            TrashKind::Resource => "resource",
+            TrashKind::Certificate => "certificate",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            TrashKind::Challenge => "challenges",
+            TrashKind::Notebook => "challenge_notebooks",
+            TrashKind::Resource => "resources",
+            TrashKind::Certificate => "certificates",
+        }
+    }
+
+    fn title_column(&self) -> &'static str {
+        match self {
+            TrashKind::Notebook => "assignment_name",
+            _ => "title",
+        }
+    }
+
+    /// Column of `challenge_submissions` pointing at this kind; purging it cascades to them
+    fn dependents_column(&self) -> Option<&'static str> {
+        match self {
+            TrashKind::Challenge => Some("challenge_id"),
+            TrashKind::Notebook => Some("notebook_id"),
+            TrashKind::Resource | TrashKind::Certificate => None,
+        }
+    }
+}
+
+/// Items of `kind` in the trash, most recently deleted first
+pub async fn list_trash(pool: &PgPool, kind: TrashKind) -> Result<Vec<TrashedItem>, AppError> {
+    let (counts, join) = match kind.dependents_column() {
+        Some(column) => (
+            "s.submissions, s.graded_submissions",
+            format!(
+                r#"
+                LEFT JOIN LATERAL (
+                    SELECT
+                        COUNT(*) AS submissions,
+                        COUNT(*) FILTER (WHERE cs.status = 'graded') AS graded_submissions
+                    FROM challenge_submissions cs
+                    WHERE cs.{column} = t.id
+                ) s ON TRUE
+                "#
+            ),
+        ),
+        None => (
+            "0::BIGINT AS submissions, 0::BIGINT AS graded_submissions",
+            String::new(),
+        ),
+    };
+
+    let query = format!(
+        r#"
+        SELECT t.id, t.{title} AS title, t.deleted_at, {counts}
+        FROM {table} t
+        {join}
+        WHERE t.deleted_at IS NOT NULL
+        ORDER BY t.deleted_at DESC, t.id DESC
+        "#,
+        title = kind.title_column(),
+        table = kind.table(),
+    );
+
+    let items = sqlx::query_as(&query).fetch_all(pool).await?;
+
+    Ok(items)
+}
+
+/// Take an item out of the trash. Returns its row for the audit log.
+pub async fn restore_from_trash(
+    conn: &mut PgConnection,
+    kind: TrashKind,
+    id: i32,
+) -> Result<Value, AppError> {
+    let query = format!(
+        "UPDATE {} t SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL RETURNING to_jsonb(t)",
+        kind.table()
+    );
+
+    match sqlx::query_scalar(&query)
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+    {
+        Ok(Some(item)) => Ok(item),
+        Ok(None) => Err(AppError::NotFound),
+        // Only live notebooks need a unique challenge and assignment name
+        Err(sqlx::Error::Database(db_err)) if db_err.code().as_deref() == Some("23505") => {
+            Err(AppError::Conflict(
+                "Another notebook now uses this challenge or assignment name".to_string(),
+            ))
+        }
+        Err(e) => Err(AppError::DatabaseError(e)),
+    }
+}
+
+/// What purging an item removed
+#[derive(Debug)]
+pub struct PurgedItem {
+    /// The purged row, for the audit log
+    pub item: Value,
+    pub submissions_deleted: i64,
+    notebook_paths: Vec<String>,
+}
+
+impl PurgedItem {
+    /// Delete the files of purged notebooks. Call it once the purge is committed.
+    pub async fn remove_files(&self) {
+        for path in &self.notebook_paths {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+}
+
+/// Delete a trashed item for good, along with the submissions that depend on it.
+/// Refuses to delete graded submissions unless `force` is set; the points they earned stay.
+pub async fn purge_from_trash(
+    conn: &mut PgConnection,
+    kind: TrashKind,
+    id: i32,
+    force: bool,
+) -> Result<PurgedItem, AppError> {
+    let item: Value = sqlx::query_scalar(&format!(
+        "SELECT to_jsonb(t) FROM {} t WHERE id = $1 AND deleted_at IS NOT NULL FOR UPDATE",
+        kind.table()
+    ))
+    .bind(id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let mut submissions_deleted = 0;
+    let mut notebook_paths = Vec::new();
+
+    if let Some(column) = kind.dependents_column() {
+        let (submissions, graded): (i64, i64) = sqlx::query_as(&format!(
+            r#"
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE status = 'graded')
+            FROM challenge_submissions
+            WHERE {column} = $1
+            "#
+        ))
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if graded > 0 && !force {
+            return Err(AppError::Conflict(format!(
+                "Purging this {} would delete {graded} graded submission(s); pass force=true to purge anyway",
+                kind.as_str()
+            )));
+        }
+        submissions_deleted = submissions;
+
+        let notebook_column = match kind {
+            TrashKind::Notebook => "id",
+            _ => column,
+        };
+        notebook_paths = sqlx::query_scalar(&format!(
+            "SELECT notebook_path FROM challenge_notebooks WHERE {notebook_column} = $1"
+        ))
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query(&format!("DELETE FROM {} WHERE id = $1", kind.table()))
+        .bind(id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(PurgedItem {
+        item,
+        submissions_deleted,
+        notebook_paths,
+    })
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn admin_lists_are_paged_sorted_and_filtered() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    for (title, visible) in [("Charlie", true), ("Alpha", true), ("Bravo", false)] {
+        sqlx::query(
+            r#"
+            INSERT INTO resources (title, provider, instructor_name, visible)
+            VALUES ($1, $2, 'Instructor', $3)
+            "#,
+        )
+        .bind(format!("{title} {marker}"))
+        .bind(format!("Provider {marker}"))
+        .bind(visible)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, page) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/resources?search={marker}&includeHidden=true&sort=title&pageSize=2"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["page"], 1);
+    assert_eq!(page["pageSize"], 2);
+    assert_eq!(page["totalPages"], 2);
+    let titles: Vec<&str> = page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        titles,
+        [format!("Alpha {marker}"), format!("Bravo {marker}")]
+    );
+
+    let (_, last) = send(
+        app.clone(),
+        Method::GET,
+        &format!(
+            "/admin/resources?search={marker}&includeHidden=true&sort=title&order=desc&pageSize=2&page=2"
+        ),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(last["items"].as_array().unwrap().len(), 1);
+    assert_eq!(last["items"][0]["title"], format!("Alpha {marker}"));
+
+    // Hidden resources stay out unless asked for
+    let (_, visible) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/resources?search={marker}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(visible["total"], 2);
+    let (_, hidden) = send(
+        app,
+        Method::GET,
+        &format!("/admin/resources?search={marker}&visible=false"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(hidden["total"], 1);
+    assert_eq!(hidden["items"][0]["title"], format!("Bravo {marker}"));
+}
+
+#[tokio::test]
+async fn submissions_filter_by_challenge_and_status() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    common::create_submission(&pool, student_id, challenge_id, notebook_id, 1, "graded").await;
+    common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        2,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, graded) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/submissions?challengeId={challenge_id}&status=graded"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{graded}");
+    assert_eq!(graded["total"], 1);
+    let submission = &graded["items"][0];
+    assert_eq!(submission["attemptNumber"], 1);
+    assert_eq!(
+        submission["attemptsUsed"], 2,
+        "attempts count every submission, not just the filtered ones"
+    );
+
+    let (_, all) = send(
+        app,
+        Method::GET,
+        &format!("/admin/submissions?userId={student_id}&sort=attemptNumber&order=desc"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(all["total"], 2);
+    assert_eq!(all["items"][0]["attemptNumber"], 2);
+    assert_eq!(all["items"][1]["attemptNumber"], 1);
+}
+
+#[tokio::test]
+async fn invalid_paging_and_sorting_is_rejected() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    for path in [
+        "/admin/challenges?sort=password",
+        "/admin/challenges?order=sideways",
+        "/admin/notebooks?page=0",
+        "/admin/challenges?page=9223372036854775807&pageSize=200",
+        "/admin/certificates?pageSize=1000",
+        "/admin/submissions?pageSize=abc",
+    ] {
+        let (status, body) = send(app.clone(), Method::GET, path, &admin_token, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{path}");
+        assert!(body["message"].is_string(), "{path}: {body}");
+    }
+}
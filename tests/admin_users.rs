@@ -0,0 +1,210 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn admin_searches_and_filters_users() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+
+    let marker = uuid::Uuid::new_v4().simple().to_string();
+    sqlx::query(
+        "UPDATE users SET full_name = $1, university = 'University of Jordan', major = 'Data Science', points = 120 WHERE id = $2",
+    )
+    .bind(format!("Student {marker}"))
+    .bind(student_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, found) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/users?search={marker}&major=data%20science&minPoints=100&role=user"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let items = found["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], student_id.to_string());
+    assert_eq!(items[0]["university"], "University of Jordan");
+
+    let (_, none) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/users?search={marker}&maxPoints=50"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert!(none["items"].as_array().unwrap().is_empty());
+
+    let (status, detail) = send(
+        app,
+        Method::GET,
+        &format!("/admin/users/{student_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(detail["item"]["user"]["points"], 120);
+    assert!(detail["item"]["submissions"].as_array().unwrap().is_empty());
+    assert_eq!(detail["item"]["stats"]["challengesTaken"], 0);
+}
+
+#[tokio::test]
+async fn suspended_users_lose_access_until_unsuspended() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/users/profile",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_ne!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, suspended) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/suspend"),
+        &admin_token,
+        Some(json!({ "reason": "Shared exam answers" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(suspended["item"]["suspendedReason"], "Shared exam answers");
+    assert!(suspended["item"]["suspendedAt"].is_string());
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/users/profile",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // New sign-ins are refused as well
+    assert!(
+        uj_ai_club_backend::auth::create_session(&pool, student_id)
+            .await
+            .is_err()
+    );
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/unsuspend"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let tokens = uj_ai_club_backend::auth::create_session(&pool, student_id)
+        .await
+        .unwrap();
+    let (status, _) = send(
+        app,
+        Method::GET,
+        "/users/profile",
+        &tokens.access_token,
+        None,
+    )
+    .await;
+    assert_ne!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn point_adjustments_require_a_reason_and_are_recorded() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (admin_id, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/points"),
+        &admin_token,
+        Some(json!({ "delta": 10, "reason": "   " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, adjusted) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/points"),
+        &admin_token,
+        Some(json!({ "delta": 25, "reason": "Workshop volunteer" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(adjusted["item"]["points"], 25);
+
+    let (reason, adjusted_by): (String, uuid::Uuid) =
+        sqlx::query_as("SELECT reason, created_by FROM point_transactions WHERE user_id = $1")
+            .bind(student_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(reason, "Workshop volunteer");
+    assert_eq!(adjusted_by, admin_id);
+
+    let (status, reset) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/jupyterhub-username/reset"),
+        &admin_token,
+        Some(json!({ "jupyterhubUsername": format!("student_{}", student_id.simple()) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        reset["item"]["jupyterhubUsername"],
+        format!("student_{}", student_id.simple())
+    );
+
+    let (status, cleared) = send(
+        app,
+        Method::POST,
+        &format!("/admin/users/{student_id}/jupyterhub-username/reset"),
+        &admin_token,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cleared["item"]["jupyterhubUsername"].is_null());
+}
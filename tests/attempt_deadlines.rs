@@ -0,0 +1,188 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use uj_ai_club_backend::{
+    attempts::{TimeoutAction, sweep_overdue_attempts},
+    create_router,
+};
+
+/// The sweeper closes every overdue attempt in the database, including ones other tests
+/// in this file are still asserting on
+static SWEEPER: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Move an attempt's deadline `minutes` from now (negative for overdue)
+async fn set_deadline(pool: &sqlx::PgPool, submission_id: uuid::Uuid, minutes: i32) {
+    sqlx::query(
+        "UPDATE challenge_submissions SET deadline_at = NOW() + make_interval(mins => $1) WHERE id = $2",
+    )
+    .bind(minutes)
+    .bind(submission_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn submission_status(pool: &sqlx::PgPool, submission_id: uuid::Uuid) -> String {
+    sqlx::query_scalar("SELECT status FROM challenge_submissions WHERE id = $1")
+        .bind(submission_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn starting_a_challenge_returns_the_attempt_deadline() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    sqlx::query("UPDATE challenges SET visible = true WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE challenge_notebooks SET time_limit_minutes = 45 WHERE id = $1")
+        .bind(notebook_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, started) = send(
+        app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/start"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{started}");
+    assert_eq!(started["timeLimitMinutes"], 45);
+
+    let (started_at, deadline_at): (time::OffsetDateTime, time::OffsetDateTime) =
+        sqlx::query_as("SELECT started_at, deadline_at FROM challenge_submissions WHERE id = $1")
+            .bind(uuid::Uuid::parse_str(started["submissionId"].as_str().unwrap()).unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(deadline_at - started_at, time::Duration::minutes(45));
+    assert!(started["deadline"].is_string());
+}
+
+#[tokio::test]
+async fn submitting_after_the_deadline_is_rejected() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let _sweeper = SWEEPER.lock().await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    set_deadline(&pool, submission_id, -30).await;
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(format!("user_{}", student_id.simple()))
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, body) = send(
+        app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/submit"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("The time limit for this attempt has passed"),
+        "{body}"
+    );
+
+    // The attempt was closed rather than left open for another try
+    assert_ne!(submission_status(&pool, submission_id).await, "in_progress");
+}
+
+#[tokio::test]
+async fn sweeper_closes_only_overdue_attempts() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let _sweeper = SWEEPER.lock().await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let (other_challenge_id, other_notebook_id) =
+        common::create_challenge_with_notebook(&pool, 100).await;
+
+    let overdue = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    set_deadline(&pool, overdue, -30).await;
+
+    let running = common::create_submission(
+        &pool,
+        student_id,
+        other_challenge_id,
+        other_notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    set_deadline(&pool, running, 30).await;
+
+    let mut settings = common::test_config().attempts;
+    settings.timeout_action = TimeoutAction::Submit;
+    let closed = sweep_overdue_attempts(&pool, &settings).await.unwrap();
+    assert!(closed >= 1);
+
+    let (status, submitted_at, deadline_at): (
+        String,
+        Option<time::OffsetDateTime>,
+        Option<time::OffsetDateTime>,
+    ) = sqlx::query_as(
+        "SELECT status, submitted_at, deadline_at FROM challenge_submissions WHERE id = $1",
+    )
+    .bind(overdue)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(status, "grading_pending");
+    assert_eq!(submitted_at, deadline_at);
+
+    assert_eq!(submission_status(&pool, running).await, "in_progress");
+
+    // With the expire policy the attempt is closed without grading
+    set_deadline(&pool, running, -30).await;
+    settings.timeout_action = TimeoutAction::Expire;
+    sweep_overdue_attempts(&pool, &settings).await.unwrap();
+    assert_eq!(submission_status(&pool, running).await, "expired");
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode, header},
+};
+use common::send;
+use serde_json::json;
+use tower::ServiceExt;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn admin_changes_are_recorded_with_what_changed() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (admin_id, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, created) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Audited challenge",
+            "description": "Before the rename",
+            "visible": false,
+            "startDate": null,
+            "endDate": null,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let challenge_id = created["item"]["id"].as_i64().unwrap();
+
+    // Behind the proxy: its request ID and the client address it saw are kept, not the
+    // forwarded address the client made up
+    let request = Request::builder()
+        .method(Method::PUT)
+        .uri(format!("/admin/challenges/{challenge_id}"))
+        .header(header::AUTHORIZATION, format!("Bearer {admin_token}"))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("x-request-id", "audit-test-request")
+        .header("x-forwarded-for", "198.51.100.66, 203.0.113.7")
+        .header("x-real-ip", "203.0.113.7")
+        .body(Body::from(
+            json!({ "title": "Renamed challenge", "startDate": null, "endDate": null }).to_string(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "audit-test-request");
+
+    let (status, log) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/audit-log?targetType=challenge&targetId={challenge_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{log}");
+    let entries = log["items"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+
+    let update = &entries[0];
+    assert_eq!(update["action"], "challenge.update");
+    assert_eq!(update["actorId"], admin_id.to_string());
+    assert!(update["actorEmail"].is_string());
+    assert_eq!(update["before"]["title"], "Audited challenge");
+    assert_eq!(update["after"]["title"], "Renamed challenge");
+    assert!(
+        update["before"].get("description").is_none(),
+        "unchanged fields are left out: {update}"
+    );
+    assert_eq!(update["requestId"], "audit-test-request");
+    assert_eq!(update["ipAddress"], "203.0.113.7");
+
+    let create = &entries[1];
+    assert_eq!(create["action"], "challenge.create");
+    assert!(create["before"].is_null());
+    assert_eq!(create["after"]["description"], "Before the rename");
+    assert!(
+        create["requestId"].is_string(),
+        "an ID is assigned when none is sent"
+    );
+
+    // Without X-Real-IP the last forwarded hop, added by the proxy, is the client
+    let request = Request::builder()
+        .method(Method::DELETE)
+        .uri(format!("/admin/challenges/{challenge_id}"))
+        .header(header::AUTHORIZATION, format!("Bearer {admin_token}"))
+        .header("x-forwarded-for", "198.51.100.66, 203.0.113.9")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (_, deletes) = send(
+        app,
+        Method::GET,
+        &format!("/admin/audit-log?actorId={admin_id}&action=challenge.delete"),
+        &admin_token,
+        None,
+    )
+    .await;
+    let deletes = deletes["items"].as_array().unwrap();
+    assert_eq!(deletes.len(), 1);
+    assert_eq!(deletes[0]["targetId"], challenge_id.to_string());
+    assert_eq!(deletes[0]["before"]["title"], "Renamed challenge");
+    assert!(deletes[0]["after"].is_null());
+    assert_eq!(deletes[0]["ipAddress"], "203.0.113.9");
+}
+
+#[tokio::test]
+async fn audit_log_is_admin_only_and_validates_filters() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, user_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/audit-log",
+        &user_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/audit-log?from=yesterday",
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, log) = send(
+        app,
+        Method::GET,
+        "/admin/audit-log?from=2000-01-01&to=2000-01-02",
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(log["items"], json!([]));
+}
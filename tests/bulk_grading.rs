@@ -0,0 +1,276 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode, header},
+};
+use common::{send, upload_multipart};
+use serde_json::json;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uj_ai_club_backend::create_router;
+
+async fn user_points(pool: &PgPool, user_id: uuid::Uuid) -> i32 {
+    sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn bulk_grades_apply_all_or_nothing() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (first_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (second_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 20).await;
+    let (other_challenge_id, other_notebook_id) =
+        common::create_challenge_with_notebook(&pool, 20).await;
+    let first = common::create_submission(
+        &pool,
+        first_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let second = common::create_submission(
+        &pool,
+        second_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let elsewhere = common::create_submission(
+        &pool,
+        first_id,
+        other_challenge_id,
+        other_notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let uri = format!("/admin/challenges/{challenge_id}/grades");
+
+    let (status, rejected) = send(
+        app.clone(),
+        Method::POST,
+        &uri,
+        &admin_token,
+        Some(json!({
+            "grades": [
+                { "submissionId": first, "score": 50.0 },
+                { "submissionId": second, "score": 150.0 },
+                { "submissionId": elsewhere, "score": 10.0 },
+                { "submissionId": first, "score": 60.0 },
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{rejected}");
+    assert_eq!(rejected["success"], false);
+    let errors = rejected["errors"].as_array().unwrap();
+    let rows: Vec<i64> = errors.iter().map(|e| e["row"].as_i64().unwrap()).collect();
+    assert_eq!(rows, [2, 3, 4]);
+    assert_eq!(errors[0]["message"], "score must be between 0 and 100");
+    assert_eq!(errors[1]["message"], "Not a submission of this challenge");
+    assert_eq!(user_points(&pool, first_id).await, 0, "nothing was applied");
+
+    let (status, applied) = send(
+        app,
+        Method::POST,
+        &uri,
+        &admin_token,
+        Some(json!({
+            "grades": [
+                { "submissionId": first, "score": 50.0, "feedback": "Good start" },
+                { "submissionId": second, "score": 100.0 },
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{applied}");
+    assert_eq!(applied["graded"].as_array().unwrap().len(), 2);
+    assert_eq!(applied["graded"][0]["pointsAwarded"], 10);
+    assert_eq!(user_points(&pool, first_id).await, 10);
+    assert_eq!(user_points(&pool, second_id).await, 20);
+
+    let feedback: Option<String> =
+        sqlx::query_scalar("SELECT feedback FROM challenge_submissions WHERE id = $1")
+            .bind(first)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(feedback.as_deref(), Some("Good start"));
+}
+
+#[tokio::test]
+async fn exported_grades_can_be_filled_in_and_uploaded() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (graded_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (pending_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    let to_grade = common::create_submission(
+        &pool,
+        graded_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    common::create_submission(
+        &pool,
+        pending_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let request = Request::builder()
+        .uri(format!("/admin/challenges/{challenge_id}/grades/export"))
+        .header(header::AUTHORIZATION, format!("Bearer {admin_token}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let export = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    // Fill in a score and feedback for one student and leave the other blank
+    let mut reader = csv::Reader::from_reader(export.as_ref());
+    let headers = reader.headers().unwrap().clone();
+    let score_column = headers.iter().position(|h| h == "Score").unwrap();
+    let feedback_column = headers.iter().position(|h| h == "Feedback").unwrap();
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&headers).unwrap();
+    for record in reader.records() {
+        let mut fields: Vec<String> = record.unwrap().iter().map(str::to_string).collect();
+        if fields[0] == to_grade.to_string() {
+            fields[score_column] = "70".to_string();
+            fields[feedback_column] = "Nice, but see the comments".to_string();
+        }
+        writer.write_record(&fields).unwrap();
+    }
+    let filled = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+    let upload_uri = format!("/admin/challenges/{challenge_id}/grades/upload");
+    let (status, applied) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &upload_uri,
+        &admin_token,
+        &[],
+        &filled,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{applied}");
+    let graded = applied["graded"].as_array().unwrap();
+    assert_eq!(graded.len(), 1, "rows without a score are skipped");
+    assert_eq!(graded[0]["submissionId"], to_grade.to_string());
+    assert_eq!(graded[0]["pointsAwarded"], 7);
+    assert_eq!(user_points(&pool, graded_id).await, 7);
+
+    let (status, rejected) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &upload_uri,
+        &admin_token,
+        &[],
+        "Submission ID,Score\nnot-a-uuid,50\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(rejected["errors"][0]["row"], 2);
+    assert_eq!(rejected["errors"][0]["message"], "Invalid submission ID");
+
+    let (status, _) = upload_multipart(
+        app,
+        Method::POST,
+        &upload_uri,
+        &admin_token,
+        &[],
+        "Name,Points\nAda,3\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn challenge_points_follow_a_max_points_change() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 80.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(user_points(&pool, student_id).await, 40);
+
+    sqlx::query("UPDATE challenge_notebooks SET max_points = 100 WHERE id = $1")
+        .bind(notebook_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let uri = format!("/admin/challenges/{challenge_id}/points/recompute");
+    let (status, recomputed) = send(app.clone(), Method::POST, &uri, &admin_token, None).await;
+    assert_eq!(status, StatusCode::OK, "{recomputed}");
+    assert_eq!(recomputed["submissionsUpdated"], 1);
+    assert_eq!(recomputed["pointsDelta"], 40);
+    assert_eq!(user_points(&pool, student_id).await, 80);
+
+    let (_, again) = send(app, Method::POST, &uri, &admin_token, None).await;
+    assert_eq!(again["submissionsUpdated"], 0);
+
+    let reasons: Vec<String> = sqlx::query_scalar(
+        "SELECT reason FROM point_transactions WHERE submission_id = $1 ORDER BY created_at",
+    )
+    .bind(submission_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons[1].starts_with("Recomputed:"));
+}
@@ -0,0 +1,303 @@
+#![allow(dead_code)]
+
+use std::sync::{Arc, Once};
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode, header::AUTHORIZATION, header::CONTENT_TYPE},
+};
+use serde_json::{Value, json};
+use sqlx::{PgPool, postgres::PgPoolOptions};
+use tower::ServiceExt;
+use uj_ai_club_backend::{
+    AppState, OAuthConfig, config::Config, grading_service::InMemoryGradingService,
+    live_updates::LiveUpdates, mailer::LogMailer,
+};
+
+static ENV: Once = Once::new();
+
+/// Set the JWT secret from the test configuration before any token is issued
+pub fn init_env() {
+    ENV.call_once(|| uj_ai_club_backend::auth::init_jwt_secret(&test_config().jwt_secret));
+}
+
+/// Connect to `TEST_DATABASE_URL` and apply the migrations.
+/// Returns `None` (and the calling test should return early) when no database is configured.
+pub async fn test_pool() -> Option<PgPool> {
+    init_env();
+
+    let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL is not set; skipping database-backed test");
+        return None;
+    };
+
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .connect(&database_url)
+        .await
+        .expect("failed to connect to TEST_DATABASE_URL");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("failed to run migrations");
+
+    Some(pool)
+}
+
+/// A pool that never connects, for tests that must be rejected before touching the database
+pub fn lazy_pool() -> PgPool {
+    init_env();
+
+    PgPoolOptions::new()
+        .connect_lazy("postgres://unused@localhost/unused")
+        .expect("failed to build lazy pool")
+}
+
+pub fn oauth_config(base_url: &str) -> OAuthConfig {
+    OAuthConfig {
+        client_id: "test-client-id".to_string(),
+        client_secret: "test-client-secret".to_string(),
+        redirect_uri: "http://localhost:3000/auth/google/callback".to_string(),
+        auth_url: format!("{base_url}/auth"),
+        token_url: format!("{base_url}/token"),
+        userinfo_url: format!("{base_url}/userinfo"),
+    }
+}
+
+/// A valid configuration with dummy values, as the app would load it in development
+pub fn test_config() -> Config {
+    test_config_with(&[])
+}
+
+/// [`test_config`] with some settings added or overridden
+pub fn test_config_with(overrides: &[(&str, &str)]) -> Config {
+    let defaults = [
+        ("DATABASE_URL", "postgres://unused@localhost/unused"),
+        ("JWT_SECRET", "integration-test-secret"),
+        ("GOOGLE_CLIENT_ID", "test-client-id"),
+        ("GOOGLE_CLIENT_SECRET", "test-client-secret"),
+        (
+            "GOOGLE_REDIRECT_URI",
+            "http://localhost:3000/auth/google/callback",
+        ),
+    ];
+
+    Config::from_vars(
+        defaults
+            .iter()
+            .chain(overrides)
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+    )
+    .expect("test configuration is valid")
+}
+
+pub fn app_state(pool: PgPool, oauth_config: OAuthConfig) -> AppState {
+    app_state_with_grading(pool, oauth_config, Arc::new(InMemoryGradingService::new()))
+}
+
+/// App state whose grading service calls go to `grading`, for tests that inspect them
+pub fn app_state_with_grading(
+    pool: PgPool,
+    oauth_config: OAuthConfig,
+    grading: Arc<InMemoryGradingService>,
+) -> AppState {
+    let mut config = test_config();
+    config.google = oauth_config;
+
+    AppState {
+        pool,
+        config: Arc::new(config),
+        mailer: Arc::new(LogMailer::new(None)),
+        grading,
+        live_updates: LiveUpdates::new(),
+    }
+}
+
+/// Serve a router on an ephemeral local port and return its base URL
+pub async fn spawn_server(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind stand-in server");
+    let addr = listener
+        .local_addr()
+        .expect("stand-in server has no address");
+
+    tokio::spawn(async move {
+        axum::serve(listener, router)
+            .await
+            .expect("stand-in server failed");
+    });
+
+    format!("http://{addr}")
+}
+
+/// Insert a user with the given role and open a session for them.
+/// Returns the user id and a bearer access token.
+pub async fn create_user_with_role(pool: &PgPool, role: &str) -> (uuid::Uuid, String) {
+    let user_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO users (id, email, full_name, role) VALUES (gen_random_uuid(), $1, 'Test User', $2) RETURNING id",
+    )
+    .bind(format!("{role}-{}@example.com", uuid::Uuid::new_v4()))
+    .bind(role)
+    .fetch_one(pool)
+    .await
+    .expect("failed to insert test user");
+
+    let tokens = uj_ai_club_backend::auth::create_session(pool, user_id)
+        .await
+        .expect("failed to create test session");
+
+    (user_id, tokens.access_token)
+}
+
+/// Send an authenticated JSON request through the router and decode the JSON reply
+pub async fn send(
+    app: Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .header(CONTENT_TYPE, "application/json")
+        .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+const MULTIPART_BOUNDARY: &str = "integration-test-boundary";
+
+/// Send an authenticated multipart form with the given text `fields` and `csv` as its `file`
+/// field, and decode the JSON reply
+pub async fn upload_multipart(
+    app: Router,
+    method: Method,
+    uri: &str,
+    token: &str,
+    fields: &[(&str, &str)],
+    csv: &str,
+) -> (StatusCode, Value) {
+    let mut body = String::new();
+    for (name, value) in fields {
+        body.push_str(&format!(
+            "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        ));
+    }
+    body.push_str(&format!(
+        "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"data.csv\"\r\nContent-Type: text/csv\r\n\r\n{csv}\r\n--{MULTIPART_BOUNDARY}--\r\n"
+    ));
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// Create a visible prediction challenge through the admin API. `settings` override the
+/// defaults: auto grading, two attempts and no dates.
+pub async fn create_prediction_challenge(app: Router, token: &str, settings: Value) -> i32 {
+    let mut body = json!({
+        "title": "Hidden test set",
+        "description": "Predict the labels of the test set",
+        "visible": true,
+        "startDate": null,
+        "endDate": null,
+        "allowedSubmissions": 2,
+        "gradingMode": "auto",
+        "challengeType": "prediction",
+    });
+    if let Value::Object(settings) = settings {
+        body.as_object_mut().unwrap().extend(settings);
+    }
+
+    let (status, created) = send(app, Method::POST, "/admin/challenges", token, Some(body)).await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    assert_eq!(created["item"]["challengeType"], "prediction");
+
+    created["item"]["id"].as_i64().unwrap() as i32
+}
+
+/// Insert a visible challenge with a notebook worth `max_points`.
+/// Returns the challenge id and notebook id.
+pub async fn create_challenge_with_notebook(pool: &PgPool, max_points: i32) -> (i32, i32) {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+
+    let challenge_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO challenges (week, title, description, challenge_url)
+        VALUES (1, $1, 'Integration test challenge', 'https://example.com/challenge')
+        RETURNING id
+        "#,
+    )
+    .bind(format!("Challenge {suffix}"))
+    .fetch_one(pool)
+    .await
+    .expect("failed to insert test challenge");
+
+    let notebook_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO challenge_notebooks (challenge_id, assignment_name, notebook_filename, notebook_path, max_points)
+        VALUES ($1, $2, 'challenge.ipynb', '/tmp/challenge.ipynb', $3)
+        RETURNING id
+        "#,
+    )
+    .bind(challenge_id)
+    .bind(format!("assignment_{suffix}"))
+    .bind(max_points)
+    .fetch_one(pool)
+    .await
+    .expect("failed to insert test notebook");
+
+    (challenge_id, notebook_id)
+}
+
+/// Insert a submission attempt in the given status
+pub async fn create_submission(
+    pool: &PgPool,
+    user_id: uuid::Uuid,
+    challenge_id: i32,
+    notebook_id: i32,
+    attempt_number: i32,
+    status: &str,
+) -> uuid::Uuid {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO challenge_submissions (user_id, challenge_id, notebook_id, attempt_number, status, started_at)
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(challenge_id)
+    .bind(notebook_id)
+    .bind(attempt_number)
+    .bind(status)
+    .fetch_one(pool)
+    .await
+    .expect("failed to insert test submission")
+}
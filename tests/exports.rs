@@ -0,0 +1,251 @@
+mod common;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{HeaderMap, Method, Request, StatusCode, header},
+};
+use tower::ServiceExt;
+use uj_ai_club_backend::create_router;
+
+/// GET an export and return the raw response
+async fn download(app: Router, uri: &str, token: &str) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header(header::AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    (status, headers, body.to_vec())
+}
+
+#[tokio::test]
+async fn challenge_grades_export_as_csv_with_filters() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    sqlx::query("UPDATE users SET university = 'UJ', major = 'Computer Science, AI' WHERE id = $1")
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    common::create_submission(&pool, student_id, challenge_id, notebook_id, 1, "graded").await;
+    common::create_submission(&pool, student_id, challenge_id, notebook_id, 2, "error").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, headers, body) = download(
+        app.clone(),
+        &format!("/admin/challenges/{challenge_id}/grades/export?sort=attemptNumber"),
+        &admin_token,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        headers[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/csv")
+    );
+    assert_eq!(
+        headers[header::CONTENT_DISPOSITION],
+        format!("attachment; filename=\"challenge-{challenge_id}-grades.csv\"")
+    );
+
+    let mut reader = csv::Reader::from_reader(body.as_slice());
+    let headers = reader.headers().unwrap().clone();
+    assert_eq!(&headers[1], "Name");
+    assert_eq!(&headers[6], "Attempt");
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(&rows[0][4], "Computer Science, AI", "commas are quoted");
+    assert_eq!(&rows[0][6], "1");
+    assert_eq!(&rows[0][7], "graded");
+    assert_eq!(&rows[1][6], "2");
+
+    // The list filters narrow the export too
+    let (status, _, body) = download(
+        app,
+        &format!("/admin/submissions/export?challengeId={challenge_id}&status=graded"),
+        &admin_token,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let mut reader = csv::Reader::from_reader(body.as_slice());
+    let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][7], "graded");
+}
+
+#[tokio::test]
+async fn text_that_looks_like_a_formula_is_not_evaluated() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    sqlx::query(
+        "UPDATE users SET full_name = '=HYPERLINK(\"https://evil.example\",\"Ada\")', university = '@UJ', major = '-1+2' WHERE id = $1",
+    )
+    .bind(student_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    sqlx::query("UPDATE challenge_submissions SET feedback = '+2 for the plots' WHERE id = $1")
+        .bind(submission_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _, body) = download(
+        app.clone(),
+        &format!("/admin/challenges/{challenge_id}/grades/export"),
+        &admin_token,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut reader = csv::Reader::from_reader(body.as_slice());
+    let headers = reader.headers().unwrap().clone();
+    let column = |name: &str| headers.iter().position(|h| h == name).unwrap();
+    let row = reader.records().next().unwrap().unwrap();
+    assert_eq!(
+        &row[column("Name")],
+        "'=HYPERLINK(\"https://evil.example\",\"Ada\")"
+    );
+    assert_eq!(&row[column("University")], "'@UJ");
+    assert_eq!(&row[column("Major")], "'-1+2");
+    assert_eq!(&row[column("Feedback")], "'+2 for the plots");
+
+    // Uploading the file back keeps the feedback as it was written
+    let mut fields: Vec<String> = row.iter().map(str::to_string).collect();
+    fields[column("Score")] = "80".to_string();
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&headers).unwrap();
+    writer.write_record(&fields).unwrap();
+    let filled = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    let (status, applied) = common::upload_multipart(
+        app,
+        Method::POST,
+        &format!("/admin/challenges/{challenge_id}/grades/upload"),
+        &admin_token,
+        &[],
+        &filled,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{applied}");
+
+    let feedback: Option<String> =
+        sqlx::query_scalar("SELECT feedback FROM challenge_submissions WHERE id = $1")
+            .bind(submission_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(feedback.as_deref(), Some("+2 for the plots"));
+}
+
+#[tokio::test]
+async fn empty_export_still_has_a_header_row() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, _) = common::create_challenge_with_notebook(&pool, 10).await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _, body) = download(
+        app.clone(),
+        &format!("/admin/challenges/{challenge_id}/grades/export"),
+        &admin_token,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let text = String::from_utf8(body).unwrap();
+    assert_eq!(text.lines().count(), 1);
+    assert!(text.starts_with("Submission ID,Name,Email"));
+
+    let (status, _, _) = download(app, "/admin/challenges/0/grades/export", &admin_token).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[cfg(feature = "xlsx")]
+#[tokio::test]
+async fn grades_export_as_xlsx() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    common::create_submission(&pool, student_id, challenge_id, notebook_id, 1, "graded").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, headers, body) = download(
+        app,
+        &format!("/admin/challenges/{challenge_id}/grades/export?format=xlsx"),
+        &admin_token,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers[header::CONTENT_TYPE],
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    );
+    assert!(body.starts_with(b"PK"), "an XLSX file is a zip archive");
+}
+
+#[tokio::test]
+async fn exports_need_grading_permission_and_a_known_format() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, user_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _, _) = download(app.clone(), "/admin/submissions/export", &user_token).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    for uri in [
+        "/admin/submissions/export?format=pdf",
+        "/admin/submissions/export?sort=password",
+    ] {
+        let (status, _, _) = download(app.clone(), uri, &admin_token).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+    }
+}
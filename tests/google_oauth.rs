@@ -0,0 +1,257 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Form, Json, Router,
+    body::Body,
+    extract::State,
+    http::{
+        Request, StatusCode,
+        header::{COOKIE, LOCATION, SET_COOKIE},
+    },
+    routing::{get, post},
+};
+use base64::Engine;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tower::ServiceExt;
+use uj_ai_club_backend::create_router;
+
+/// Stand-in for Google's token and userinfo endpoints that records the PKCE verifier it receives
+#[derive(Clone, Default)]
+struct FakeGoogle {
+    code_verifier: Arc<Mutex<Option<String>>>,
+    email: String,
+    sub: String,
+}
+
+async fn fake_token(
+    State(fake): State<FakeGoogle>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Json<Value> {
+    *fake.code_verifier.lock().unwrap() = form.get("code_verifier").cloned();
+
+    Json(json!({
+        "access_token": "stand-in-access-token",
+        "token_type": "Bearer",
+        "expires_in": 3600
+    }))
+}
+
+async fn fake_userinfo(State(fake): State<FakeGoogle>) -> Json<Value> {
+    Json(json!({
+        "sub": fake.sub,
+        "email": fake.email,
+        "name": "OAuth Test Student",
+        "picture": null
+    }))
+}
+
+async fn spawn_fake_google(fake: FakeGoogle) -> String {
+    let router = Router::new()
+        .route("/token", post(fake_token))
+        .route("/userinfo", get(fake_userinfo))
+        .with_state(fake);
+
+    common::spawn_server(router).await
+}
+
+async fn get_request(app: Router, uri: &str) -> axum::response::Response {
+    app.oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+/// GET with a `Cookie` header, as the browser sends it back
+async fn get_with_cookie(app: Router, uri: &str, cookie: &str) -> axum::response::Response {
+    app.oneshot(
+        Request::builder()
+            .uri(uri)
+            .header(COOKIE, cookie)
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+}
+
+/// The `name=value` part of a response's `Set-Cookie` header
+fn set_cookie(response: &axum::response::Response) -> String {
+    let header = response.headers()[SET_COOKIE].to_str().unwrap();
+    header.split(';').next().unwrap().to_string()
+}
+
+/// The cookie `google_auth_init` would have set for `state`
+fn state_cookie(state: &str) -> String {
+    format!(
+        "oauth_state={}",
+        hex::encode(Sha256::digest(state.as_bytes()))
+    )
+}
+
+async fn error_message(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let value: Value = serde_json::from_slice(&body).unwrap();
+    value["message"].as_str().unwrap_or_default().to_string()
+}
+
+fn query_param(url: &str, name: &str) -> Option<String> {
+    url::Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+#[tokio::test]
+async fn callback_rejects_missing_state() {
+    let app = create_router(common::app_state(
+        common::lazy_pool(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let response = get_request(app, "/auth/google/callback?code=abc").await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        error_message(response).await,
+        "Missing OAuth state parameter"
+    );
+}
+
+#[tokio::test]
+async fn callback_rejects_unknown_state() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let response = get_with_cookie(
+        app,
+        "/auth/google/callback?code=abc&state=forged",
+        &state_cookie("forged"),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(
+        error_message(response)
+            .await
+            .contains("Invalid or already used")
+    );
+}
+
+#[tokio::test]
+async fn callback_rejects_expired_state() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+
+    let expired_state = format!("expired-{}", uuid::Uuid::new_v4());
+    sqlx::query(
+        "INSERT INTO oauth_states (state, pkce_verifier, expires_at) VALUES ($1, 'verifier', NOW() - INTERVAL '1 minute')",
+    )
+    .bind(&expired_state)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let response = get_with_cookie(
+        app,
+        &format!("/auth/google/callback?code=abc&state={expired_state}"),
+        &state_cookie(&expired_state),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(error_message(response).await.contains("expired"));
+}
+
+#[tokio::test]
+async fn sign_in_round_trip_verifies_state_and_pkce() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+
+    let fake = FakeGoogle {
+        email: format!("oauth-{}@example.com", uuid::Uuid::new_v4()),
+        sub: uuid::Uuid::new_v4().to_string(),
+        ..Default::default()
+    };
+    let google_url = spawn_fake_google(fake.clone()).await;
+    let app = create_router(common::app_state(pool, common::oauth_config(&google_url)));
+
+    // Start the flow: the redirect to Google carries the state and an S256 PKCE challenge
+    let response = get_request(app.clone(), "/auth/google").await;
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    let authorize_url = response.headers()[LOCATION].to_str().unwrap().to_string();
+    assert!(authorize_url.starts_with(&format!("{google_url}/auth")));
+    assert_eq!(
+        query_param(&authorize_url, "code_challenge_method").as_deref(),
+        Some("S256")
+    );
+    let oauth_state = query_param(&authorize_url, "state").expect("state in authorize URL");
+    let code_challenge =
+        query_param(&authorize_url, "code_challenge").expect("code_challenge in authorize URL");
+    let cookie = set_cookie(&response);
+    let set_cookie_header = response.headers()[SET_COOKIE].to_str().unwrap();
+    assert!(set_cookie_header.contains("HttpOnly"));
+    assert!(set_cookie_header.contains("SameSite=Lax"));
+
+    // Another browser cannot finish the flow, and does not use up its state
+    let callback_uri = format!(
+        "/auth/google/callback?code=stand-in-code&state={}",
+        urlencoding::encode(&oauth_state)
+    );
+    let response = get_request(app.clone(), &callback_uri).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(error_message(response).await.contains("another browser"));
+    let response = get_with_cookie(app.clone(), &callback_uri, &state_cookie("other-flow")).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Google redirects the starting browser back with the same state
+    let response = get_with_cookie(app.clone(), &callback_uri, &cookie).await;
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(set_cookie(&response), "oauth_state=");
+    let frontend_url = response.headers()[LOCATION].to_str().unwrap().to_string();
+    // Tokens travel in the fragment, never in the query string
+    let frontend_url = url::Url::parse(&frontend_url).unwrap();
+    assert!(
+        frontend_url
+            .query_pairs()
+            .all(|(key, _)| !key.contains("token"))
+    );
+    let fragment: HashMap<String, String> =
+        url::form_urlencoded::parse(frontend_url.fragment().unwrap().as_bytes())
+            .into_owned()
+            .collect();
+    assert!(fragment.contains_key("token"));
+    assert!(fragment.contains_key("refresh_token"));
+
+    // The verifier sent to the token endpoint matches the challenge from the first leg
+    let code_verifier = fake
+        .code_verifier
+        .lock()
+        .unwrap()
+        .clone()
+        .expect("token endpoint received a code_verifier");
+    let expected_challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .encode(Sha256::digest(code_verifier.as_bytes()));
+    assert_eq!(code_challenge, expected_challenge);
+
+    // A state can only be used once
+    let response = get_with_cookie(app, &callback_uri, &cookie).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn parallel_grades_of_one_submission_credit_points_once() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let grade_uri = format!("/admin/submissions/{submission_id}/grade");
+    let requests: Vec<_> = (0..12)
+        .map(|i| {
+            let app = app.clone();
+            let grade_uri = grade_uri.clone();
+            let admin_token = admin_token.clone();
+            tokio::spawn(async move {
+                send(
+                    app,
+                    Method::POST,
+                    &grade_uri,
+                    &admin_token,
+                    Some(json!({ "score": 40.0 + i as f64 * 5.0 })),
+                )
+                .await
+            })
+        })
+        .collect();
+
+    for request in requests {
+        let (status, body) = request.await.unwrap();
+        assert_eq!(status, StatusCode::OK, "{body}");
+    }
+
+    let points_awarded: i32 =
+        sqlx::query_scalar("SELECT points_awarded FROM challenge_submissions WHERE id = $1")
+            .bind(submission_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let user_points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(student_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let ledger_total: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0) FROM point_transactions WHERE submission_id = $1",
+    )
+    .bind(submission_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(user_points, points_awarded);
+    assert_eq!(ledger_total, points_awarded as i64);
+}
+
+#[tokio::test]
+async fn nbgrader_webhook_marks_latest_attempt_pending() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let first =
+        common::create_submission(&pool, student_id, challenge_id, notebook_id, 1, "graded").await;
+    let second = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        2,
+        "in_progress",
+    )
+    .await;
+
+    let jupyterhub_username = format!("user_{}", student_id.simple());
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(&jupyterhub_username)
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let assignment_name: String =
+        sqlx::query_scalar("SELECT assignment_name FROM challenge_notebooks WHERE id = $1")
+            .bind(notebook_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        "/webhooks/nbgrader/grade",
+        "",
+        Some(json!({
+            "assignmentName": assignment_name,
+            "studentId": jupyterhub_username,
+            "submissionId": "nbgrader-123",
+            "score": 7.5,
+            "maxScore": 10.0
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let statuses: Vec<(uuid::Uuid, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, status, nbgrader_submission_id FROM challenge_submissions WHERE user_id = $1 ORDER BY attempt_number",
+    )
+    .bind(student_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(statuses[0], (first, "graded".to_string(), None));
+    assert_eq!(
+        statuses[1],
+        (
+            second,
+            "grading_pending".to_string(),
+            Some("nbgrader-123".to_string())
+        )
+    );
+}
@@ -0,0 +1,298 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use uj_ai_club_backend::{
+    create_router,
+    grading::{GradingRequest, deliver_grading_job, enqueue_grading_job},
+    grading_service::{GradingServiceCall, InMemoryGradingService},
+    models::ChallengeNotebook,
+};
+
+#[tokio::test]
+async fn submitting_queues_a_grading_job() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(format!("user_{}", student_id.simple()))
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let grading = Arc::new(InMemoryGradingService::new());
+    let app = create_router(common::app_state_with_grading(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+        grading.clone(),
+    ));
+
+    let (status, body) = send(
+        app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/submit"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let (kind, notebook): (String, Option<i32>) =
+        sqlx::query_as("SELECT kind, notebook_id FROM grading_jobs WHERE submission_id = $1")
+            .bind(submission_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!((kind.as_str(), notebook), ("submit", Some(notebook_id)));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_submits_queue_one_grading_job() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(format!("user_{}", student_id.simple()))
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = create_router(common::app_state_with_grading(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+        Arc::new(InMemoryGradingService::new()),
+    ));
+    let uri = format!("/challenges/{challenge_id}/submit");
+
+    let submits: Vec<_> = (0..4)
+        .map(|_| {
+            let (app, uri, token) = (app.clone(), uri.clone(), student_token.clone());
+            tokio::spawn(async move { send(app, Method::POST, &uri, &token, None).await })
+        })
+        .collect();
+    for submit in submits {
+        let (status, body) = submit.await.unwrap();
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["status"], "grading_pending");
+    }
+
+    let jobs: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM grading_jobs WHERE submission_id = $1")
+            .bind(submission_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(jobs, 1);
+}
+
+#[tokio::test]
+async fn failed_deliveries_back_off_and_can_be_retried() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (_, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let notebook: ChallengeNotebook =
+        sqlx::query_as("SELECT * FROM challenge_notebooks WHERE id = $1")
+            .bind(notebook_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let job_id = enqueue_grading_job(
+        &mut pool.acquire().await.unwrap(),
+        &GradingRequest::setup_assignment(&notebook),
+        None,
+        Some(notebook_id),
+    )
+    .await
+    .unwrap();
+
+    let grading = Arc::new(InMemoryGradingService::new());
+    grading.set_unavailable(true);
+
+    deliver_grading_job(&pool, grading.as_ref(), job_id)
+        .await
+        .unwrap();
+
+    let (status, attempts, last_error, retry_scheduled): (String, i32, Option<String>, bool) =
+        sqlx::query_as(
+            "SELECT status, attempts, last_error, next_attempt_at > NOW() FROM grading_jobs WHERE id = $1",
+        )
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+    assert_eq!(attempts, 1);
+    assert!(last_error.is_some());
+    assert!(retry_scheduled);
+
+    // Not due yet, so a second delivery does nothing
+    deliver_grading_job(&pool, grading.as_ref(), job_id)
+        .await
+        .unwrap();
+    let attempts: i32 = sqlx::query_scalar("SELECT attempts FROM grading_jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(attempts, 1);
+
+    // Out of attempts: the job gives up
+    sqlx::query("UPDATE grading_jobs SET max_attempts = 2, next_attempt_at = NOW() WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    deliver_grading_job(&pool, grading.as_ref(), job_id)
+        .await
+        .unwrap();
+
+    let app = create_router(common::app_state_with_grading(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+        grading.clone(),
+    ));
+
+    let (status, queue) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/grading-jobs?status=failed",
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(queue["failed"].as_i64().unwrap() >= 1);
+    let failed = queue["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["id"] == job_id.to_string())
+        .expect("failed job listed");
+    assert_eq!(failed["attempts"], 2);
+    assert_eq!(failed["kind"], "setup_assignment");
+
+    let (status, retried) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/grading-jobs/{job_id}/retry"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{retried}");
+    assert_eq!(retried["item"]["attempts"], 3);
+    assert_eq!(retried["item"]["status"], "failed");
+
+    // Once the service is back, retrying delivers the job
+    grading.set_unavailable(false);
+    let (status, retried) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/grading-jobs/{job_id}/retry"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retried["item"]["status"], "succeeded");
+    assert!(retried["item"]["lastError"].is_null());
+    assert_eq!(
+        grading.calls(),
+        vec![GradingServiceCall::SetupAssignment {
+            assignment_name: notebook.assignment_name.clone(),
+            max_points: 100,
+        }]
+    );
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        &format!("/admin/grading-jobs/{job_id}/retry"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn admins_read_submitted_notebooks_from_the_grading_service() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, grader_token) = common::create_user_with_role(&pool, "grader").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 100).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let jupyterhub_username = format!("user_{}", student_id.simple());
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(&jupyterhub_username)
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let assignment_name: String =
+        sqlx::query_scalar("SELECT assignment_name FROM challenge_notebooks WHERE id = $1")
+            .bind(notebook_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    let grading = Arc::new(InMemoryGradingService::new());
+    let app = create_router(common::app_state_with_grading(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+        grading.clone(),
+    ));
+    let file_uri = format!("/admin/submissions/{submission_id}/file");
+
+    // Not collected yet
+    let (status, _) = send(app.clone(), Method::GET, &file_uri, &grader_token, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    grading.put_submitted_notebook(
+        &jupyterhub_username,
+        &assignment_name,
+        br#"{"cells": []}"#.to_vec(),
+    );
+    let (status, notebook) = send(app, Method::GET, &file_uri, &grader_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(notebook["cells"], serde_json::json!([]));
+}
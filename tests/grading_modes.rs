@@ -0,0 +1,194 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uj_ai_club_backend::create_router;
+
+/// A student with an in-progress attempt on a fresh challenge using `grading_mode`.
+/// Returns the student's id, the submission id and the notebook's assignment name.
+async fn attempt_in_mode(pool: &PgPool, grading_mode: &str) -> (uuid::Uuid, uuid::Uuid, String) {
+    let (student_id, _) = common::create_user_with_role(pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(pool, 80).await;
+    sqlx::query("UPDATE challenges SET grading_mode = $1 WHERE id = $2")
+        .bind(grading_mode)
+        .bind(challenge_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(format!("user_{}", student_id.simple()))
+        .bind(student_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let submission_id = common::create_submission(
+        pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    let assignment_name: String =
+        sqlx::query_scalar("SELECT assignment_name FROM challenge_notebooks WHERE id = $1")
+            .bind(notebook_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    (student_id, submission_id, assignment_name)
+}
+
+async fn report_score(pool: &PgPool, student_id: uuid::Uuid, assignment_name: &str) -> Value {
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/webhooks/nbgrader/grade",
+        "",
+        Some(json!({
+            "assignmentName": assignment_name,
+            "studentId": format!("user_{}", student_id.simple()),
+            "score": 7.5,
+            "maxScore": 10.0
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    body
+}
+
+#[tokio::test]
+async fn auto_mode_credits_converted_points() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, submission_id, assignment_name) = attempt_in_mode(&pool, "auto").await;
+
+    let body = report_score(&pool, student_id, &assignment_name).await;
+    assert_eq!(body["pointsAwarded"], 60);
+
+    let (status, points_awarded, points_credited): (String, i32, bool) = sqlx::query_as(
+        "SELECT status, points_awarded, points_credited FROM challenge_submissions WHERE id = $1",
+    )
+    .bind(submission_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        (status.as_str(), points_awarded, points_credited),
+        ("graded", 60, true)
+    );
+
+    let (user_points, rank): (i32, Option<i32>) =
+        sqlx::query_as("SELECT points, rank FROM users WHERE id = $1")
+            .bind(student_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(user_points, 60);
+    assert!(rank.is_some());
+
+    let ledger: (i32, Option<uuid::Uuid>) = sqlx::query_as(
+        "SELECT amount, created_by FROM point_transactions WHERE submission_id = $1",
+    )
+    .bind(submission_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(ledger, (60, None));
+}
+
+#[tokio::test]
+async fn auto_with_review_proposes_points_without_crediting() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, submission_id, assignment_name) =
+        attempt_in_mode(&pool, "auto_with_review").await;
+
+    let body = report_score(&pool, student_id, &assignment_name).await;
+    assert_eq!(body["pointsAwarded"], 0);
+
+    let (status, points_awarded, points_credited): (String, i32, bool) = sqlx::query_as(
+        "SELECT status, points_awarded, points_credited FROM challenge_submissions WHERE id = $1",
+    )
+    .bind(submission_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        (status.as_str(), points_awarded, points_credited),
+        ("grading_pending", 60, false)
+    );
+
+    let user_points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(student_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(user_points, 0);
+}
+
+#[tokio::test]
+async fn admin_sets_and_validates_grading_mode() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, created) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Auto graded",
+            "description": "d",
+            "startDate": null,
+            "endDate": null
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    assert_eq!(created["item"]["gradingMode"], "manual");
+
+    let (status, updated) = send(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/challenges/{}", created["item"]["id"]),
+        &admin_token,
+        Some(json!({ "gradingMode": "auto", "startDate": null, "endDate": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["item"]["gradingMode"], "auto");
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Bad",
+            "description": "d",
+            "startDate": null,
+            "endDate": null,
+            "gradingMode": "sometimes"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,285 @@
+mod common;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use common::send;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uj_ai_club_backend::create_router;
+
+/// Move the challenge's end date `hours` from now (negative for the past)
+async fn set_end_date(pool: &PgPool, challenge_id: i32, hours: i32) {
+    sqlx::query(
+        "UPDATE challenges SET visible = true, end_date = NOW() + make_interval(hours => $1) WHERE id = $2",
+    )
+    .bind(hours)
+    .bind(challenge_id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn set_late_policy(app: Router, token: &str, challenge_id: i32, policy: Value) -> Value {
+    let (status, body) = send(
+        app,
+        Method::PUT,
+        &format!("/admin/challenges/{challenge_id}"),
+        token,
+        Some(json!({ "latePolicy": policy, "startDate": null, "endDate": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    body
+}
+
+#[tokio::test]
+async fn late_penalties_are_applied_when_grading_and_recomputing() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    set_end_date(&pool, challenge_id, -48).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    // Submitted a day and an hour after the end date
+    sqlx::query(
+        r#"
+        UPDATE challenge_submissions
+        SET submitted_at = (SELECT end_date FROM challenges WHERE id = $2) + INTERVAL '25 hours'
+        WHERE id = $1
+        "#,
+    )
+    .bind(submission_id)
+    .bind(challenge_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let updated = set_late_policy(
+        app.clone(),
+        &admin_token,
+        challenge_id,
+        json!({ "policy": "stepped", "penaltyPercent": 10, "penaltyPeriod": "day" }),
+    )
+    .await;
+    assert_eq!(updated["item"]["latePolicy"]["policy"], "stepped");
+    assert_eq!(updated["item"]["latePolicy"]["graceMinutes"], 0);
+
+    // Two started days late cost 20% of the 40 points the score is worth
+    let (status, graded) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 80 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{graded}");
+    assert_eq!(graded["item"]["pointsAwarded"], 32);
+    assert_eq!(graded["item"]["late"]["minutesLate"], 25 * 60);
+    assert_eq!(graded["item"]["late"]["penaltyPercent"], 20.0);
+    assert_eq!(graded["item"]["late"]["practiceOnly"], false);
+
+    let (status, submission) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(submission["pointsAwarded"], 32);
+    assert_eq!(submission["late"]["penaltyPercent"], 20.0);
+
+    // Switching the challenge to practice-only takes the points back on recompute
+    set_late_policy(
+        app.clone(),
+        &admin_token,
+        challenge_id,
+        json!({ "policy": "practice_only" }),
+    )
+    .await;
+    let (status, recomputed) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/challenges/{challenge_id}/points/recompute"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{recomputed}");
+    assert_eq!(recomputed["submissionsUpdated"], 1);
+    assert_eq!(recomputed["pointsDelta"], -32);
+
+    let (_, submission) = send(
+        app,
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(submission["pointsAwarded"], 0);
+    assert_eq!(submission["score"], 80.0);
+    assert_eq!(submission["late"]["penaltyPercent"], 100.0);
+    assert_eq!(submission["late"]["practiceOnly"], true);
+
+    let points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(student_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(points, 0);
+}
+
+#[tokio::test]
+async fn ended_challenges_accept_attempts_only_as_the_policy_allows() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, _) = common::create_challenge_with_notebook(&pool, 10).await;
+    set_end_date(&pool, challenge_id, -1).await;
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let start_path = format!("/challenges/{challenge_id}/start");
+
+    let (status, body) = send(app.clone(), Method::POST, &start_path, &student_token, None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Challenge has ended");
+
+    // A two hour grace window reopens it, and the attempt cannot outlast the window
+    set_late_policy(
+        app.clone(),
+        &admin_token,
+        challenge_id,
+        json!({ "policy": "grace", "graceMinutes": 120 }),
+    )
+    .await;
+    let (status, started) =
+        send(app.clone(), Method::POST, &start_path, &student_token, None).await;
+    assert_eq!(status, StatusCode::OK, "{started}");
+    let (deadline_at, closes_at): (time::OffsetDateTime, time::OffsetDateTime) = sqlx::query_as(
+        r#"
+        SELECT cs.deadline_at, c.end_date + INTERVAL '2 hours'
+        FROM challenge_submissions cs
+        JOIN challenges c ON c.id = cs.challenge_id
+        WHERE cs.user_id = $1 AND cs.challenge_id = $2
+        "#,
+    )
+    .bind(student_id)
+    .bind(challenge_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(deadline_at <= closes_at);
+
+    let (status, submitted) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/challenges/{challenge_id}/submit"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{submitted}");
+    assert!(submitted["late"]["minutesLate"].as_i64().unwrap() >= 60);
+    assert_eq!(submitted["late"]["penaltyPercent"], 0.0);
+
+    // An untimed attempt left open past a cutoff can no longer be submitted
+    let (cutoff_challenge_id, cutoff_notebook_id) =
+        common::create_challenge_with_notebook(&pool, 10).await;
+    set_end_date(&pool, cutoff_challenge_id, -1).await;
+    common::create_submission(
+        &pool,
+        student_id,
+        cutoff_challenge_id,
+        cutoff_notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    let (status, body) = send(
+        app,
+        Method::POST,
+        &format!("/challenges/{cutoff_challenge_id}/submit"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Challenge has ended");
+}
+
+#[tokio::test]
+async fn invalid_late_policies_are_rejected() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, _) = common::create_challenge_with_notebook(&pool, 10).await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    for policy in [
+        json!({ "policy": "whenever" }),
+        json!({ "policy": "grace" }),
+        json!({ "policy": "linear", "penaltyPercent": 0 }),
+        json!({ "policy": "linear", "penaltyPercent": 150 }),
+        json!({ "policy": "stepped", "penaltyPercent": 5, "penaltyPeriod": "week" }),
+        json!({ "policy": "cutoff", "graceMinutes": -5 }),
+    ] {
+        let (status, body) = send(
+            app.clone(),
+            Method::PUT,
+            &format!("/admin/challenges/{challenge_id}"),
+            &admin_token,
+            Some(json!({ "latePolicy": policy, "startDate": null, "endDate": null })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{policy}: {body}");
+    }
+
+    let (status, created) = send(
+        app,
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Late policy challenge",
+            "description": "Late work loses 5% an hour",
+            "visible": false,
+            "startDate": null,
+            "endDate": null,
+            "latePolicy": { "policy": "linear", "penaltyPercent": 5, "penaltyPeriod": "hour" },
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    assert_eq!(created["item"]["latePolicy"]["policy"], "linear");
+    assert_eq!(created["item"]["latePolicy"]["penaltyPeriod"], "hour");
+}
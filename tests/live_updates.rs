@@ -0,0 +1,197 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use common::send;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uj_ai_club_backend::{
+    AppState, create_router,
+    live_updates::{LIVE_UPDATES_CHANNEL, LiveUpdate, LiveUpdates, spawn_live_update_listener},
+};
+
+/// A backend instance with its own connection listening for live updates
+async fn instance(pool: &PgPool) -> Router {
+    let live_updates = LiveUpdates::new();
+    spawn_live_update_listener(pool.clone(), live_updates.clone());
+
+    // The listener connects in the background; wait until notifications reach it
+    let mut probe = live_updates.subscribe();
+    let ping = LiveUpdate::Leaderboard { challenge_id: -1 };
+    let ready = async {
+        loop {
+            sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(LIVE_UPDATES_CHANNEL)
+                .bind(r#"{"kind":"leaderboard","challenge_id":-1}"#)
+                .execute(pool)
+                .await
+                .unwrap();
+            let received = tokio::time::timeout(Duration::from_millis(100), probe.recv()).await;
+            if matches!(received, Ok(Ok(ref update)) if *update == ping) {
+                return;
+            }
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), ready)
+        .await
+        .expect("live update listener did not start");
+
+    create_router(AppState {
+        live_updates,
+        ..common::app_state(pool.clone(), common::oauth_config("http://127.0.0.1:9"))
+    })
+}
+
+/// Reads server-sent events off a streaming response
+struct EventReader {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl EventReader {
+    async fn open(base_url: &str, path: &str, token: &str) -> Self {
+        let response = reqwest::Client::new()
+            .get(format!("{base_url}{path}"))
+            .bearer_auth(token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/event-stream",
+            "{path}"
+        );
+
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// The next event's name and JSON data, skipping keep-alive comments
+    async fn next(&mut self) -> (String, Value) {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                let field = |name: &str| {
+                    block
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .map(|value| value.trim_start().to_string())
+                };
+                if let (Some(event), Some(data)) = (field("event:"), field("data:")) {
+                    return (event, serde_json::from_str(&data).unwrap());
+                }
+                continue;
+            }
+
+            let chunk = tokio::time::timeout(Duration::from_secs(10), self.response.chunk())
+                .await
+                .expect("no event within 10 seconds")
+                .unwrap()
+                .expect("stream ended");
+            self.buffer.push_str(&String::from_utf8_lossy(&chunk));
+        }
+    }
+}
+
+#[tokio::test]
+async fn streams_require_auth_and_an_existing_challenge() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/challenges/1/submission/stream",
+        "not-a-token",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        app,
+        Method::GET,
+        "/challenges/2147483647/submission/stream",
+        &token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn grading_is_pushed_to_the_student_and_leaderboard_watchers() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, watcher_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+
+    // Watchers are connected to one instance while the grade lands on another
+    let watched = common::spawn_server(instance(&pool).await).await;
+    let grading_instance = instance(&pool).await;
+
+    let mut submission = EventReader::open(
+        &watched,
+        &format!("/challenges/{challenge_id}/submission/stream"),
+        &student_token,
+    )
+    .await;
+    let mut leaderboard = EventReader::open(
+        &watched,
+        &format!("/challenges/{challenge_id}/leaderboard/stream"),
+        &watcher_token,
+    )
+    .await;
+
+    let (event, current) = submission.next().await;
+    assert_eq!(event, "submission");
+    assert_eq!(current["status"], "grading_pending");
+    let (event, standings) = leaderboard.next().await;
+    assert_eq!(event, "leaderboard");
+    assert_eq!(standings, json!([]));
+
+    let (status, _) = send(
+        grading_instance,
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 80.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (event, graded) = submission.next().await;
+    assert_eq!(event, "submission");
+    assert_eq!(graded["id"], submission_id.to_string());
+    assert_eq!(graded["status"], "graded");
+    assert_eq!(graded["pointsAwarded"], 40);
+
+    let (_, standings) = leaderboard.next().await;
+    assert_eq!(standings[0]["user_id"], student_id.to_string());
+    assert_eq!(standings[0]["points_awarded"], 40);
+}
@@ -0,0 +1,231 @@
+mod common;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use common::send;
+use serde_json::{Value, json};
+use uj_ai_club_backend::create_router;
+
+/// The user's notifications about `key` = `id`; other tests notify everyone concurrently
+async fn notifications_about(app: Router, token: &str, key: &str, id: &Value) -> Vec<Value> {
+    let (status, body) = send(app, Method::GET, "/users/notifications", token, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    body["notifications"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|notification| &notification["data"][key] == id)
+        .cloned()
+        .collect()
+}
+
+#[tokio::test]
+async fn graded_submission_notifies_the_student() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, other_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    // Announcements from other tests would otherwise land in this student's counts
+    let (status, preferences) = send(
+        app.clone(),
+        Method::PUT,
+        "/users/notifications/preferences",
+        &student_token,
+        Some(json!({ "challengePublished": false, "certificatePublished": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        preferences,
+        json!({
+            "submissionGraded": true,
+            "challengePublished": false,
+            "certificatePublished": false
+        })
+    );
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 80.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let notifications = notifications_about(
+        app.clone(),
+        &student_token,
+        "submissionId",
+        &json!(submission_id),
+    )
+    .await;
+    assert_eq!(notifications.len(), 1);
+    let notification = &notifications[0];
+    assert_eq!(notification["kind"], "submission_graded");
+    assert_eq!(notification["data"]["challengeId"], challenge_id);
+    assert!(notification["body"].as_str().unwrap().contains("40 points"));
+    assert!(notification["readAt"].is_null());
+
+    let (_, unread) = send(
+        app.clone(),
+        Method::GET,
+        "/users/notifications/unread-count",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(unread["count"], 1);
+
+    // Only the owner can mark it read
+    let read_path = format!(
+        "/users/notifications/{}/read",
+        notification["id"].as_str().unwrap()
+    );
+    let (status, _) = send(app.clone(), Method::POST, &read_path, &other_token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, read) = send(app.clone(), Method::POST, &read_path, &student_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(read["readAt"].is_string());
+
+    let (_, unread) = send(
+        app.clone(),
+        Method::GET,
+        "/users/notifications?unread=true",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(unread["notifications"], json!([]));
+    assert_eq!(unread["unreadCount"], 0);
+
+    // Regrading notifies again; marking all read clears it
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 100.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, cleared) = send(
+        app.clone(),
+        Method::POST,
+        "/users/notifications/read-all",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cleared["updated"], 1);
+}
+
+#[tokio::test]
+async fn published_challenge_notifies_members_who_opted_in() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, member_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, opted_out_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, preferences) = send(
+        app.clone(),
+        Method::GET,
+        "/users/notifications/preferences",
+        &member_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(preferences["challengePublished"], true);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        "/users/notifications/preferences",
+        &opted_out_token,
+        Some(json!({ "challengePublished": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, created) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Notified challenge",
+            "description": "Announced on publish",
+            "visible": false,
+            "startDate": null,
+            "endDate": null,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let challenge_id = created["item"]["id"].clone();
+
+    assert!(
+        notifications_about(app.clone(), &member_token, "challengeId", &challenge_id)
+            .await
+            .is_empty()
+    );
+
+    for visible in [true, false] {
+        let (status, _) = send(
+            app.clone(),
+            Method::PATCH,
+            &format!("/admin/challenges/{challenge_id}/visibility"),
+            &admin_token,
+            Some(json!({ "visible": visible })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let notifications =
+        notifications_about(app.clone(), &member_token, "challengeId", &challenge_id).await;
+    assert_eq!(notifications.len(), 1, "hiding it again does not notify");
+    assert_eq!(notifications[0]["kind"], "challenge_published");
+    assert!(
+        notifications[0]["body"]
+            .as_str()
+            .unwrap()
+            .contains("Notified challenge")
+    );
+    assert!(
+        notifications_about(app, &opted_out_token, "challengeId", &challenge_id)
+            .await
+            .is_empty()
+    );
+}
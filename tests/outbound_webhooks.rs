@@ -0,0 +1,353 @@
+mod common;
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{
+    Router,
+    body::Bytes,
+    http::{HeaderMap, Method, StatusCode},
+    routing::post,
+};
+use common::send;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uj_ai_club_backend::{
+    create_router,
+    outbound_webhooks::{EVENT_HEADER, process_due_webhook_deliveries, webhook_client},
+    signing::{self, DELIVERY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+
+const SECRET: &str = "discord-bot-shared-secret";
+
+#[derive(Clone, Default)]
+struct Receiver {
+    received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    /// Answer this many requests with a 500 before accepting
+    failures_left: Arc<AtomicUsize>,
+}
+
+impl Receiver {
+    /// Serve the stand-in integration and return its webhook URL
+    async fn spawn(&self) -> String {
+        let receiver = self.clone();
+        let router = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| {
+                let receiver = receiver.clone();
+                async move {
+                    receiver.received.lock().unwrap().push((headers, body));
+                    let failing = receiver
+                        .failures_left
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                        .is_ok();
+                    if failing {
+                        (StatusCode::INTERNAL_SERVER_ERROR, "bot is restarting")
+                    } else {
+                        (StatusCode::OK, "ok")
+                    }
+                }
+            }),
+        );
+
+        format!("{}/hook", common::spawn_server(router).await)
+    }
+
+    /// Requests received so far whose event matches `filter`, with the parsed event
+    fn events(&self, filter: impl Fn(&Value) -> bool) -> Vec<(HeaderMap, Bytes, Value)> {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(headers, body)| {
+                let event = serde_json::from_slice(body).unwrap();
+                (headers.clone(), body.clone(), event)
+            })
+            .filter(|(_, _, event)| filter(event))
+            .collect()
+    }
+}
+
+async fn subscribe(app: Router, admin_token: &str, url: &str, event_types: &[&str]) -> String {
+    let (status, body) = send(
+        app,
+        Method::POST,
+        "/admin/webhooks",
+        admin_token,
+        Some(json!({
+            "name": "Discord bot",
+            "url": url,
+            "secret": SECRET,
+            "eventTypes": event_types,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(body["item"].get("secret").is_none());
+
+    body["item"]["id"].as_str().unwrap().to_string()
+}
+
+/// Run the worker until `done` holds; other tests' deliveries may be due at the same time
+async fn deliver_until(pool: &PgPool, done: impl Fn() -> bool) {
+    let client = webhook_client();
+    for _ in 0..20 {
+        process_due_webhook_deliveries(pool, &client).await.unwrap();
+        if done() {
+            return;
+        }
+    }
+    panic!("webhook was not delivered");
+}
+
+#[tokio::test]
+async fn subscriptions_are_validated_and_restricted_to_admins() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (_, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let valid = json!({
+        "name": "Bot",
+        "url": "https://bot.example.com/hook",
+        "secret": SECRET,
+        "eventTypes": ["submission.graded"],
+    });
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/webhooks",
+        &student_token,
+        Some(valid.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    for (field, value) in [
+        ("eventTypes", json!(["submission.deleted"])),
+        ("eventTypes", json!([])),
+        ("secret", json!("short")),
+        ("url", json!("ftp://bot.example.com")),
+    ] {
+        let mut invalid = valid.clone();
+        invalid[field] = value;
+        let (status, body) = send(
+            app.clone(),
+            Method::POST,
+            "/admin/webhooks",
+            &admin_token,
+            Some(invalid),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{field}: {body}");
+    }
+}
+
+#[tokio::test]
+async fn published_challenge_is_delivered_signed() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let receiver = Receiver::default();
+    let url = receiver.spawn().await;
+    let subscription_id =
+        subscribe(app.clone(), &admin_token, &url, &["challenge.published"]).await;
+
+    let (status, created) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/challenges",
+        &admin_token,
+        Some(json!({
+            "title": "Webhook draft",
+            "description": "Hidden until published",
+            "visible": false,
+            "startDate": null,
+            "endDate": null,
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let challenge_id = created["item"]["id"].as_i64().unwrap();
+
+    let queued: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM outbound_webhook_deliveries WHERE subscription_id = $1::UUID",
+    )
+    .bind(&subscription_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(queued, 0, "hidden challenges are not announced");
+
+    let (status, _) = send(
+        app.clone(),
+        Method::PATCH,
+        &format!("/admin/challenges/{challenge_id}/visibility"),
+        &admin_token,
+        Some(json!({ "visible": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let is_ours = |event: &Value| event["data"]["challengeId"] == challenge_id;
+    deliver_until(&pool, || !receiver.events(is_ours).is_empty()).await;
+
+    let events = receiver.events(is_ours);
+    assert_eq!(events.len(), 1);
+    let (headers, raw_body, event) = &events[0];
+    assert_eq!(event["type"], "challenge.published");
+    assert_eq!(event["data"]["title"], "Webhook draft");
+    assert_eq!(headers[EVENT_HEADER], "challenge.published");
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    assert!(
+        signing::verify(
+            SECRET,
+            header(SIGNATURE_HEADER),
+            header(TIMESTAMP_HEADER),
+            header(DELIVERY_HEADER),
+            raw_body,
+            now
+        )
+        .is_ok()
+    );
+
+    let (status, log) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/admin/webhooks/{subscription_id}/deliveries"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let delivery = &log["items"][0];
+    assert_eq!(delivery["status"], "succeeded");
+    assert_eq!(delivery["responseStatus"], 200);
+    assert_eq!(delivery["id"].as_str(), header(DELIVERY_HEADER));
+
+    let (status, _) = send(
+        app,
+        Method::DELETE,
+        &format!("/admin/webhooks/{subscription_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn failed_deliveries_are_retried() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let receiver = Receiver::default();
+    receiver.failures_left.store(1, Ordering::SeqCst);
+    let url = receiver.spawn().await;
+    let subscription_id = subscribe(app.clone(), &admin_token, &url, &["submission.graded"]).await;
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({ "score": 80.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let is_ours = |event: &Value| event["data"]["submissionId"] == submission_id.to_string();
+    deliver_until(&pool, || !receiver.events(is_ours).is_empty()).await;
+
+    let (delivery_id, status, attempts, response_status, last_error): (
+        uuid::Uuid,
+        String,
+        i32,
+        Option<i32>,
+        Option<String>,
+    ) = sqlx::query_as(
+        "SELECT id, status, attempts, response_status, last_error FROM outbound_webhook_deliveries WHERE subscription_id = $1::UUID",
+    )
+    .bind(&subscription_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(status, "pending");
+    assert_eq!(attempts, 1);
+    assert_eq!(response_status, Some(500));
+    assert!(last_error.unwrap().contains("bot is restarting"));
+
+    // Retrying makes it due straight away instead of after the backoff
+    let (status, retried) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/webhook-deliveries/{delivery_id}/retry"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{retried}");
+    deliver_until(&pool, || receiver.events(is_ours).len() == 2).await;
+
+    let events = receiver.events(is_ours);
+    assert_eq!(
+        events[0].2["id"], events[1].2["id"],
+        "retries resend the same event"
+    );
+    assert_eq!(events[1].2["data"]["pointsAwarded"], 40);
+
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM outbound_webhook_deliveries WHERE id = $1")
+            .bind(delivery_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "succeeded");
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        &format!("/admin/webhook-deliveries/{delivery_id}/retry"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    sqlx::query("DELETE FROM webhook_subscriptions WHERE id = $1::UUID")
+        .bind(&subscription_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+}
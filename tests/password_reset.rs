@@ -0,0 +1,305 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    async_trait,
+    http::{Method, StatusCode},
+};
+use common::send;
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use uj_ai_club_backend::{
+    AppState, create_router,
+    mailer::{EmailMessage, Mailer, SmtpMailer},
+};
+
+/// Mailer that keeps every message in memory
+#[derive(Clone, Default)]
+struct RecordingMailer {
+    sent: Arc<Mutex<Vec<EmailMessage>>>,
+}
+
+impl RecordingMailer {
+    /// The latest message, waiting for mail sent after the response
+    async fn next_message(&self) -> Option<EmailMessage> {
+        for _ in 0..100 {
+            if let Some(message) = self.sent.lock().unwrap().pop() {
+                return Some(message);
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+
+        None
+    }
+}
+
+#[async_trait]
+impl Mailer for RecordingMailer {
+    async fn send(&self, message: EmailMessage) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+/// Minimal SMTP server that accepts every message and records the DATA section
+async fn spawn_fake_smtp() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let inbox = received.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let inbox = inbox.clone();
+
+            tokio::spawn(async move {
+                let (reader, mut writer) = socket.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let command = line.to_ascii_uppercase();
+                    let reply: &[u8] = if command.starts_with("DATA") {
+                        writer
+                            .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                            .await
+                            .unwrap();
+                        let mut data = String::new();
+                        while let Ok(Some(data_line)) = lines.next_line().await {
+                            if data_line == "." {
+                                break;
+                            }
+                            data.push_str(&data_line);
+                            data.push('\n');
+                        }
+                        inbox.lock().unwrap().push(data);
+                        b"250 OK\r\n"
+                    } else if command.starts_with("QUIT") {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    } else {
+                        b"250 OK\r\n"
+                    };
+                    writer.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (format!("smtp://{addr}"), received)
+}
+
+fn token_from_link(body: &str) -> String {
+    body.split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .expect("email contains a token link")
+        .to_string()
+}
+
+#[tokio::test]
+async fn smtp_mailer_delivers_to_stand_in_server() {
+    let (smtp_url, received) = spawn_fake_smtp().await;
+    let mailer = SmtpMailer::new(&smtp_url, "UJ AI Club <no-reply@example.com>").unwrap();
+
+    mailer
+        .send(EmailMessage {
+            to: "student@example.com".to_string(),
+            subject: "Reset your password".to_string(),
+            body: "Open the link to continue".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    assert!(received[0].contains("To: student@example.com"));
+    assert!(received[0].contains("Subject: Reset your password"));
+    assert!(received[0].contains("Open the link to continue"));
+}
+
+#[tokio::test]
+async fn password_reset_flow_replaces_password_and_revokes_sessions() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+
+    let email = format!("reset-{}@example.com", uuid::Uuid::new_v4());
+    let old_hash = bcrypt::hash("old-password", 4).unwrap();
+    sqlx::query(
+        "INSERT INTO users (id, email, password_hash, full_name) VALUES (gen_random_uuid(), $1, $2, 'Reset Tester')",
+    )
+    .bind(&email)
+    .bind(&old_hash)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mailer = RecordingMailer::default();
+    let app = create_router(AppState {
+        mailer: Arc::new(mailer.clone()),
+        ..common::app_state(pool, common::oauth_config("http://127.0.0.1:9"))
+    });
+
+    let (status, login) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/login",
+        "",
+        Some(json!({ "email": email, "password": "old-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let old_access_token = login["token"].as_str().unwrap().to_string();
+
+    // Unknown addresses get the same answer and no email
+    let (status, unknown) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/password-reset/request",
+        "",
+        Some(json!({ "email": "nobody@example.com" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(mailer.sent.lock().unwrap().is_empty());
+
+    let (status, known) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/password-reset/request",
+        "",
+        Some(json!({ "email": email })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(known, unknown);
+
+    let reset_email = mailer.next_message().await.expect("reset email sent");
+    assert_eq!(reset_email.to, email);
+    let token = token_from_link(&reset_email.body);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/password-reset/confirm",
+        "",
+        Some(json!({ "token": token, "newPassword": "new-password-123" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The token is single-use
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/password-reset/confirm",
+        "",
+        Some(json!({ "token": token, "newPassword": "another-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Sessions opened with the old password are gone
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/logout",
+        &old_access_token,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/login",
+        "",
+        Some(json!({ "email": email, "password": "old-password" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        "/auth/login",
+        "",
+        Some(json!({ "email": email, "password": "new-password-123" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn email_verification_marks_account_verified() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+
+    let email = format!("verify-{}@example.com", uuid::Uuid::new_v4());
+    let password_hash = bcrypt::hash("password-123", 4).unwrap();
+    let user_id: uuid::Uuid = sqlx::query_scalar(
+        "INSERT INTO users (id, email, password_hash, full_name) VALUES (gen_random_uuid(), $1, $2, 'Verify Tester') RETURNING id",
+    )
+    .bind(&email)
+    .bind(&password_hash)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let mailer = RecordingMailer::default();
+    let app = create_router(AppState {
+        mailer: Arc::new(mailer.clone()),
+        ..common::app_state(pool.clone(), common::oauth_config("http://127.0.0.1:9"))
+    });
+
+    let (_, login) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/login",
+        "",
+        Some(json!({ "email": email, "password": "password-123" })),
+    )
+    .await;
+    let access_token = login["token"].as_str().unwrap().to_string();
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        "/auth/verify-email/request",
+        &access_token,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let verification_email = mailer
+        .sent
+        .lock()
+        .unwrap()
+        .pop()
+        .expect("verification email sent");
+    let token = token_from_link(&verification_email.body);
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        "/auth/verify-email/confirm",
+        "",
+        Some(json!({ "token": token })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let verified: bool =
+        sqlx::query_scalar("SELECT email_verified_at IS NOT NULL FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(verified);
+}
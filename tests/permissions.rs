@@ -0,0 +1,143 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn grader_only_reaches_submission_routes() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, token) = common::create_user_with_role(&pool, "grader").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(app.clone(), Method::GET, "/admin/submissions", &token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    for uri in [
+        "/admin/challenges",
+        "/admin/resources",
+        "/admin/certificates",
+        "/admin/roles",
+    ] {
+        let (status, _) = send(app.clone(), Method::GET, uri, &token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn content_editor_manages_resources_and_certificates_only() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, token) = common::create_user_with_role(&pool, "content_editor").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    for uri in ["/admin/resources", "/admin/certificates"] {
+        let (status, _) = send(app.clone(), Method::GET, uri, &token, None).await;
+        assert_eq!(status, StatusCode::OK, "{uri}");
+    }
+
+    for uri in [
+        "/admin/submissions",
+        "/admin/challenges",
+        "/admin/notebooks",
+    ] {
+        let (status, _) = send(app.clone(), Method::GET, uri, &token, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn admin_assigns_roles_and_they_apply_immediately() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (admin_id, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/submissions",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, roles) = send(app.clone(), Method::GET, "/admin/roles", &admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let grader = roles["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|role| role["name"] == "grader")
+        .expect("grader role listed");
+    assert_eq!(grader["permissions"], json!(["grade_submissions"]));
+
+    let (status, updated) = send(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/users/{student_id}/role"),
+        &admin_token,
+        Some(json!({ "role": "grader" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["item"]["role"], "grader");
+
+    // The existing session picks up the new role without signing in again
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/submissions",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/users/{student_id}/role"),
+        &admin_token,
+        Some(json!({ "role": "wizard" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/users/{admin_id}/role"),
+        &admin_token,
+        Some(json!({ "role": "user" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Graders cannot hand out roles themselves
+    let (status, _) = send(
+        app,
+        Method::PUT,
+        &format!("/admin/users/{admin_id}/role"),
+        &student_token,
+        Some(json!({ "role": "user" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn grading_and_adjustments_are_recorded_in_the_ledger() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 200).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let grade_uri = format!("/admin/submissions/{submission_id}/grade");
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &grade_uri,
+        &admin_token,
+        Some(json!({ "score": 80.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A regrade only records the difference
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &grade_uri,
+        &admin_token,
+        Some(json!({ "score": 50.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/points"),
+        &admin_token,
+        Some(json!({ "delta": 5, "reason": "Helped at the workshop" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, history) = send(
+        app,
+        Method::GET,
+        "/users/points/history",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["points"], 105);
+
+    let transactions = history["transactions"].as_array().unwrap();
+    let amounts: Vec<i64> = transactions
+        .iter()
+        .map(|t| t["amount"].as_i64().unwrap())
+        .collect();
+    assert_eq!(amounts.iter().sum::<i64>(), 105);
+    assert!(amounts.contains(&160));
+    assert!(amounts.contains(&-60));
+    assert!(amounts.contains(&5));
+
+    let regrade = transactions.iter().find(|t| t["amount"] == -60).unwrap();
+    assert_eq!(regrade["source"], "submission");
+    assert_eq!(regrade["submissionId"], submission_id.to_string());
+    assert_eq!(regrade["challengeId"], challenge_id);
+    assert!(regrade["reason"].as_str().unwrap().starts_with("Regraded"));
+
+    let adjustment = transactions.iter().find(|t| t["amount"] == 5).unwrap();
+    assert_eq!(adjustment["source"], "admin");
+    assert_eq!(adjustment["reason"], "Helped at the workshop");
+}
+
+#[tokio::test]
+async fn recompute_restores_points_from_the_ledger() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/users/{student_id}/points"),
+        &admin_token,
+        Some(json!({ "delta": 40, "reason": "Hackathon winner" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Simulate drift from an out-of-band update
+    sqlx::query("UPDATE users SET points = 999 WHERE id = $1")
+        .bind(student_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, result) = send(
+        app.clone(),
+        Method::POST,
+        "/admin/points/recompute",
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(result["usersUpdated"].as_u64().unwrap() >= 1);
+
+    let points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(student_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(points, 40);
+
+    let student_token = uj_ai_club_backend::auth::create_session(&pool, student_id)
+        .await
+        .unwrap()
+        .access_token;
+    let (status, _) = send(
+        app,
+        Method::POST,
+        "/admin/points/recompute",
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
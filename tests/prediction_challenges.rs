@@ -0,0 +1,319 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::{create_prediction_challenge, send, upload_multipart};
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn predictions_are_scored_against_the_hidden_test_set() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let challenge_id = create_prediction_challenge(app.clone(), &admin_token, json!({})).await;
+    let task_path = format!("/admin/challenges/{challenge_id}/prediction");
+    let predictions_path = format!("/challenges/{challenge_id}/predictions");
+
+    let (status, body) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &predictions_path,
+        &student_token,
+        &[],
+        "id,target\n1,cat\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (status, task) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &task_path,
+        &admin_token,
+        &[
+            ("metric", "accuracy"),
+            ("baseline", "0.5"),
+            ("maxPoints", "40"),
+        ],
+        "id,target\n1,cat\n2,dog\n3,cat\n4,dog\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{task}");
+    assert_eq!(task["item"]["metric"], "accuracy");
+    assert_eq!(task["item"]["rowCount"], 4);
+
+    let (status, task) = send(app.clone(), Method::GET, &task_path, &admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["item"]["maxPoints"], 40);
+    assert!(task["item"].get("groundTruth").is_none());
+
+    // Files that do not line up with the test set are rejected without using an attempt
+    for csv in [
+        "id,target\n1,cat\n2,dog\n3,cat\n",
+        "id,target\n1,cat\n2,dog\n3,cat\n4,dog\n5,cat\n",
+        "id,target\n1,cat\n1,dog\n3,cat\n4,dog\n",
+        "id,label\n1,cat\n2,dog\n3,cat\n4,dog\n",
+    ] {
+        let (status, body) = upload_multipart(
+            app.clone(),
+            Method::POST,
+            &predictions_path,
+            &student_token,
+            &[],
+            csv,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{csv}: {body}");
+    }
+
+    // Three of four right is 0.75, halfway from the 0.5 baseline to perfect
+    let (status, scored) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &predictions_path,
+        &student_token,
+        &[],
+        "ID,Target\n4,dog\n3,cat\n2,dog\n1,dog\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{scored}");
+    assert_eq!(scored["attemptNumber"], 1);
+    assert_eq!(scored["status"], "graded");
+    assert_eq!(scored["metricValue"], 0.75);
+    assert_eq!(scored["score"], 50.0);
+    assert_eq!(scored["pointsAwarded"], 20);
+    assert_eq!(scored["attemptsRemaining"], 1);
+
+    let (status, submission) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{submission}");
+    assert_eq!(submission["metricValue"], 0.75);
+    assert_eq!(submission["pointsAwarded"], 20);
+
+    let (status, perfect) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &predictions_path,
+        &student_token,
+        &[],
+        "id,target\n1,cat\n2,dog\n3,cat\n4,dog\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{perfect}");
+    assert_eq!(perfect["pointsAwarded"], 40);
+
+    let (status, body) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &predictions_path,
+        &student_token,
+        &[],
+        "id,target\n1,cat\n2,dog\n3,cat\n4,dog\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Submission limit reached for this challenge (2 attempts)"
+    );
+
+    // Stored scores come from this test set, so it is fixed once students have submitted
+    let (status, body) = upload_multipart(
+        app,
+        Method::PUT,
+        &task_path,
+        &admin_token,
+        &[("metric", "accuracy")],
+        "id,target\n1,dog\n2,cat\n3,dog\n4,cat\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+
+    let points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+        .bind(student_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(points, 60);
+}
+
+#[tokio::test]
+async fn regression_and_probability_metrics_score_natively() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (_, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    // RMSE against a baseline of 2: an error of 1 everywhere halves it
+    let challenge_id = create_prediction_challenge(
+        app.clone(),
+        &admin_token,
+        json!({ "gradingMode": "manual" }),
+    )
+    .await;
+    let (status, task) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/challenges/{challenge_id}/prediction"),
+        &admin_token,
+        &[
+            ("metric", "rmse"),
+            ("baseline", "2"),
+            ("targetColumn", "price"),
+        ],
+        "id,price\na,10\nb,20\nc,30\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{task}");
+    let (status, scored) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &format!("/challenges/{challenge_id}/predictions"),
+        &student_token,
+        &[],
+        "id,price\na,11\nb,19\nc,31\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{scored}");
+    assert_eq!(scored["metricValue"], 1.0);
+    assert_eq!(scored["score"], 50.0);
+    // Manual grading leaves the score for an admin to confirm
+    assert_eq!(scored["status"], "grading_pending");
+    assert_eq!(scored["pointsAwarded"], 0);
+
+    // AUC ranks the positives above the negatives but for one tied pair
+    let challenge_id = create_prediction_challenge(app.clone(), &admin_token, json!({})).await;
+    let (status, task) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/challenges/{challenge_id}/prediction"),
+        &admin_token,
+        &[("metric", "auc")],
+        "id,target\n1,0\n2,0\n3,1\n4,1\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{task}");
+    let (status, scored) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &format!("/challenges/{challenge_id}/predictions"),
+        &student_token,
+        &[],
+        "id,target\n1,0.1\n2,0.6\n3,0.6\n4,0.9\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{scored}");
+    assert_eq!(scored["metricValue"], 0.875);
+
+    // Probabilities outside [0, 1] are not a valid submission
+    let (status, body) = upload_multipart(
+        app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/predictions"),
+        &student_token,
+        &[],
+        "id,target\n1,0.1\n2,0.6\n3,1.6\n4,0.9\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[tokio::test]
+async fn ground_truth_uploads_are_validated() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (_, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let challenge_id = create_prediction_challenge(app.clone(), &admin_token, json!({})).await;
+    let task_path = format!("/admin/challenges/{challenge_id}/prediction");
+
+    for (fields, csv) in [
+        (vec![("metric", "precision")], "id,target\n1,0\n2,1\n"),
+        (vec![("metric", "log_loss")], "id,target\n1,0\n2,0\n"),
+        (vec![("metric", "mae")], "id,target\n1,low\n2,high\n"),
+        (
+            vec![("metric", "rmse"), ("baseline", "0")],
+            "id,target\n1,1\n",
+        ),
+        (vec![("metric", "accuracy")], "id,target\n"),
+    ] {
+        let (status, body) = upload_multipart(
+            app.clone(),
+            Method::PUT,
+            &task_path,
+            &admin_token,
+            &fields,
+            csv,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{fields:?}: {body}");
+    }
+
+    let (status, _) = send(app.clone(), Method::GET, &task_path, &admin_token, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Notebook challenges neither take a test set nor accept predictions
+    let (notebook_challenge_id, _) = common::create_challenge_with_notebook(&pool, 10).await;
+    sqlx::query("UPDATE challenges SET visible = true WHERE id = $1")
+        .bind(notebook_challenge_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, body) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/challenges/{notebook_challenge_id}/prediction"),
+        &admin_token,
+        &[("metric", "accuracy")],
+        "id,target\n1,cat\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Only prediction challenges take a ground truth file"
+    );
+    let (status, _) = upload_multipart(
+        app.clone(),
+        Method::POST,
+        &format!("/challenges/{notebook_challenge_id}/predictions"),
+        &student_token,
+        &[],
+        "id,target\n1,cat\n",
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Prediction challenges cannot be started as notebooks either
+    let (status, _) = send(
+        app,
+        Method::POST,
+        &format!("/challenges/{challenge_id}/start"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
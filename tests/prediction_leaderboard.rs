@@ -0,0 +1,377 @@
+mod common;
+
+use std::collections::HashSet;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use common::{create_prediction_challenge, send, upload_multipart};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uj_ai_club_backend::{
+    create_router,
+    prediction::{public_ids, read_prediction_csv},
+    prediction_leaderboard::reveal_private_leaderboard,
+};
+
+const TRUTH: &str = "id,target\n1,0\n2,1\n3,0\n4,1\n5,0\n6,1\n7,0\n8,1\n9,0\n10,1\n";
+
+/// A prediction challenge with three attempts that ends tomorrow
+async fn create_split_challenge(app: Router, pool: &PgPool, token: &str) -> i32 {
+    let challenge_id =
+        create_prediction_challenge(app, token, json!({ "allowedSubmissions": 3 })).await;
+
+    sqlx::query("UPDATE challenges SET end_date = NOW() + INTERVAL '1 day' WHERE id = $1")
+        .bind(challenge_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    challenge_id
+}
+
+/// The ground truth with the first `wrong_public` public and `wrong_private` private labels flipped
+fn predictions(public: &HashSet<&str>, wrong_public: usize, wrong_private: usize) -> String {
+    let mut csv = "id,target\n".to_string();
+    let (mut flipped_public, mut flipped_private) = (0, 0);
+    for line in TRUTH.lines().skip(1) {
+        let (id, label) = line.split_once(',').unwrap();
+        let flip = if public.contains(id) {
+            flipped_public += 1;
+            flipped_public <= wrong_public
+        } else {
+            flipped_private += 1;
+            flipped_private <= wrong_private
+        };
+        let label = match (label, flip) {
+            ("0", true) => "1",
+            ("1", true) => "0",
+            (label, _) => label,
+        };
+        csv.push_str(&format!("{id},{label}\n"));
+    }
+
+    csv
+}
+
+#[tokio::test]
+async fn private_leaderboard_is_revealed_from_final_submissions() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (careful_id, careful_token) = common::create_user_with_role(&pool, "user").await;
+    let (overfit_id, overfit_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let challenge_id = create_split_challenge(app.clone(), &pool, &admin_token).await;
+    let predictions_path = format!("/challenges/{challenge_id}/predictions");
+    let finals_path = format!("/challenges/{challenge_id}/final-submissions");
+    let leaderboard_path = format!("/challenges/{challenge_id}/leaderboard");
+
+    let (status, task) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &format!("/admin/challenges/{challenge_id}/prediction"),
+        &admin_token,
+        &[
+            ("metric", "accuracy"),
+            ("maxPoints", "10"),
+            ("publicPercent", "50"),
+            ("finalSubmissions", "1"),
+        ],
+        TRUTH,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{task}");
+    assert_eq!(task["item"]["publicPercent"], 50);
+
+    let truth = read_prediction_csv(TRUTH.as_bytes(), "id", "target").unwrap();
+    let public = public_ids(challenge_id, &truth, 50);
+    assert_eq!(public.len(), 5);
+
+    // One student's best public attempt does worse in private than their second one
+    let mut careful_attempts = Vec::new();
+    for csv in [predictions(&public, 1, 5), predictions(&public, 5, 0)] {
+        let (status, scored) = upload_multipart(
+            app.clone(),
+            Method::POST,
+            &predictions_path,
+            &careful_token,
+            &[],
+            &csv,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{scored}");
+        careful_attempts.push(scored);
+    }
+    assert_eq!(careful_attempts[0]["publicMetricValue"], 0.8);
+    assert_eq!(careful_attempts[0]["metricValue"], Value::Null);
+    assert_eq!(careful_attempts[0]["score"], Value::Null);
+    assert_eq!(careful_attempts[0]["pointsAwarded"], 0);
+
+    // Grading by hand would credit and announce the hidden private result
+    let (status, body) = send(
+        app.clone(),
+        Method::POST,
+        &format!(
+            "/admin/submissions/{}/grade",
+            careful_attempts[0]["submissionId"].as_str().unwrap()
+        ),
+        &admin_token,
+        Some(json!({ "score": 100 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    // The other tops the public rows and misses every private one, then tries the opposite
+    for csv in [
+        predictions(&public, 1, 1),
+        predictions(&public, 0, 5),
+        predictions(&public, 5, 0),
+    ] {
+        let (status, scored) = upload_multipart(
+            app.clone(),
+            Method::POST,
+            &predictions_path,
+            &overfit_token,
+            &[],
+            &csv,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{scored}");
+    }
+
+    let (status, board) = send(
+        app.clone(),
+        Method::GET,
+        &leaderboard_path,
+        &careful_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(board[0]["leaderboard"], "public");
+    assert_eq!(board[0]["user_id"], json!(overfit_id));
+    assert_eq!(board[0]["metric_value"], 1.0);
+    assert_eq!(board[1]["user_id"], json!(careful_id));
+    assert_eq!(board[1]["challenge_rank"], 2);
+    assert_eq!(board[1]["points_awarded"], 0);
+
+    let (status, submission) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &careful_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(submission["publicMetricValue"], 0.0);
+    assert_eq!(submission["metricValue"], Value::Null);
+
+    // Without a choice the best public attempt counts; the careful student picks the other
+    let (status, finals) = send(app.clone(), Method::GET, &finals_path, &careful_token, None).await;
+    assert_eq!(status, StatusCode::OK, "{finals}");
+    assert_eq!(finals["finalSubmissions"], 1);
+    assert_eq!(finals["attempts"][0]["final"], true);
+    assert_eq!(finals["attempts"][1]["final"], false);
+
+    let first = careful_attempts[0]["submissionId"].clone();
+    let second = careful_attempts[1]["submissionId"].clone();
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &finals_path,
+        &careful_token,
+        Some(json!({ "submissionIds": [first, second] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &finals_path,
+        &overfit_token,
+        Some(json!({ "submissionIds": [second] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, finals) = send(
+        app.clone(),
+        Method::PUT,
+        &finals_path,
+        &careful_token,
+        Some(json!({ "submissionIds": [second] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{finals}");
+    assert_eq!(finals["attempts"][0]["final"], false);
+    assert_eq!(finals["attempts"][1]["selected"], true);
+    assert_eq!(finals["attempts"][1]["final"], true);
+    assert_eq!(finals["attempts"][1]["metricValue"], Value::Null);
+
+    // Once the challenge ends the private ranking shows, with the shake-up
+    sqlx::query(
+        "UPDATE challenge_submissions SET submitted_at = submitted_at - INTERVAL '1 hour' WHERE challenge_id = $1",
+    )
+    .bind(challenge_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE challenges SET end_date = NOW() - INTERVAL '1 minute' WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (_, board) = send(
+        app.clone(),
+        Method::GET,
+        &leaderboard_path,
+        &careful_token,
+        None,
+    )
+    .await;
+    assert_eq!(board[0]["leaderboard"], "private");
+    assert_eq!(board[0]["user_id"], json!(careful_id));
+    assert_eq!(board[0]["metric_value"], 1.0);
+    assert_eq!(board[0]["public_rank"], 2);
+    assert_eq!(board[0]["rank_change"], 1);
+    assert_eq!(board[1]["user_id"], json!(overfit_id));
+    assert_eq!(board[1]["metric_value"], 0.0);
+    assert_eq!(board[1]["rank_change"], -1);
+
+    let (status, body) = send(
+        app.clone(),
+        Method::PUT,
+        &finals_path,
+        &careful_token,
+        Some(json!({ "submissionIds": [first] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["message"],
+        "Final submissions are fixed once the challenge has ended"
+    );
+
+    // Points for the counted attempts are credited once, when the board is revealed
+    assert!(
+        reveal_private_leaderboard(&pool, challenge_id)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !reveal_private_leaderboard(&pool, challenge_id)
+            .await
+            .unwrap()
+    );
+
+    for (user_id, expected) in [(careful_id, 10), (overfit_id, 0)] {
+        let points: i32 = sqlx::query_scalar("SELECT points FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(points, expected);
+    }
+
+    let (_, submission) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &careful_token,
+        None,
+    )
+    .await;
+    assert_eq!(submission["metricValue"], 1.0);
+    assert_eq!(submission["pointsAwarded"], 10);
+
+    // An attempt that did not count shows its private score but no points
+    let (_, submission) = send(
+        app,
+        Method::GET,
+        &format!("/challenges/{challenge_id}/submission"),
+        &overfit_token,
+        None,
+    )
+    .await;
+    assert_eq!(submission["attemptNumber"], 3);
+    assert_eq!(submission["metricValue"], 1.0);
+    assert_eq!(submission["pointsAwarded"], 0);
+}
+
+#[tokio::test]
+async fn splits_need_an_end_date_and_a_valid_share() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (_, student_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let challenge_id = create_split_challenge(app.clone(), &pool, &admin_token).await;
+    let task_path = format!("/admin/challenges/{challenge_id}/prediction");
+
+    for fields in [
+        [("metric", "accuracy"), ("publicPercent", "0")],
+        [("metric", "accuracy"), ("publicPercent", "100")],
+        [("metric", "accuracy"), ("finalSubmissions", "0")],
+    ] {
+        let (status, body) = upload_multipart(
+            app.clone(),
+            Method::PUT,
+            &task_path,
+            &admin_token,
+            &fields,
+            TRUTH,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{fields:?}: {body}");
+    }
+
+    // Without a split there is no private leaderboard to choose attempts for
+    let (status, _) = upload_multipart(
+        app.clone(),
+        Method::PUT,
+        &task_path,
+        &admin_token,
+        &[("metric", "accuracy")],
+        TRUTH,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/challenges/{challenge_id}/final-submissions"),
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "This challenge has no private leaderboard");
+
+    sqlx::query("UPDATE challenges SET end_date = NULL WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = upload_multipart(
+        app,
+        Method::PUT,
+        &task_path,
+        &admin_token,
+        &[("metric", "accuracy"), ("publicPercent", "30")],
+        TRUTH,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
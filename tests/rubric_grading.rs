@@ -0,0 +1,239 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use common::send;
+use serde_json::json;
+use uj_ai_club_backend::create_router;
+
+#[tokio::test]
+async fn rubric_grade_and_feedback_reach_the_student_once_graded() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    sqlx::query("UPDATE challenge_submissions SET feedback = 'Draft' WHERE id = $1")
+        .bind(submission_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let rubric_uri = format!("/admin/challenges/{challenge_id}/rubric");
+    let (status, rubric) = send(
+        app.clone(),
+        Method::PUT,
+        &rubric_uri,
+        &admin_token,
+        Some(json!({
+            "criteria": [
+                { "title": "Model quality", "weight": 2.0, "maxPoints": 10.0 },
+                { "title": "Write-up", "maxPoints": 5.0, "description": "Clear explanation" },
+            ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{rubric}");
+    let criteria = rubric["items"].as_array().unwrap();
+    assert_eq!(criteria.len(), 2);
+    assert_eq!(criteria[1]["weight"], 1.0);
+    let quality_id = criteria[0]["id"].clone();
+    let write_up_id = criteria[1]["id"].clone();
+
+    // Nothing is released before the grade
+    let submission_uri = format!("/challenges/{challenge_id}/submission");
+    let (_, pending) = send(
+        app.clone(),
+        Method::GET,
+        &submission_uri,
+        &student_token,
+        None,
+    )
+    .await;
+    assert!(pending["feedback"].is_null());
+    assert!(pending["rubric"].is_null());
+
+    let (status, graded) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/submissions/{submission_id}/grade"),
+        &admin_token,
+        Some(json!({
+            "feedback": "  Solid model, the write-up is excellent.  ",
+            "rubric": [
+                { "criterionId": quality_id, "points": 5.0, "comment": "Overfits" },
+                { "criterionId": write_up_id, "points": 5.0 },
+            ],
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{graded}");
+    // (2 * 5/10 + 1 * 5/5) / 3 of 100, and that share of the notebook's 50 points
+    assert_eq!(graded["item"]["score"], 66.67);
+    assert_eq!(graded["item"]["pointsAwarded"], 33);
+
+    let (_, released) = send(
+        app.clone(),
+        Method::GET,
+        &submission_uri,
+        &student_token,
+        None,
+    )
+    .await;
+    assert_eq!(released["status"], "graded");
+    assert_eq!(
+        released["feedback"],
+        "Solid model, the write-up is excellent."
+    );
+    let breakdown = released["rubric"].as_array().unwrap();
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown[0]["title"], "Model quality");
+    assert_eq!(breakdown[0]["points"], 5.0);
+    assert_eq!(breakdown[0]["maxPoints"], 10.0);
+    assert_eq!(breakdown[0]["comment"], "Overfits");
+    assert!(breakdown[1]["comment"].is_null());
+
+    // Editing the rubric later leaves the recorded breakdown alone
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &rubric_uri,
+        &admin_token,
+        Some(json!({ "criteria": [{ "title": "Everything", "maxPoints": 1.0 }] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, after_edit) = send(app, Method::GET, &submission_uri, &student_token, None).await;
+    assert_eq!(after_edit["rubric"][0]["title"], "Model quality");
+    assert_eq!(after_edit["score"], 66.67);
+}
+
+#[tokio::test]
+async fn rubric_scores_are_validated() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, _) = common::create_user_with_role(&pool, "user").await;
+    let (_, grader_token) = common::create_user_with_role(&pool, "grader").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    let submission_id = common::create_submission(
+        &pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "grading_pending",
+    )
+    .await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+    let rubric_uri = format!("/admin/challenges/{challenge_id}/rubric");
+    let grade_uri = format!("/admin/submissions/{submission_id}/grade");
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &grade_uri,
+        &grader_token,
+        Some(json!({ "rubric": [] })),
+    )
+    .await;
+    assert_eq!(
+        status,
+        StatusCode::BAD_REQUEST,
+        "no rubric to score against"
+    );
+
+    // Graders read the rubric but only challenge managers change it
+    let criteria = json!({ "criteria": [{ "title": "Accuracy", "maxPoints": 10.0 }] });
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &rubric_uri,
+        &grader_token,
+        Some(criteria.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    for invalid in [
+        json!({ "criteria": [{ "title": " ", "maxPoints": 10.0 }] }),
+        json!({ "criteria": [{ "title": "Accuracy", "maxPoints": 0.0 }] }),
+        json!({ "criteria": [{ "title": "Accuracy", "maxPoints": 1.0, "weight": -1.0 }] }),
+    ] {
+        let (status, _) = send(
+            app.clone(),
+            Method::PUT,
+            &rubric_uri,
+            &admin_token,
+            Some(invalid.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid}");
+    }
+    let (status, _) = send(
+        app.clone(),
+        Method::PUT,
+        &rubric_uri,
+        &admin_token,
+        Some(criteria),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, rubric) = send(app.clone(), Method::GET, &rubric_uri, &grader_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let criterion_id = rubric["items"][0]["id"].as_i64().unwrap();
+
+    for invalid in [
+        json!({ "score": 80.0, "rubric": [{ "criterionId": criterion_id, "points": 8.0 }] }),
+        json!({ "feedback": "No grade" }),
+        json!({ "rubric": [] }),
+        json!({ "rubric": [{ "criterionId": criterion_id, "points": 11.0 }] }),
+        json!({ "rubric": [
+            { "criterionId": criterion_id, "points": 8.0 },
+            { "criterionId": criterion_id, "points": 9.0 },
+        ] }),
+        json!({ "rubric": [
+            { "criterionId": criterion_id, "points": 8.0 },
+            { "criterionId": 0, "points": 1.0 },
+        ] }),
+    ] {
+        let (status, _) = send(
+            app.clone(),
+            Method::POST,
+            &grade_uri,
+            &grader_token,
+            Some(invalid.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid}");
+    }
+
+    // A plain score still works on a challenge with a rubric
+    let (status, graded) = send(
+        app,
+        Method::POST,
+        &grade_uri,
+        &grader_token,
+        Some(json!({ "score": 90.0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(graded["item"]["rubric"].is_null());
+    assert!(graded["item"]["feedback"].is_null());
+}
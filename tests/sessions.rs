@@ -0,0 +1,120 @@
+mod common;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use serde_json::json;
+use uj_ai_club_backend::{auth::create_session, create_router};
+
+async fn refresh(app: Router, refresh_token: &str) -> (StatusCode, serde_json::Value) {
+    common::send(
+        app,
+        Method::POST,
+        "/auth/refresh",
+        "",
+        Some(json!({ "refreshToken": refresh_token })),
+    )
+    .await
+}
+
+async fn authorised(app: Router, access_token: &str) -> StatusCode {
+    let (status, _) =
+        common::send(app, Method::GET, "/users/notifications", access_token, None).await;
+    status
+}
+
+#[tokio::test]
+async fn refresh_rotates_the_token_and_reuse_revokes_the_session() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (user_id, _) = common::create_user_with_role(&pool, "user").await;
+    let session = create_session(&pool, user_id).await.unwrap();
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, rotated) = refresh(app.clone(), &session.refresh_token).await;
+    assert_eq!(status, StatusCode::OK, "{rotated}");
+    let refresh_token = rotated["refreshToken"].as_str().unwrap().to_string();
+    let access_token = rotated["token"].as_str().unwrap().to_string();
+    assert_ne!(refresh_token, session.refresh_token);
+    assert_eq!(authorised(app.clone(), &access_token).await, StatusCode::OK);
+
+    // Presenting the rotated token again means it was copied: the whole session ends
+    let (status, _) = refresh(app.clone(), &session.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = refresh(app.clone(), &refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        authorised(app.clone(), &access_token).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        authorised(app, &session.access_token).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn logout_revokes_one_session_and_logout_all_every_session() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (user_id, _) = common::create_user_with_role(&pool, "user").await;
+    let laptop = create_session(&pool, user_id).await.unwrap();
+    let phone = create_session(&pool, user_id).await.unwrap();
+    let tablet = create_session(&pool, user_id).await.unwrap();
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, body) = common::send(
+        app.clone(),
+        Method::POST,
+        "/auth/logout",
+        &laptop.access_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["success"], true);
+
+    // Only the session that signed out is revoked, refresh token included
+    assert_eq!(
+        authorised(app.clone(), &laptop.access_token).await,
+        StatusCode::UNAUTHORIZED
+    );
+    let (status, _) = refresh(app.clone(), &laptop.refresh_token).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        authorised(app.clone(), &phone.access_token).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        authorised(app.clone(), &tablet.access_token).await,
+        StatusCode::OK
+    );
+
+    let (status, _) = common::send(
+        app.clone(),
+        Method::POST,
+        "/auth/logout-all",
+        &phone.access_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for session in [&phone, &tablet] {
+        assert_eq!(
+            authorised(app.clone(), &session.access_token).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let (status, _) = refresh(app.clone(), &session.refresh_token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
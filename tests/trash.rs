@@ -0,0 +1,309 @@
+mod common;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use common::send;
+use serde_json::{Value, json};
+use uj_ai_club_backend::create_router;
+
+/// The trash entry for `id`, if it is in the `kind` trash
+async fn trashed(app: Router, token: &str, kind: &str, id: i32) -> Option<Value> {
+    let (status, body) = send(
+        app,
+        Method::GET,
+        &format!("/admin/{kind}/trash"),
+        token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["id"] == id)
+        .cloned()
+}
+
+#[tokio::test]
+async fn deleted_challenge_keeps_graded_work_until_purged_with_force() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (student_id, student_token) = common::create_user_with_role(&pool, "user").await;
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 50).await;
+    sqlx::query("UPDATE challenges SET visible = true WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    common::create_submission(&pool, student_id, challenge_id, notebook_id, 1, "graded").await;
+
+    let notebook_path =
+        std::env::temp_dir().join(format!("trash-test-{}.ipynb", uuid::Uuid::new_v4()));
+    std::fs::write(&notebook_path, "{}").unwrap();
+    sqlx::query("UPDATE challenge_notebooks SET notebook_path = $1 WHERE id = $2")
+        .bind(notebook_path.to_str().unwrap())
+        .bind(notebook_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::DELETE,
+        &format!("/admin/challenges/{challenge_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Gone from every view, but the submission is still there
+    let (_, challenges) = send(
+        app.clone(),
+        Method::GET,
+        "/challenges",
+        &student_token,
+        None,
+    )
+    .await;
+    assert!(
+        !challenges
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c["id"] == challenge_id)
+    );
+    let (_, admin_challenges) = send(
+        app.clone(),
+        Method::GET,
+        "/admin/challenges?includeHidden=true",
+        &admin_token,
+        None,
+    )
+    .await;
+    assert!(
+        !admin_challenges["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c["id"] == challenge_id)
+    );
+    for path in [
+        format!("/admin/challenges/{challenge_id}"),
+        format!("/challenges/{challenge_id}/submission"),
+    ] {
+        let token = if path.starts_with("/admin") {
+            &admin_token
+        } else {
+            &student_token
+        };
+        let (status, _) = send(app.clone(), Method::GET, &path, token, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{path}");
+    }
+    let (status, _) = send(
+        app.clone(),
+        Method::DELETE,
+        &format!("/admin/challenges/{challenge_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "already in the trash");
+
+    let entry = trashed(app.clone(), &admin_token, "challenges", challenge_id)
+        .await
+        .expect("deleted challenge is in the trash");
+    assert_eq!(entry["submissions"], 1);
+    assert_eq!(entry["gradedSubmissions"], 1);
+    assert!(!entry["deletedAt"].is_null());
+
+    // Purging would destroy graded work, so it needs force
+    let purge_path = format!("/admin/challenges/{challenge_id}/purge");
+    let (status, refused) =
+        send(app.clone(), Method::DELETE, &purge_path, &admin_token, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(refused["message"].as_str().unwrap().contains("force=true"));
+    assert!(notebook_path.exists());
+
+    let (status, purged) = send(
+        app.clone(),
+        Method::DELETE,
+        &format!("{purge_path}?force=true"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{purged}");
+    assert_eq!(purged["submissionsDeleted"], 1);
+    assert!(!notebook_path.exists(), "the notebook file is removed");
+
+    let remaining: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM challenge_submissions WHERE challenge_id = $1")
+            .bind(challenge_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, 0);
+    assert!(
+        trashed(app.clone(), &admin_token, "challenges", challenge_id)
+            .await
+            .is_none()
+    );
+
+    let (status, _) = send(
+        app,
+        Method::POST,
+        &format!("/admin/challenges/{challenge_id}/restore"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn restored_items_come_back() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, admin_token) = common::create_user_with_role(&pool, "admin").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(&pool, 10).await;
+    let resource_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO resources (title, provider, instructor_name, visible)
+        VALUES ('Trashed resource', 'Provider', 'Instructor', true)
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let app = create_router(common::app_state(
+        pool.clone(),
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    let (status, _) = send(
+        app.clone(),
+        Method::DELETE,
+        &format!("/admin/resources/{resource_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/resources/{resource_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(
+        app.clone(),
+        Method::POST,
+        &format!("/admin/resources/{resource_id}/restore"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, resource) = send(
+        app.clone(),
+        Method::GET,
+        &format!("/resources/{resource_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(resource["title"], "Trashed resource");
+
+    // A trashed notebook cannot come back once its challenge has a new one
+    let (status, _) = send(
+        app.clone(),
+        Method::DELETE,
+        &format!("/admin/notebooks/{notebook_id}"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let entry = trashed(app.clone(), &admin_token, "notebooks", notebook_id)
+        .await
+        .expect("deleted notebook is in the trash");
+    assert_eq!(entry["gradedSubmissions"], 0);
+
+    let replacement_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO challenge_notebooks (challenge_id, assignment_name, notebook_filename, notebook_path, max_points)
+        SELECT challenge_id, assignment_name, notebook_filename, notebook_path, max_points
+        FROM challenge_notebooks
+        WHERE id = $1
+        RETURNING id
+        "#,
+    )
+    .bind(notebook_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let restore_path = format!("/admin/notebooks/{notebook_id}/restore");
+    let (status, _) = send(app.clone(), Method::POST, &restore_path, &admin_token, None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    sqlx::query("DELETE FROM challenge_notebooks WHERE id = $1")
+        .bind(replacement_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, _) = send(app.clone(), Method::POST, &restore_path, &admin_token, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, notebook) = send(
+        app,
+        Method::GET,
+        &format!("/admin/challenges/{challenge_id}/notebook"),
+        &admin_token,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(notebook["item"]["id"], notebook_id);
+}
+
+#[tokio::test]
+async fn trash_requires_the_manage_permission() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (_, user_token) = common::create_user_with_role(&pool, "user").await;
+    let app = create_router(common::app_state(
+        pool,
+        common::oauth_config("http://127.0.0.1:9"),
+    ));
+
+    for (method, path) in [
+        (Method::GET, "/admin/challenges/trash"),
+        (Method::GET, "/admin/certificates/trash"),
+        (Method::POST, "/admin/resources/1/restore"),
+        (Method::DELETE, "/admin/notebooks/1/purge?force=true"),
+    ] {
+        let (status, _) = send(app.clone(), method, path, &user_token, Some(json!({}))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{path}");
+    }
+}
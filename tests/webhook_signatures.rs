@@ -0,0 +1,294 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    body::Body,
+    http::{Request, StatusCode, header::CONTENT_TYPE},
+};
+use serde_json::json;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uj_ai_club_backend::{
+    AppState, create_router,
+    signing::{self, DELIVERY_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+};
+
+const SECRET: &str = "nbgrader-test-secret";
+
+fn signed_app(pool: PgPool) -> Router {
+    create_router(AppState {
+        config: Arc::new(common::test_config_with(&[(
+            "NBGRADER_WEBHOOK_SECRET",
+            SECRET,
+        )])),
+        ..common::app_state(pool, common::oauth_config("http://127.0.0.1:9"))
+    })
+}
+
+/// A student with an in-progress attempt; returns the webhook body reporting a score for it
+async fn pending_grade(pool: &PgPool) -> (uuid::Uuid, Vec<u8>) {
+    let (student_id, _) = common::create_user_with_role(pool, "user").await;
+    let (challenge_id, notebook_id) = common::create_challenge_with_notebook(pool, 100).await;
+    sqlx::query("UPDATE users SET jupyterhub_username = $1 WHERE id = $2")
+        .bind(format!("user_{}", student_id.simple()))
+        .bind(student_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    let submission_id = common::create_submission(
+        pool,
+        student_id,
+        challenge_id,
+        notebook_id,
+        1,
+        "in_progress",
+    )
+    .await;
+    let assignment_name: String =
+        sqlx::query_scalar("SELECT assignment_name FROM challenge_notebooks WHERE id = $1")
+            .bind(notebook_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    let body = json!({
+        "assignmentName": assignment_name,
+        "studentId": format!("user_{}", student_id.simple()),
+        "score": 9.0,
+        "maxScore": 10.0
+    });
+
+    (submission_id, body.to_string().into_bytes())
+}
+
+async fn deliver(app: Router, headers: &[(&str, String)], body: &[u8]) -> StatusCode {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/webhooks/nbgrader/grade")
+        .header(CONTENT_TYPE, "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, value);
+    }
+
+    app.oneshot(request.body(Body::from(body.to_vec())).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+fn signed_headers(body: &[u8], timestamp: i64, delivery_id: &str) -> Vec<(&'static str, String)> {
+    vec![
+        (SIGNATURE_HEADER, signing::sign(SECRET, timestamp, delivery_id, body)),
+        (TIMESTAMP_HEADER, timestamp.to_string()),
+        (DELIVERY_HEADER, delivery_id.to_string()),
+    ]
+}
+
+async fn submission_status(pool: &PgPool, submission_id: uuid::Uuid) -> String {
+    sqlx::query_scalar("SELECT status FROM challenge_submissions WHERE id = $1")
+        .bind(submission_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+async fn delivery_statuses(pool: &PgPool, delivery_id: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT status FROM webhook_deliveries WHERE source = 'nbgrader' AND delivery_id = $1 ORDER BY received_at",
+    )
+    .bind(delivery_id)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[test]
+fn signatures_cover_the_timestamp_delivery_and_body() {
+    let now = 1_700_000_000;
+    let body = br#"{"score":1}"#;
+    let delivery = "delivery-1";
+    let signature = signing::sign(SECRET, now, delivery, body);
+    let verify = |secret: &str, timestamp: &str, delivery_id: &str, body: &[u8], now: i64| {
+        signing::verify(
+            secret,
+            Some(&signature),
+            Some(timestamp),
+            Some(delivery_id),
+            body,
+            now,
+        )
+    };
+
+    assert!(verify(SECRET, "1700000000", delivery, body, now).is_ok());
+    assert_eq!(
+        verify(SECRET, "1700000001", delivery, body, now),
+        Err(signing::SignatureError::Mismatch)
+    );
+    // The same signature cannot be replayed under a fresh delivery ID
+    assert_eq!(
+        verify(SECRET, "1700000000", "delivery-2", body, now),
+        Err(signing::SignatureError::Mismatch)
+    );
+    assert_eq!(
+        verify(SECRET, "1700000000", delivery, b"{}", now),
+        Err(signing::SignatureError::Mismatch)
+    );
+    assert_eq!(
+        verify("other", "1700000000", delivery, body, now),
+        Err(signing::SignatureError::Mismatch)
+    );
+    assert_eq!(
+        verify(SECRET, "1700000000", delivery, body, now + 3600),
+        Err(signing::SignatureError::Stale)
+    );
+    for extreme in [i64::MIN, i64::MAX] {
+        assert_eq!(
+            verify(SECRET, &extreme.to_string(), delivery, body, now),
+            Err(signing::SignatureError::Stale)
+        );
+    }
+    assert_eq!(
+        signing::verify(SECRET, None, Some("1700000000"), Some(delivery), body, now),
+        Err(signing::SignatureError::Missing(SIGNATURE_HEADER))
+    );
+    assert_eq!(
+        signing::verify(SECRET, Some(&signature), Some("1700000000"), None, body, now),
+        Err(signing::SignatureError::Missing(DELIVERY_HEADER))
+    );
+}
+
+#[tokio::test]
+async fn signed_delivery_is_processed_once() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (submission_id, body) = pending_grade(&pool).await;
+    let delivery_id = uuid::Uuid::new_v4().to_string();
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+    let status = deliver(
+        signed_app(pool.clone()),
+        &signed_headers(&body, now, &delivery_id),
+        &body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        submission_status(&pool, submission_id).await,
+        "grading_pending"
+    );
+
+    // Replaying the exact same request is refused and logged
+    let status = deliver(
+        signed_app(pool.clone()),
+        &signed_headers(&body, now, &delivery_id),
+        &body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        delivery_statuses(&pool, &delivery_id).await,
+        vec!["processed", "rejected"]
+    );
+}
+
+#[tokio::test]
+async fn forged_stale_and_unsigned_deliveries_are_rejected() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (submission_id, body) = pending_grade(&pool).await;
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+    let forged_id = uuid::Uuid::new_v4().to_string();
+    let mut forged = signed_headers(&body, now, &forged_id);
+    forged[0].1 = signing::sign("not-the-secret", now, &forged_id, &body);
+    assert_eq!(
+        deliver(signed_app(pool.clone()), &forged, &body).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // A captured signature does not verify under a fresh delivery ID
+    let replayed_id = uuid::Uuid::new_v4().to_string();
+    let mut replayed = signed_headers(&body, now, &uuid::Uuid::new_v4().to_string());
+    replayed[2].1 = replayed_id.clone();
+    assert_eq!(
+        deliver(signed_app(pool.clone()), &replayed, &body).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let stale_id = uuid::Uuid::new_v4().to_string();
+    assert_eq!(
+        deliver(
+            signed_app(pool.clone()),
+            &signed_headers(&body, now - 3600, &stale_id),
+            &body
+        )
+        .await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    assert_eq!(
+        deliver(signed_app(pool.clone()), &[], &body).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    assert_eq!(submission_status(&pool, submission_id).await, "in_progress");
+    assert_eq!(delivery_statuses(&pool, &forged_id).await, vec!["rejected"]);
+    assert_eq!(delivery_statuses(&pool, &replayed_id).await, vec!["rejected"]);
+    assert_eq!(delivery_statuses(&pool, &stale_id).await, vec!["rejected"]);
+
+    let error: String = sqlx::query_scalar(
+        "SELECT error FROM webhook_deliveries WHERE delivery_id = $1 AND status = 'rejected'",
+    )
+    .bind(&stale_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(error.contains("outside the allowed window"), "{error}");
+}
+
+#[tokio::test]
+async fn failed_delivery_can_be_retried() {
+    let Some(pool) = common::test_pool().await else {
+        return;
+    };
+    let (submission_id, body) = pending_grade(&pool).await;
+    let delivery_id = uuid::Uuid::new_v4().to_string();
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+    // Reported for an assignment that does not exist yet: processing fails
+    let mut unknown: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let assignment_name = unknown["assignmentName"].as_str().unwrap().to_string();
+    unknown["assignmentName"] = json!(format!("{assignment_name}-missing"));
+    let unknown = unknown.to_string().into_bytes();
+    assert_eq!(
+        deliver(
+            signed_app(pool.clone()),
+            &signed_headers(&unknown, now, &delivery_id),
+            &unknown
+        )
+        .await,
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(
+        deliver(
+            signed_app(pool.clone()),
+            &signed_headers(&body, now, &delivery_id),
+            &body
+        )
+        .await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        submission_status(&pool, submission_id).await,
+        "grading_pending"
+    );
+    assert_eq!(
+        delivery_statuses(&pool, &delivery_id).await,
+        vec!["failed", "processed"]
+    );
+}